@@ -0,0 +1,153 @@
+-- Minimal cardano-db-sync schema subset with a few governance rows, for
+-- running the `dbsync` provider against a local PostgreSQL:
+--
+--   createdb dbsync_fixture && psql dbsync_fixture -f fixtures/dbsync.sql
+--   DBSYNC_URL=postgres://localhost/dbsync_fixture BLOCKFROST_NETWORK=preview \
+--     cargo run --features dbsync
+--
+-- Only the tables and columns the provider reads are created, with the
+-- db-sync column types simplified. Not a substitute for a real db-sync database.
+
+CREATE TYPE voterrole AS ENUM ('ConstitutionalCommittee', 'DRep', 'SPO');
+CREATE TYPE vote AS ENUM ('Yes', 'No', 'Abstain');
+CREATE TYPE govactiontype AS ENUM (
+    'ParameterChange', 'HardForkInitiation', 'TreasuryWithdrawals', 'NoConfidence',
+    'NewCommittee', 'NewConstitution', 'InfoAction'
+);
+
+CREATE TABLE meta (id bigserial PRIMARY KEY, start_time timestamp, network_name varchar, version varchar);
+CREATE TABLE epoch (id bigserial PRIMARY KEY, no integer, start_time timestamp, end_time timestamp);
+CREATE TABLE block (id bigserial PRIMARY KEY, hash bytea, epoch_no integer, slot_no bigint, block_no integer, time timestamp);
+CREATE TABLE tx (id bigserial PRIMARY KEY, hash bytea, block_id bigint);
+CREATE TABLE tx_out (id bigserial PRIMARY KEY, tx_id bigint, index smallint, stake_address_id bigint, value numeric(20, 0));
+CREATE TABLE tx_in (id bigserial PRIMARY KEY, tx_in_id bigint, tx_out_id bigint, tx_out_index smallint);
+CREATE TABLE stake_address (id bigserial PRIMARY KEY, hash_raw bytea, view varchar);
+CREATE TABLE pool_hash (id bigserial PRIMARY KEY, hash_raw bytea, view varchar);
+CREATE TABLE pool_update (id bigserial PRIMARY KEY, hash_id bigint, registered_tx_id bigint);
+CREATE TABLE pool_retire (id bigserial PRIMARY KEY, hash_id bigint, announced_tx_id bigint, retiring_epoch integer);
+CREATE TABLE off_chain_pool_data (id bigserial PRIMARY KEY, pool_id bigint, ticker_name varchar, json jsonb);
+CREATE TABLE delegation (id bigserial PRIMARY KEY, addr_id bigint, cert_index integer, pool_hash_id bigint, tx_id bigint);
+CREATE TABLE epoch_stake (id bigserial PRIMARY KEY, addr_id bigint, pool_id bigint, amount numeric(20, 0), epoch_no integer);
+CREATE TABLE reward (id bigserial PRIMARY KEY, addr_id bigint, amount numeric(20, 0), spendable_epoch integer);
+CREATE TABLE reward_rest (id bigserial PRIMARY KEY, addr_id bigint, amount numeric(20, 0), spendable_epoch integer);
+CREATE TABLE withdrawal (id bigserial PRIMARY KEY, addr_id bigint, amount numeric(20, 0), tx_id bigint);
+CREATE TABLE voting_anchor (id bigserial PRIMARY KEY, url varchar, data_hash bytea);
+CREATE TABLE off_chain_vote_data (
+    id bigserial PRIMARY KEY, voting_anchor_id bigint, json jsonb, language varchar,
+    comment varchar, is_valid boolean
+);
+CREATE TABLE off_chain_vote_drep_data (
+    id bigserial PRIMARY KEY, off_chain_vote_data_id bigint, payment_address varchar,
+    given_name varchar, objectives varchar, motivations varchar, qualifications varchar,
+    image_url varchar, image_hash varchar
+);
+CREATE TABLE drep_hash (id bigserial PRIMARY KEY, raw bytea, view varchar, has_script boolean);
+CREATE TABLE drep_registration (
+    id bigserial PRIMARY KEY, tx_id bigint, cert_index integer, deposit bigint,
+    drep_hash_id bigint, voting_anchor_id bigint
+);
+CREATE TABLE drep_distr (id bigserial PRIMARY KEY, hash_id bigint, amount numeric(20, 0), epoch_no integer, active_until integer);
+CREATE TABLE delegation_vote (id bigserial PRIMARY KEY, addr_id bigint, cert_index integer, drep_hash_id bigint, tx_id bigint);
+CREATE TABLE gov_action_proposal (
+    id bigserial PRIMARY KEY, tx_id bigint, index bigint, deposit numeric(20, 0),
+    return_address bigint, expiration integer, voting_anchor_id bigint, type govactiontype,
+    description jsonb, ratified_epoch integer, enacted_epoch integer, dropped_epoch integer,
+    expired_epoch integer
+);
+CREATE TABLE treasury_withdrawal (id bigserial PRIMARY KEY, gov_action_proposal_id bigint, stake_address_id bigint, amount numeric(20, 0));
+CREATE TABLE committee_hash (id bigserial PRIMARY KEY, raw bytea, has_script boolean);
+CREATE TABLE committee (id bigserial PRIMARY KEY, gov_action_proposal_id bigint);
+CREATE TABLE committee_member (id bigserial PRIMARY KEY, committee_id bigint, committee_hash_id bigint, expiration_epoch integer);
+CREATE TABLE committee_registration (id bigserial PRIMARY KEY, tx_id bigint, cert_index integer, cold_key_id bigint, hot_key_id bigint);
+CREATE TABLE committee_de_registration (id bigserial PRIMARY KEY, tx_id bigint, cert_index integer, cold_key_id bigint);
+CREATE TABLE voting_procedure (
+    id bigserial PRIMARY KEY, tx_id bigint, index integer, gov_action_proposal_id bigint,
+    voter_role voterrole, committee_voter bigint, drep_voter bigint, pool_voter bigint, vote vote
+);
+
+INSERT INTO meta (start_time, network_name, version) VALUES ('2022-10-25 00:00:00', 'preview', '13.5.0.0');
+
+INSERT INTO epoch (no, start_time, end_time) VALUES
+    (700, '2024-09-20 00:00:00', '2024-09-21 00:00:00'),
+    (701, '2024-09-21 00:00:00', '2024-09-22 00:00:00');
+
+INSERT INTO block (hash, epoch_no, slot_no, block_no, time) VALUES
+    (sha256('block1'), 700, 60480000, 2500000, '2024-09-20 01:00:00'),
+    (sha256('block2'), 701, 60566400, 2504000, '2024-09-21 02:00:00');
+
+INSERT INTO tx (hash, block_id) VALUES
+    (sha256('tx-drep-a'), 1),   -- 1: DRep A registration
+    (sha256('tx-drep-b'), 1),   -- 2: DRep B registration
+    (sha256('tx-proposal'), 1), -- 3: treasury withdrawal proposal
+    (sha256('tx-vote-a'), 2),   -- 4: DRep A votes
+    (sha256('tx-vote-pool'), 2),-- 5: pool votes
+    (sha256('tx-deleg'), 1),    -- 6: stake delegations
+    (sha256('tx-pool-reg'), 1), -- 7: pool registration
+    (sha256('tx-cc'), 1),       -- 8: committee hot key authorization
+    (sha256('tx-vote-cc'), 2),  -- 9: committee votes
+    (sha256('tx-drep-b-ret'), 2); -- 10: DRep B retires
+
+INSERT INTO stake_address (hash_raw, view) VALUES
+    (decode('e08f448a947d3413e6fc85f9a7f0d27b7502579eb1f7f7d44e4eb0de7a', 'hex'), 'stake_test1uz85fz55056p8ehushu60uxj0d6sy4u7k8ml04zwf6cdu7szkju4w'),
+    (decode('e00da6a24a8f58c8dcb87147322b07a9833b7f9c99cb5dd419d92b5374', 'hex'), 'stake_test1uqx6dgj23avv3h9cw9rny2c84xpnkluun894m4qemy44xaqnfwt9d');
+
+INSERT INTO tx_out (tx_id, index, stake_address_id, value) VALUES
+    (6, 0, 1, 5000000000),
+    (6, 1, 2, 1200000000),
+    (6, 2, 1, 300000000);
+INSERT INTO tx_in (tx_in_id, tx_out_id, tx_out_index) VALUES (4, 6, 2);
+
+INSERT INTO pool_hash (hash_raw, view) VALUES
+    (decode('2fb4dbf59739df95b252c8f1bbf4464acd5ecb67fecaea0fb5774bec', 'hex'), 'pool1976dhavh880etvjjercmhazxftx4ajm8lm9w5ra4wa97cxjnqxm');
+INSERT INTO pool_update (hash_id, registered_tx_id) VALUES (1, 7);
+INSERT INTO off_chain_pool_data (pool_id, ticker_name, json) VALUES
+    (1, 'FIXT', '{"name": "Fixture Pool", "description": "Seeded for local testing", "homepage": "https://example.com"}');
+
+INSERT INTO delegation (addr_id, cert_index, pool_hash_id, tx_id) VALUES (1, 0, 1, 6), (2, 1, 1, 6);
+INSERT INTO epoch_stake (addr_id, pool_id, amount, epoch_no) VALUES
+    (1, 1, 5000000000, 701),
+    (2, 1, 1200000000, 701);
+INSERT INTO reward (addr_id, amount, spendable_epoch) VALUES (1, 25000000, 700);
+INSERT INTO withdrawal (addr_id, amount, tx_id) VALUES (1, 5000000, 4);
+
+INSERT INTO voting_anchor (url, data_hash) VALUES
+    ('https://example.com/drep-a.jsonld', sha256('drep-a-metadata')),
+    ('https://example.com/proposal.jsonld', sha256('proposal-metadata'));
+INSERT INTO off_chain_vote_data (voting_anchor_id, json, language, comment, is_valid) VALUES
+    (1, '{"body": {"givenName": "Fixture DRep"}}', 'en', NULL, true),
+    (2, '{"body": {"title": "Fund the fixture", "abstract": "A seeded treasury withdrawal"}}', 'en', NULL, true);
+INSERT INTO off_chain_vote_drep_data (off_chain_vote_data_id, given_name, objectives) VALUES
+    (1, 'Fixture DRep', 'Exercise the db-sync provider');
+
+INSERT INTO drep_hash (raw, view, has_script) VALUES
+    (decode('27228b027fa9c6f5cb5beacef67d70599eb8b720ddd2089e940a774d', 'hex'), 'drep1yu3gkqnl48r0tj6mat80vltstx0t3deqmhfq3855pfm56dcf8xv', false),
+    (decode('3e944d5a7e7489765e836e72a27dcf0d2ae61345d8fde93307874dff', 'hex'), 'drep1862y6kn7wjyhvh5rdee2ylw0p54wvy69mr77jvc8saxl7euewpw', false),
+    (NULL, 'drep_always_abstain', false),
+    (NULL, 'drep_always_no_confidence', false);
+INSERT INTO drep_registration (tx_id, cert_index, deposit, drep_hash_id, voting_anchor_id) VALUES
+    (1, 0, 500000000, 1, 1),
+    (2, 0, 500000000, 2, NULL),
+    (10, 0, -500000000, 2, NULL);
+INSERT INTO drep_distr (hash_id, amount, epoch_no, active_until) VALUES
+    (1, 5000000000, 701, 720),
+    (2, 1200000000, 701, 690),
+    (3, 800000000, 701, NULL),
+    (4, 100000000, 701, NULL);
+INSERT INTO delegation_vote (addr_id, cert_index, drep_hash_id, tx_id) VALUES (1, 2, 1, 6), (2, 3, 2, 6);
+
+INSERT INTO gov_action_proposal (tx_id, index, deposit, return_address, expiration, voting_anchor_id, type, description) VALUES
+    (3, 0, 100000000000, 1, 706, 2, 'TreasuryWithdrawals', '{"tag": "TreasuryWithdrawals"}');
+INSERT INTO treasury_withdrawal (gov_action_proposal_id, stake_address_id, amount) VALUES (1, 2, 250000000000);
+
+INSERT INTO committee_hash (raw, has_script) VALUES
+    (decode('e1c554243a0c006d50dcb196a8e6e3f25927cbc9e255b7d6055dae1b', 'hex'), false),
+    (decode('81de86639f5905a6c0c3063f69d933f2fa3c06de2fa935bb42e78c34', 'hex'), false);
+INSERT INTO committee (gov_action_proposal_id) VALUES (NULL);
+INSERT INTO committee_member (committee_id, committee_hash_id, expiration_epoch) VALUES (1, 1, 800);
+INSERT INTO committee_registration (tx_id, cert_index, cold_key_id, hot_key_id) VALUES (8, 0, 1, 2);
+
+INSERT INTO voting_procedure (tx_id, index, gov_action_proposal_id, voter_role, committee_voter, drep_voter, pool_voter, vote) VALUES
+    (4, 0, 1, 'DRep', NULL, 1, NULL, 'No'),
+    (4, 1, 1, 'DRep', NULL, 1, NULL, 'Yes'),
+    (5, 0, 1, 'SPO', NULL, NULL, 1, 'Abstain'),
+    (9, 0, 1, 'ConstitutionalCommittee', 2, NULL, NULL, 'Yes');
@@ -0,0 +1,56 @@
+#!/usr/bin/env python3
+"""Replays the recorded Ogmios frames in frames.json over HTTP JSON-RPC.
+
+    python3 fixtures/ogmios/standin.py [port]   # default 1337
+"""
+import json
+import sys
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+
+FRAMES = json.loads((Path(__file__).parent / "frames.json").read_text())
+
+
+def matches(entry, params):
+    # Apply the proposal and DRep filters so unknown ids come back empty
+    proposals = params.get("proposals")
+    if proposals is not None and "proposal" in entry:
+        return any(
+            p["transaction"]["id"] == entry["proposal"]["transaction"]["id"]
+            and p["index"] == entry["proposal"]["index"]
+            for p in proposals
+        )
+    credentials = params.get("keys", []) + params.get("scripts", [])
+    if credentials and "type" in entry:
+        return entry.get("id") in credentials
+    return True
+
+
+class Handler(BaseHTTPRequestHandler):
+    def do_POST(self):
+        request = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+        frame = FRAMES.get(request.get("method"))
+        if frame is None:
+            frame = {
+                "jsonrpc": "2.0",
+                "method": request.get("method"),
+                "error": {"code": -32601, "message": "Method not found"},
+            }
+        else:
+            frame = dict(frame)
+            if isinstance(frame["result"], list):
+                params = request.get("params") or {}
+                frame["result"] = [e for e in frame["result"] if matches(e, params)]
+        frame["id"] = request.get("id")
+
+        body = json.dumps(frame).encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+
+if __name__ == "__main__":
+    port = int(sys.argv[1]) if len(sys.argv) > 1 else 1337
+    HTTPServer(("127.0.0.1", port), Handler).serve_forever()
@@ -1,8 +1,8 @@
+use crate::api::error::ApiError;
 use crate::models::*;
 use crate::providers::CachedProviderRouter;
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{Path, Query},
     response::Json,
 };
 use serde::Deserialize;
@@ -16,9 +16,9 @@ pub struct ActionsQueryParams {
 }
 
 pub async fn get_actions(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Query(params): Query<ActionsQueryParams>,
-) -> Result<Json<ActionsPage>, StatusCode> {
+) -> Result<Json<ActionsPage>, ApiError> {
     let page = params.page.unwrap_or(1);
     let count = params.count.unwrap_or(20);
 
@@ -26,51 +26,47 @@ pub async fn get_actions(
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             tracing::error!("Error fetching governance actions: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_action(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<Option<GovernanceAction>>, StatusCode> {
+) -> Result<Json<Option<GovernanceAction>>, ApiError> {
     match router.get_governance_action(&id).await {
         Ok(Some(action)) => Ok(Json(Some(action))),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Ok(None) => Err(ApiError::not_found(format!("Governance action {}", id))),
         Err(e) => {
             tracing::error!("Error fetching governance action: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_action_votes(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<ActionVotingBreakdown>, StatusCode> {
+) -> Result<Json<ActionVotingBreakdown>, ApiError> {
     match router.get_action_voting_results(&id).await {
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             tracing::error!("Error fetching action voting results: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_action_participation(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<ActionVoterParticipation>, StatusCode> {
+) -> Result<Json<ActionVoterParticipation>, ApiError> {
     match router.get_action_voter_participation(&id).await {
         Ok(result) => Ok(Json(result)),
-        Err(error) => {
-            tracing::error!("Error fetching action participation {}: {}", id, error);
-            if error.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+        Err(e) => {
+            tracing::error!("Error fetching action participation {}: {}", id, e);
+            Err(e.into())
         }
     }
 }
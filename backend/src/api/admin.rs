@@ -0,0 +1,432 @@
+use crate::api::error::ApiError;
+use crate::cache::keys::CacheKeyKind;
+use crate::cache::CacheEntry;
+use crate::models::common::ErrorResponse;
+use crate::providers::{CachedProviderRouter, ConsistencyKind, ConsistencyReport};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// Cache administration and provider diagnostics routes, all behind the
+/// admin bearer token.
+pub fn router(token: &str) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/admin/cache",
+            get(list_cache_entries).delete(invalidate_cache),
+        )
+        .route("/admin/cache/entry", get(get_cache_entry))
+        .route("/admin/consistency/:kind/:id", get(check_consistency))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            require_admin_token,
+        ))
+}
+
+/// Rejects requests without `Authorization: Bearer <ADMIN_API_TOKEN>`.
+pub async fn require_admin_token(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match presented {
+        Some(presented) if constant_time_eq(presented.as_bytes(), token.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "missing or invalid admin token".to_string(),
+                code: "unauthorized".to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheEntryInfo {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CacheKeyKind>,
+    pub size_bytes: usize,
+    pub age_secs: u64,
+    pub ttl_secs: u64,
+    /// Seconds until the entry goes stale
+    pub remaining_secs: u64,
+    pub stale_for_secs: u64,
+    /// Seconds until the entry can no longer be served at all
+    pub expires_in_secs: u64,
+    pub fresh: bool,
+}
+
+impl CacheEntryInfo {
+    fn new(key: String, entry: &CacheEntry) -> Self {
+        Self {
+            kind: CacheKeyKind::of(&key),
+            key,
+            size_bytes: entry.bytes.len(),
+            age_secs: entry.age().as_secs(),
+            ttl_secs: entry.ttl.as_secs(),
+            remaining_secs: entry.remaining().as_secs(),
+            stale_for_secs: entry.stale_for.as_secs(),
+            expires_in_secs: entry.expires_in().as_secs(),
+            fresh: entry.is_fresh(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    #[serde(default)]
+    pub prefix: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheListing {
+    pub prefix: String,
+    pub total: usize,
+    pub entries: Vec<CacheEntryInfo>,
+}
+
+pub async fn list_cache_entries(
+    router: CachedProviderRouter,
+    Query(params): Query<ListParams>,
+) -> Json<CacheListing> {
+    let entries = router.cache().entries(&params.prefix);
+    let total = entries.len();
+    let entries = entries
+        .into_iter()
+        .take(params.limit.unwrap_or(DEFAULT_LIST_LIMIT))
+        .map(|(key, entry)| CacheEntryInfo::new(key, &entry))
+        .collect();
+
+    Json(CacheListing {
+        prefix: params.prefix,
+        total,
+        entries,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntryParams {
+    pub key: String,
+}
+
+pub async fn get_cache_entry(
+    router: CachedProviderRouter,
+    Query(params): Query<EntryParams>,
+) -> Result<Json<CacheEntryInfo>, ApiError> {
+    match router.cache().inspect(&params.key).await {
+        Some(entry) => Ok(Json(CacheEntryInfo::new(params.key, &entry))),
+        None => Err(ApiError::not_found(format!("Cache entry {}", params.key))),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvalidateParams {
+    pub key: Option<String>,
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub all: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvalidateResult {
+    pub scope: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// In-memory entries dropped; L2 entries are dropped too but not counted
+    pub invalidated: usize,
+}
+
+pub async fn invalidate_cache(
+    router: CachedProviderRouter,
+    Query(params): Query<InvalidateParams>,
+) -> Result<Json<InvalidateResult>, ApiError> {
+    let cache = router.cache();
+    let result = match params {
+        InvalidateParams { key: Some(key), .. } => InvalidateResult {
+            scope: "key",
+            invalidated: cache.invalidate(&key).await as usize,
+            target: Some(key),
+        },
+        InvalidateParams {
+            prefix: Some(prefix),
+            ..
+        } if !prefix.is_empty() => InvalidateResult {
+            scope: "prefix",
+            invalidated: cache.invalidate_prefix(&prefix).await,
+            target: Some(prefix),
+        },
+        InvalidateParams { all: true, .. } => {
+            let invalidated = cache.entry_count();
+            cache.clear().await;
+            InvalidateResult {
+                scope: "all",
+                target: None,
+                invalidated,
+            }
+        }
+        _ => {
+            return Err(ApiError::invalid_input(
+                "specify key, a non-empty prefix, or all=true",
+            ))
+        }
+    };
+
+    tracing::info!(
+        "Admin cache invalidation ({}{}): {} entries",
+        result.scope,
+        result
+            .target
+            .as_deref()
+            .map(|target| format!(" {}", target))
+            .unwrap_or_default(),
+        result.invalidated
+    );
+    Ok(Json(result))
+}
+
+/// Fetches one entity from both Koios and Blockfrost and returns how their
+/// answers differ.
+pub async fn check_consistency(
+    router: CachedProviderRouter,
+    Path((kind, id)): Path<(String, String)>,
+) -> Result<Json<ConsistencyReport>, ApiError> {
+    let kind = ConsistencyKind::parse(&kind).ok_or_else(|| {
+        ApiError::invalid_input(format!(
+            "unknown kind {}; expected drep, action or votes",
+            kind
+        ))
+    })?;
+    Ok(Json(router.check_consistency(kind, &id).await))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys::CacheKey;
+    use crate::cache::CacheManager;
+    use crate::network::Network;
+    use crate::providers::routing::{ProviderKind, RoutingPolicy};
+    use crate::providers::testing::StubProvider;
+    use crate::providers::ProviderRouter;
+    use crate::state::NetworkState;
+    use axum::body::Body;
+    use axum::http::{Method, Request as HttpRequest};
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "s3cret";
+
+    async fn app() -> (Router, CachedProviderRouter) {
+        let router = ProviderRouter::stubbed(
+            RoutingPolicy::default(),
+            &[(ProviderKind::Koios, Arc::new(StubProvider::new()))],
+        );
+        let cache = CacheManager::new(true, 100, HashMap::new(), None);
+        let router = CachedProviderRouter::new(router, cache, None, None, Network::Preview);
+        for id in ["drep1a", "drep1b"] {
+            let id = id.to_string();
+            router
+                .cache()
+                .set(&CacheKey::DRep { id: id.clone() }, &"drep")
+                .await;
+            router
+                .cache()
+                .set(&CacheKey::DRepMetadata { id }, &"metadata")
+                .await;
+        }
+        router.cache().set(&CacheKey::DRepStats, &"stats").await;
+
+        let state = AppState {
+            default_network: Network::Preview,
+            networks: Arc::new(HashMap::from([(
+                Network::Preview,
+                NetworkState {
+                    router: router.clone(),
+                    warmer: Arc::default(),
+                },
+            )])),
+        };
+        (super::router(TOKEN).with_state(state), router)
+    }
+
+    async fn send(
+        app: &Router,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+    ) -> (StatusCode, Value) {
+        let mut request = HttpRequest::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn keys(listing: &Value) -> Vec<&str> {
+        listing["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["key"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rejects_a_missing_or_wrong_token() {
+        let (app, router) = app().await;
+        for token in [None, Some("wrong"), Some("s3cre"), Some("")] {
+            for (method, uri) in [
+                (Method::GET, "/admin/cache"),
+                (Method::GET, "/admin/cache/entry?key=dreps_stats"),
+                (Method::DELETE, "/admin/cache?all=true"),
+            ] {
+                let (status, body) = send(&app, method, uri, token).await;
+                assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {:?}", uri, token);
+                assert_eq!(body["code"], "unauthorized");
+            }
+        }
+        assert_eq!(router.cache().entries("").len(), 5);
+    }
+
+    #[tokio::test]
+    async fn lists_entries_by_prefix() {
+        let (app, _) = app().await;
+
+        let (status, listing) =
+            send(&app, Method::GET, "/admin/cache?prefix=drep:", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listing["prefix"], "drep:");
+        assert_eq!(listing["total"], 2);
+        assert_eq!(keys(&listing), ["drep:drep1a", "drep:drep1b"]);
+        assert_eq!(listing["entries"][0]["kind"], "drep");
+
+        let (_, listing) = send(&app, Method::GET, "/admin/cache?limit=1", Some(TOKEN)).await;
+        assert_eq!(listing["total"], 5);
+        assert_eq!(keys(&listing).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn shows_an_entry_age_and_remaining_ttl() {
+        let (app, router) = app().await;
+        let key = CacheKey::DRepStats.to_string();
+        let mut entry = router.cache().inspect(&key).await.unwrap();
+        entry.inserted_at -= Duration::from_secs(10);
+        router.cache().restore(&key, &entry).await;
+
+        let (status, info) = send(
+            &app,
+            Method::GET,
+            "/admin/cache/entry?key=dreps_stats",
+            Some(TOKEN),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let ttl = CacheKey::DRepStats.ttl_seconds();
+        assert_eq!(info["key"], "dreps_stats");
+        assert_eq!(info["ttl_secs"], ttl);
+        assert!((10..=11).contains(&info["age_secs"].as_u64().unwrap()));
+        let remaining = info["remaining_secs"].as_u64().unwrap();
+        assert!((ttl - 11..=ttl - 10).contains(&remaining), "{}", remaining);
+        assert_eq!(info["fresh"], true);
+
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            "/admin/cache/entry?key=drep:drep1z",
+            Some(TOKEN),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn invalidates_a_key_a_prefix_or_everything() {
+        let (app, router) = app().await;
+        let negative = CacheKey::DRep {
+            id: "drep1a".to_string(),
+        }
+        .negative()
+        .unwrap();
+        router.cache().set(&negative, &"absent").await;
+
+        let (status, result) = send(
+            &app,
+            Method::DELETE,
+            "/admin/cache?key=drep:drep1a",
+            Some(TOKEN),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result["scope"], "key");
+        assert_eq!(result["invalidated"], 1);
+        assert!(router.cache().inspect("drep:drep1a").await.is_none());
+        assert!(router.cache().inspect("drep:drep1b").await.is_some());
+        // Its negative entry and metadata go with it
+        assert!(router
+            .cache()
+            .inspect("negative:drep:drep1a")
+            .await
+            .is_none());
+        assert!(router
+            .cache()
+            .inspect("drep_metadata:drep1a")
+            .await
+            .is_none());
+
+        let (_, result) = send(
+            &app,
+            Method::DELETE,
+            "/admin/cache?prefix=drep_metadata:",
+            Some(TOKEN),
+        )
+        .await;
+        assert_eq!(result["scope"], "prefix");
+        assert_eq!(result["invalidated"], 1);
+        assert!(router.cache().entries("drep_metadata:").is_empty());
+        assert!(router.cache().inspect("dreps_stats").await.is_some());
+
+        let (status, _) = send(&app, Method::DELETE, "/admin/cache", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, result) = send(&app, Method::DELETE, "/admin/cache?all=true", Some(TOKEN)).await;
+        assert_eq!(result["scope"], "all");
+        assert!(router.cache().inspect("drep:drep1b").await.is_none());
+        assert!(router.cache().inspect("dreps_stats").await.is_none());
+    }
+}
@@ -1,8 +1,8 @@
+use crate::api::error::ApiError;
 use crate::models::*;
 use crate::providers::CachedProviderRouter;
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{Path, Query},
     response::Json,
 };
 use serde::Deserialize;
@@ -46,76 +46,74 @@ impl DRepsQueryParams {
 }
 
 pub async fn get_dreps(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Query(params): Query<DRepsQueryParams>,
-) -> Result<Json<DRepsPage>, StatusCode> {
+) -> Result<Json<DRepsPage>, ApiError> {
     let query = params.into_query();
 
     match router.get_dreps_page(&query).await {
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             tracing::error!("Error fetching DReps: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_drep(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<Option<DRep>>, StatusCode> {
+) -> Result<Json<Option<DRep>>, ApiError> {
     match router.get_drep(&id).await {
         Ok(Some(drep)) => Ok(Json(Some(drep))),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Ok(None) => Err(ApiError::not_found(format!("DRep {}", id))),
         Err(e) => {
             tracing::error!("Error fetching DRep: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_drep_delegators(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<Vec<DRepDelegator>>, StatusCode> {
+) -> Result<Json<Vec<DRepDelegator>>, ApiError> {
     match router.get_drep_delegators(&id).await {
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             tracing::error!("Error fetching DRep delegators: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_drep_votes(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<Vec<DRepVotingHistory>>, StatusCode> {
+) -> Result<Json<Vec<DRepVotingHistory>>, ApiError> {
     match router.get_drep_voting_history(&id).await {
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             tracing::error!("Error fetching DRep voting history: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
 pub async fn get_drep_metadata(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(id): Path<String>,
-) -> Result<Json<Option<Value>>, StatusCode> {
+) -> Result<Json<Option<Value>>, ApiError> {
     match router.get_drep_metadata(&id).await {
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             tracing::error!("Error fetching DRep metadata: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
 
-pub async fn get_drep_stats(
-    State(router): State<CachedProviderRouter>,
-) -> Result<Json<DRepStats>, StatusCode> {
+pub async fn get_drep_stats(router: CachedProviderRouter) -> Result<Json<DRepStats>, ApiError> {
     match router.get_drep_stats().await {
         Ok(stats) => Ok(Json(stats)),
         Err(e) => {
@@ -0,0 +1,62 @@
+use crate::models::common::ErrorResponse;
+use crate::providers::ProviderError;
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+/// Handler error rendered as an `ErrorResponse` with a status code matching
+/// the provider failure.
+#[derive(Debug)]
+pub struct ApiError(ProviderError);
+
+impl ApiError {
+    pub fn not_found(what: impl Into<String>) -> Self {
+        Self(ProviderError::NotFound(what.into()))
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self(ProviderError::InvalidInput(message.into()))
+    }
+}
+
+impl From<ProviderError> for ApiError {
+    fn from(error: ProviderError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, code) = match &self.0 {
+            ProviderError::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+            ProviderError::InvalidInput(_) => (StatusCode::BAD_REQUEST, "invalid_input"),
+            ProviderError::RateLimited { .. } => (StatusCode::SERVICE_UNAVAILABLE, "rate_limited"),
+            ProviderError::Unavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+            ProviderError::NotRecorded(_) => (StatusCode::SERVICE_UNAVAILABLE, "not_recorded"),
+            ProviderError::Timeout => (StatusCode::GATEWAY_TIMEOUT, "upstream_timeout"),
+            ProviderError::Upstream { .. }
+            | ProviderError::Transport(_)
+            | ProviderError::Decode(_) => (StatusCode::BAD_GATEWAY, "upstream_error"),
+        };
+
+        let body = ErrorResponse {
+            error: self.0.to_string(),
+            code: code.to_string(),
+        };
+        let mut response = (status, Json(body)).into_response();
+
+        if let ProviderError::RateLimited {
+            retry_after: Some(retry_after),
+        } = &self.0
+        {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(retry_after.as_secs().max(1)),
+            );
+        }
+
+        response
+    }
+}
@@ -1,42 +1,77 @@
-use crate::providers::CachedProviderRouter;
+use crate::state::{AppState, NetworkState};
 use axum::{extract::State, http::StatusCode, response::Json};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 
-pub async fn health_check(
-    State(router): State<CachedProviderRouter>,
-) -> Result<Json<Value>, StatusCode> {
+/// Health of the default network at the top level, and of every served
+/// network under `networks`.
+pub async fn health_check(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let mut networks = Map::new();
+    for (network, network_state) in state.networks() {
+        networks.insert(network.to_string(), network_health(network_state).await);
+    }
+
+    let mut body = networks
+        .get(state.default_network.as_str())
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    body["networks"] = Value::Object(networks);
+    Ok(Json(body))
+}
+
+async fn network_health(network: &NetworkState) -> Value {
+    let router = &network.router;
     let is_healthy = router.health_check().await.unwrap_or(false);
     let cache_stats = router.cache_stats().await;
+    let warmer = network.warmer.snapshot();
+    let by_kind: Map<String, Value> = cache_stats
+        .by_kind
+        .iter()
+        .map(|stats| (stats.kind.to_string(), json!(stats)))
+        .collect();
 
-    if is_healthy {
-        Ok(Json(json!({
-            "status": "healthy",
-            "providers": {
-                "blockfrost": "ok",
-                "koios": "ok"
-            },
-            "cache": {
-                "enabled": cache_stats.enabled,
-                "entries": cache_stats.entries,
-                "hits": cache_stats.hits,
-                "misses": cache_stats.misses,
-                "hit_rate": format!("{:.2}%", cache_stats.hit_rate)
-            }
-        })))
+    // Circuit breaker state per provider, so it's visible why traffic moved
+    let circuits: Map<String, Value> = router
+        .provider_health()
+        .into_iter()
+        .map(|snapshot| (snapshot.name.to_string(), json!(snapshot)))
+        .collect();
+
+    // Request usage and endpoint per provider, to watch paid-tier quotas and
+    // spot a provider configured for the wrong network
+    let quotas: Map<String, Value> = router
+        .provider_quotas()
+        .into_iter()
+        .map(|snapshot| (snapshot.name.to_string(), json!(snapshot)))
+        .collect();
+
+    let (status, provider_status) = if is_healthy {
+        ("healthy", "ok")
     } else {
-        Ok(Json(json!({
-            "status": "degraded",
-            "providers": {
-                "blockfrost": "unknown",
-                "koios": "unknown"
-            },
-            "cache": {
-                "enabled": cache_stats.enabled,
-                "entries": cache_stats.entries,
-                "hits": cache_stats.hits,
-                "misses": cache_stats.misses,
-                "hit_rate": format!("{:.2}%", cache_stats.hit_rate)
-            }
-        })))
+        ("degraded", "unknown")
+    };
+
+    let mut body = json!({
+        "status": status,
+        "network": router.network(),
+        "providers": {
+            "blockfrost": provider_status,
+            "koios": provider_status
+        },
+        "circuits": circuits,
+        "quotas": quotas,
+        "cache": {
+            "enabled": cache_stats.enabled,
+            "entries": cache_stats.entries,
+            "l2": cache_stats.l2,
+            "hits": cache_stats.hits,
+            "misses": cache_stats.misses,
+            "hit_rate": format!("{:.2}%", cache_stats.hit_rate),
+            "by_kind": by_kind,
+            "warmer": warmer
+        }
+    });
+    if let Some(index) = router.index() {
+        body["indexer"] = json!(index.status());
     }
+    body
 }
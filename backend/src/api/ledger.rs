@@ -0,0 +1,18 @@
+use crate::api::error::ApiError;
+use crate::models::LedgerState;
+use crate::providers::CachedProviderRouter;
+use axum::Json;
+
+pub async fn get_ledger_state(router: CachedProviderRouter) -> Result<Json<LedgerState>, ApiError> {
+    match router.get_ledger_state().await {
+        Ok(Some(state)) => Ok(Json(state)),
+        Ok(None) => Err(ApiError::not_found(format!(
+            "Ledger state for {} (OGMIOS_URL not configured)",
+            router.network()
+        ))),
+        Err(e) => {
+            tracing::error!("Error fetching ledger state: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::cache::metrics::KindStats;
+use crate::providers::cached_router::CacheStats;
+use crate::state::AppState;
+use axum::{extract::State, http::header, response::IntoResponse};
+use std::fmt::Write;
+
+/// Cache and provider consistency metrics in the Prometheus text exposition
+/// format, labelled with the network each cache serves.
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let mut stats = Vec::new();
+    let mut consistency = Vec::new();
+    for (network, network_state) in state.networks() {
+        stats.push((
+            network.to_string(),
+            network_state.router.cache_stats().await,
+        ));
+        consistency.push((
+            network.to_string(),
+            network_state.router.consistency_stats(),
+        ));
+    }
+    let mut body = String::new();
+
+    describe(
+        &mut body,
+        "govtwool_cache_entries",
+        "gauge",
+        "Entries held in the in-memory cache.",
+    );
+    for (network, stats) in &stats {
+        let _ = writeln!(
+            body,
+            "govtwool_cache_entries{{network=\"{}\"}} {}",
+            network, stats.entries
+        );
+    }
+
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_hits_total",
+        "counter",
+        "Lookups answered with a fresh entry.",
+        |s| s.hits,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_stale_hits_total",
+        "counter",
+        "Lookups answered with a stale entry while it was refreshed.",
+        |s| s.stale_hits,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_misses_total",
+        "counter",
+        "Lookups that had to go to the providers.",
+        |s| s.misses,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_inserts_total",
+        "counter",
+        "Values written to the cache.",
+        |s| s.inserts,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_evictions_total",
+        "counter",
+        "Entries dropped on expiry or to stay within CACHE_MAX_ENTRIES.",
+        |s| s.evictions,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_invalidations_total",
+        "counter",
+        "Entries removed by invalidation.",
+        |s| s.invalidations,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_kind_entries",
+        "gauge",
+        "Entries held in the in-memory cache, by key kind.",
+        |s| s.entries,
+    );
+    per_kind(
+        &mut body,
+        &stats,
+        "govtwool_cache_kind_bytes",
+        "gauge",
+        "Serialized bytes held in the in-memory cache, by key kind.",
+        |s| s.bytes,
+    );
+
+    describe(
+        &mut body,
+        "govtwool_consistency_checks_total",
+        "counter",
+        "Koios/Blockfrost consistency checks where both providers answered.",
+    );
+    for (network, kinds) in &consistency {
+        for (kind, counts) in kinds {
+            let _ = writeln!(
+                body,
+                "govtwool_consistency_checks_total{{network=\"{}\",kind=\"{}\"}} {}",
+                network, kind, counts.checks
+            );
+        }
+    }
+    describe(
+        &mut body,
+        "govtwool_consistency_mismatches_total",
+        "counter",
+        "Consistency checks where the providers disagreed on at least one field.",
+    );
+    for (network, kinds) in &consistency {
+        for (kind, counts) in kinds {
+            let _ = writeln!(
+                body,
+                "govtwool_consistency_mismatches_total{{network=\"{}\",kind=\"{}\"}} {}",
+                network, kind, counts.inconsistent
+            );
+        }
+    }
+    describe(
+        &mut body,
+        "govtwool_consistency_field_mismatches_total",
+        "counter",
+        "Fields the providers disagreed on, with array indices collapsed to [].",
+    );
+    for (network, kinds) in &consistency {
+        for (kind, counts) in kinds {
+            for (field, count) in &counts.fields {
+                let _ = writeln!(
+                    body,
+                    "govtwool_consistency_field_mismatches_total{{network=\"{}\",kind=\"{}\",field=\"{}\"}} {}",
+                    network, kind, field, count
+                );
+            }
+        }
+    }
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+fn describe(body: &mut String, name: &str, metric_type: &str, help: &str) {
+    let _ = writeln!(body, "# HELP {} {}", name, help);
+    let _ = writeln!(body, "# TYPE {} {}", name, metric_type);
+}
+
+fn per_kind(
+    body: &mut String,
+    networks: &[(String, CacheStats)],
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    value: impl Fn(&KindStats) -> u64,
+) {
+    describe(body, name, metric_type, help);
+    for (network, stats) in networks {
+        for kind_stats in &stats.by_kind {
+            let _ = writeln!(
+                body,
+                "{}{{network=\"{}\",kind=\"{}\"}} {}",
+                name,
+                network,
+                kind_stats.kind,
+                value(kind_stats)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys::CacheKey;
+    use crate::cache::CacheManager;
+    use crate::network::Network;
+    use crate::providers::routing::{ProviderKind, RoutingPolicy};
+    use crate::providers::testing::StubProvider;
+    use crate::providers::{CachedProviderRouter, ProviderRouter};
+    use crate::state::NetworkState;
+    use axum::extract::State;
+    use axum::response::IntoResponse;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn network(network: Network) -> (Network, NetworkState) {
+        let router = ProviderRouter::stubbed(
+            RoutingPolicy::default(),
+            &[(ProviderKind::Koios, Arc::new(StubProvider::new()))],
+        );
+        let cache = CacheManager::new(true, 100, HashMap::new(), None);
+        let router = CachedProviderRouter::new(router, cache, None, None, network.clone());
+        (
+            network,
+            NetworkState {
+                router,
+                warmer: Arc::default(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn exports_per_kind_counters_for_each_network() {
+        let (preview, preview_state) = network(Network::Preview);
+        let (preprod, preprod_state) = network(Network::Preprod);
+        let cache = preview_state.router.cache();
+        let key = CacheKey::DRep {
+            id: "drep1a".to_string(),
+        };
+        cache.set(&key, &"a").await;
+        cache.get::<String>(&key).await;
+        cache
+            .get::<String>(&CacheKey::DRep {
+                id: "drep1b".to_string(),
+            })
+            .await;
+
+        let state = AppState {
+            default_network: preview.clone(),
+            networks: Arc::new(HashMap::from([
+                (preview, preview_state),
+                (preprod, preprod_state),
+            ])),
+        };
+        let response = metrics(State(state)).await.into_response();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+
+        for line in [
+            "# TYPE govtwool_cache_hits_total counter",
+            "# TYPE govtwool_cache_kind_bytes gauge",
+            "govtwool_cache_entries{network=\"preview\"} 1",
+            "govtwool_cache_entries{network=\"preprod\"} 0",
+            "govtwool_cache_hits_total{network=\"preview\",kind=\"drep\"} 1",
+            "govtwool_cache_misses_total{network=\"preview\",kind=\"drep\"} 1",
+            "govtwool_cache_inserts_total{network=\"preview\",kind=\"drep\"} 1",
+            "govtwool_cache_stale_hits_total{network=\"preview\",kind=\"drep\"} 0",
+            "govtwool_cache_evictions_total{network=\"preview\",kind=\"drep\"} 0",
+            "govtwool_cache_kind_entries{network=\"preview\",kind=\"drep\"} 1",
+            "govtwool_cache_kind_bytes{network=\"preview\",kind=\"drep\"} 3",
+            "govtwool_cache_hits_total{network=\"preprod\",kind=\"drep\"} 0",
+        ] {
+            assert!(lines.contains(&line), "missing {:?} in\n{}", line, body);
+        }
+        // Every sample line is `name{labels} value`
+        for line in lines.iter().filter(|line| !line.starts_with('#')) {
+            let (_, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "{}", line);
+        }
+    }
+}
@@ -1,4 +1,8 @@
 pub mod actions;
+pub mod admin;
 pub mod dreps;
+pub mod error;
 pub mod health;
+pub mod ledger;
+pub mod metrics;
 pub mod stake;
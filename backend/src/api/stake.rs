@@ -1,9 +1,6 @@
+use crate::api::error::ApiError;
 use crate::providers::CachedProviderRouter;
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
-};
+use axum::{extract::Path, Json};
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -17,9 +14,9 @@ pub struct StakeDelegationResponse {
 }
 
 pub async fn get_stake_delegation(
-    State(router): State<CachedProviderRouter>,
+    router: CachedProviderRouter,
     Path(stake_address): Path<String>,
-) -> Result<Json<StakeDelegationResponse>, StatusCode> {
+) -> Result<Json<StakeDelegationResponse>, ApiError> {
     match router.get_stake_delegation(&stake_address).await {
         Ok(Some(delegation)) => Ok(Json(StakeDelegationResponse {
             stake_address: delegation.stake_address,
@@ -31,11 +28,14 @@ pub async fn get_stake_delegation(
         })),
         Ok(None) => {
             tracing::warn!("Stake address not found: {}", stake_address);
-            Err(StatusCode::NOT_FOUND)
+            Err(ApiError::not_found(format!(
+                "Stake address {}",
+                stake_address
+            )))
         }
         Err(e) => {
             tracing::error!("Error fetching stake delegation: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
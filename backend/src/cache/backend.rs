@@ -0,0 +1,383 @@
+use crate::cache::keys::CacheKeyKind;
+use crate::cache::metrics::{CacheMetrics, Removal};
+use crate::cache::CacheEntry;
+use crate::config::Config;
+use crate::network::Network;
+use async_trait::async_trait;
+use moka::future::Cache;
+use moka::notification::RemovalCause;
+use moka::Expiry;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Matches key strings for `CacheBackend::remove_if`.
+pub type KeyPredicate = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Storage tier for serialized cache entries, keyed by `CacheKey::to_string`.
+///
+/// Backends are responsible for dropping entries once `CacheEntry::expires_in`
+/// has run out; `CacheManager` decides whether an entry is fresh or stale.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, anyhow::Error>;
+
+    async fn insert(&self, key: &str, entry: &CacheEntry) -> Result<(), anyhow::Error>;
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error>;
+
+    /// Removes every entry whose key matches `predicate`.
+    async fn remove_if(&self, predicate: KeyPredicate) -> Result<(), anyhow::Error>;
+
+    async fn clear(&self) -> Result<(), anyhow::Error>;
+}
+
+// Evicts each entry once its CacheKey TTL and stale window have both passed,
+// counted from when it was first stored
+struct PerKeyExpiry;
+
+impl Expiry<String, CacheEntry> for PerKeyExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &CacheEntry,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.expires_in())
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &CacheEntry,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(value.expires_in())
+    }
+}
+
+/// In-process L1 tier.
+pub struct MokaBackend {
+    cache: Cache<String, CacheEntry>,
+    metrics: Arc<CacheMetrics>,
+}
+
+impl MokaBackend {
+    pub fn new(max_entries: usize, metrics: Arc<CacheMetrics>) -> Self {
+        let listener_metrics = metrics.clone();
+        Self {
+            cache: Cache::builder()
+                .max_capacity(max_entries as u64)
+                .expire_after(PerKeyExpiry)
+                .support_invalidation_closures()
+                .eviction_listener(move |key: Arc<String>, entry: CacheEntry, cause| {
+                    let removal = match cause {
+                        RemovalCause::Expired | RemovalCause::Size => Removal::Evicted,
+                        RemovalCause::Explicit => Removal::Invalidated,
+                        RemovalCause::Replaced => Removal::Replaced,
+                    };
+                    if let Some(kind) = CacheKeyKind::of(&key) {
+                        listener_metrics.record_removed(kind, entry.bytes.len(), removal);
+                    }
+                })
+                .build(),
+            metrics,
+        }
+    }
+
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    /// Applies pending evictions so counts and metrics are current.
+    pub async fn sync(&self) {
+        self.cache.run_pending_tasks().await;
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (String, CacheEntry)> + '_ {
+        self.cache
+            .iter()
+            .map(|(key, entry)| (key.as_ref().clone(), entry))
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MokaBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, anyhow::Error> {
+        Ok(self.cache.get(key).await)
+    }
+
+    async fn insert(&self, key: &str, entry: &CacheEntry) -> Result<(), anyhow::Error> {
+        self.cache.insert(key.to_string(), entry.clone()).await;
+        if let Some(kind) = CacheKeyKind::of(key) {
+            self.metrics.record_stored(kind, entry.bytes.len());
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error> {
+        self.cache.invalidate(key).await;
+        Ok(())
+    }
+
+    async fn remove_if(&self, predicate: KeyPredicate) -> Result<(), anyhow::Error> {
+        self.cache
+            .invalidate_entries_if(move |key, _| predicate(key))?;
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), anyhow::Error> {
+        self.cache.invalidate_all();
+        Ok(())
+    }
+}
+
+/// View of a shared L2 tier scoped to one network: keys are stored under
+/// `<network>:`, so networks served by one process never see each other's
+/// entries.
+pub struct NamespacedBackend {
+    inner: Arc<dyn CacheBackend>,
+    namespace: String,
+}
+
+impl NamespacedBackend {
+    pub fn new(inner: Arc<dyn CacheBackend>, network: &Network) -> Self {
+        Self {
+            inner,
+            namespace: format!("{}:", network),
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.namespace, key)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for NamespacedBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, anyhow::Error> {
+        self.inner.get(&self.key(key)).await
+    }
+
+    async fn insert(&self, key: &str, entry: &CacheEntry) -> Result<(), anyhow::Error> {
+        self.inner.insert(&self.key(key), entry).await
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error> {
+        self.inner.remove(&self.key(key)).await
+    }
+
+    async fn remove_if(&self, predicate: KeyPredicate) -> Result<(), anyhow::Error> {
+        let namespace = self.namespace.clone();
+        self.inner
+            .remove_if(Arc::new(move |key| {
+                key.strip_prefix(namespace.as_str())
+                    .is_some_and(|key| predicate(key))
+            }))
+            .await
+    }
+
+    async fn clear(&self) -> Result<(), anyhow::Error> {
+        self.remove_if(Arc::new(|_| true)).await
+    }
+}
+
+/// Opens the L2 tier selected by `CACHE_L2_BACKEND`, if any.
+pub async fn open_l2(config: &Config) -> Result<Option<Arc<dyn CacheBackend>>, anyhow::Error> {
+    match config.cache_l2_backend.as_str() {
+        "" | "none" => Ok(None),
+        #[cfg(feature = "redis-cache")]
+        "redis" => {
+            let backend = crate::cache::redis_backend::RedisBackend::connect(
+                &config.cache_l2_redis_url,
+                config.cache_l2_prefix.clone(),
+            )
+            .await?;
+            tracing::info!("L2 cache: Redis at {}", config.cache_l2_redis_url);
+            Ok(Some(Arc::new(backend)))
+        }
+        #[cfg(feature = "redb-cache")]
+        "redb" => {
+            let backend = crate::cache::redb_backend::RedbBackend::open(&config.cache_l2_path)?;
+            tracing::info!("L2 cache: redb file {}", config.cache_l2_path);
+            Ok(Some(Arc::new(backend)))
+        }
+        #[cfg(not(feature = "redis-cache"))]
+        "redis" => Err(anyhow::anyhow!(
+            "CACHE_L2_BACKEND=redis requires building with --features redis-cache"
+        )),
+        #[cfg(not(feature = "redb-cache"))]
+        "redb" => Err(anyhow::anyhow!(
+            "CACHE_L2_BACKEND=redb requires building with --features redb-cache"
+        )),
+        other => Err(anyhow::anyhow!(
+            "Unknown CACHE_L2_BACKEND '{}' (expected none, redis or redb)",
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys::CacheKey;
+    use std::time::SystemTime;
+
+    // Entries are built from wall-clock timestamps, so allow for the time the
+    // test itself takes
+    const SLACK: Duration = Duration::from_secs(1);
+
+    fn aged(key: &CacheKey, age: Duration) -> CacheEntry {
+        let ttl = Duration::from_secs(key.ttl_seconds());
+        let stale_for = Duration::from_secs(key.kind().default_stale_seconds());
+        CacheEntry {
+            bytes: b"{}".to_vec(),
+            inserted_at: SystemTime::now() - age,
+            ttl,
+            stale_for,
+        }
+    }
+
+    fn assert_close(actual: Duration, expected: Duration, key: &CacheKey) {
+        assert!(
+            actual <= expected && actual + SLACK >= expected,
+            "{}: expected about {:?}, got {:?}",
+            key,
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn new_entries_expire_after_ttl_plus_stale_window() {
+        for key in CacheKey::samples() {
+            let ttl = Duration::from_secs(key.ttl_seconds());
+            let stale_for = Duration::from_secs(key.kind().default_stale_seconds());
+            let entry = aged(&key, Duration::ZERO);
+
+            let expiry = PerKeyExpiry.expire_after_create(&key.to_string(), &entry, Instant::now());
+            assert_close(expiry.unwrap(), ttl + stale_for, &key);
+            assert_close(entry.remaining(), ttl, &key);
+            assert!(entry.is_fresh(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn entries_go_stale_after_ttl_and_expire_after_stale_window() {
+        for key in CacheKey::samples() {
+            let ttl = Duration::from_secs(key.ttl_seconds());
+            let stale_for = Duration::from_secs(key.kind().default_stale_seconds());
+
+            // Halfway through the TTL
+            let entry = aged(&key, ttl / 2);
+            assert_close(entry.remaining(), ttl - ttl / 2, &key);
+            assert!(entry.is_fresh(), "{}", key);
+
+            // Just past the TTL: stale, still servable if the kind has a stale window
+            let entry = aged(&key, ttl + SLACK);
+            assert!(!entry.is_fresh(), "{}", key);
+            assert_eq!(entry.remaining(), Duration::ZERO, "{}", key);
+            assert_eq!(entry.expires_in().is_zero(), stale_for <= SLACK, "{}", key);
+
+            // Past the stale window too
+            let entry = aged(&key, ttl + stale_for + SLACK);
+            let expiry = PerKeyExpiry.expire_after_create(&key.to_string(), &entry, Instant::now());
+            assert_eq!(expiry, Some(Duration::ZERO), "{}", key);
+        }
+    }
+
+    #[test]
+    fn updates_keep_the_original_expiry() {
+        for key in CacheKey::samples() {
+            let ttl = Duration::from_secs(key.ttl_seconds());
+            let stale_for = Duration::from_secs(key.kind().default_stale_seconds());
+            let age = ttl / 3;
+            let updated = CacheEntry {
+                bytes: b"{\"updated\":true}".to_vec(),
+                ..aged(&key, age)
+            };
+
+            let expiry = PerKeyExpiry.expire_after_update(
+                &key.to_string(),
+                &updated,
+                Instant::now(),
+                Some(ttl + stale_for),
+            );
+            assert_close(expiry.unwrap(), ttl + stale_for - age, &key);
+        }
+    }
+
+    #[tokio::test]
+    async fn moka_drops_entries_on_their_own_schedule() {
+        let backend = MokaBackend::new(100, Arc::new(CacheMetrics::default()));
+        let short = CacheEntry {
+            bytes: b"1".to_vec(),
+            inserted_at: SystemTime::now(),
+            ttl: Duration::from_millis(50),
+            stale_for: Duration::from_millis(50),
+        };
+        let long = CacheEntry {
+            ttl: Duration::from_secs(60),
+            ..short.clone()
+        };
+        let expired = CacheEntry {
+            inserted_at: SystemTime::now() - Duration::from_secs(120),
+            ..long.clone()
+        };
+        backend.insert("drep:short", &short).await.unwrap();
+        backend.insert("drep:long", &long).await.unwrap();
+        backend.insert("drep:expired", &expired).await.unwrap();
+
+        assert!(backend.get("drep:short").await.unwrap().is_some());
+        assert!(backend.get("drep:expired").await.unwrap().is_none());
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(backend.get("drep:short").await.unwrap().is_none());
+        assert!(backend.get("drep:long").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn namespaces_keep_networks_apart() {
+        let shared: Arc<dyn CacheBackend> =
+            Arc::new(MokaBackend::new(100, Arc::new(CacheMetrics::default())));
+        let preview = NamespacedBackend::new(shared.clone(), &Network::Preview);
+        let preprod = NamespacedBackend::new(shared.clone(), &Network::Preprod);
+        let entry = aged(&CacheKey::DRepStats, Duration::ZERO);
+        for backend in [&preview, &preprod] {
+            for key in ["drep:drep1a", "drep:drep1b", "dreps_stats"] {
+                backend.insert(key, &entry).await.unwrap();
+            }
+        }
+        assert!(shared.get("preview:drep:drep1a").await.unwrap().is_some());
+        assert!(shared.get("drep:drep1a").await.unwrap().is_none());
+
+        preview.remove("drep:drep1a").await.unwrap();
+        assert!(preview.get("drep:drep1a").await.unwrap().is_none());
+        assert!(preprod.get("drep:drep1a").await.unwrap().is_some());
+
+        // Predicates see keys without the namespace
+        preview
+            .remove_if(Arc::new(|key| key.starts_with("drep:")))
+            .await
+            .unwrap();
+        assert!(preview.get("drep:drep1b").await.unwrap().is_none());
+        assert!(preview.get("dreps_stats").await.unwrap().is_some());
+        assert!(preprod.get("drep:drep1b").await.unwrap().is_some());
+
+        preprod.clear().await.unwrap();
+        for key in ["drep:drep1a", "drep:drep1b", "dreps_stats"] {
+            assert!(preprod.get(key).await.unwrap().is_none(), "{}", key);
+        }
+        assert!(preview.get("dreps_stats").await.unwrap().is_some());
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CacheKey {
@@ -47,11 +48,16 @@ pub enum CacheKey {
     EpochStartTime {
         epoch: u32,
     },
+    LedgerState,
+    /// Remembers that `key` came back empty, not found or invalid.
+    Negative {
+        key: Box<CacheKey>,
+    },
 }
 
-impl CacheKey {
-    pub fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = match self {
             CacheKey::DRepsPage {
                 page,
                 count,
@@ -95,6 +101,50 @@ impl CacheKey {
                 format!("stake_delegation:{}", stake_address)
             }
             CacheKey::EpochStartTime { epoch } => format!("epoch_start_time:{}", epoch),
+            CacheKey::LedgerState => "ledger_state".to_string(),
+            CacheKey::Negative { key } => format!("negative:{}", key),
+        };
+        f.write_str(&key)
+    }
+}
+
+impl CacheKey {
+    /// The key a not-found or invalid lookup of `self` is remembered under.
+    /// Only single-entity lookups, which bad links and bots tend to repeat,
+    /// are negatively cached.
+    pub fn negative(&self) -> Option<CacheKey> {
+        match self {
+            CacheKey::DRep { .. }
+            | CacheKey::DRepDelegators { .. }
+            | CacheKey::DRepVotingHistory { .. }
+            | CacheKey::DRepMetadata { .. }
+            | CacheKey::Action { .. }
+            | CacheKey::ActionVotes { .. }
+            | CacheKey::ActionParticipation { .. }
+            | CacheKey::StakeDelegation { .. } => Some(CacheKey::Negative {
+                key: Box::new(self.clone()),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> CacheKeyKind {
+        match self {
+            CacheKey::DRepsPage { .. } => CacheKeyKind::DRepsPage,
+            CacheKey::DRep { .. } => CacheKeyKind::DRep,
+            CacheKey::DRepDelegators { .. } => CacheKeyKind::DRepDelegators,
+            CacheKey::DRepVotingHistory { .. } => CacheKeyKind::DRepVotingHistory,
+            CacheKey::DRepMetadata { .. } => CacheKeyKind::DRepMetadata,
+            CacheKey::DRepStats => CacheKeyKind::DRepStats,
+            CacheKey::ActionsPage { .. } => CacheKeyKind::ActionsPage,
+            CacheKey::Action { .. } => CacheKeyKind::Action,
+            CacheKey::ActionVotes { .. } => CacheKeyKind::ActionVotes,
+            CacheKey::ActionParticipation { .. } => CacheKeyKind::ActionParticipation,
+            CacheKey::ActionMetadataValidation { .. } => CacheKeyKind::ActionMetadataValidation,
+            CacheKey::StakeDelegation { .. } => CacheKeyKind::StakeDelegation,
+            CacheKey::EpochStartTime { .. } => CacheKeyKind::EpochStartTime,
+            CacheKey::LedgerState => CacheKeyKind::LedgerState,
+            CacheKey::Negative { .. } => CacheKeyKind::Negative,
         }
     }
 
@@ -127,6 +177,225 @@ impl CacheKey {
             CacheKey::StakeDelegation { .. } => 60,
             // Epoch start times: 1 hour
             CacheKey::EpochStartTime { .. } => 3600,
+            // Ledger state: 60 seconds
+            CacheKey::LedgerState => 60,
+            // Not-found and invalid lookups: 30 seconds
+            CacheKey::Negative { .. } => 30,
+        }
+    }
+}
+
+/// Exact keys of the entries that were derived from the one under the key
+/// string `key` and go stale with it: its negative entry and, for a DRep,
+/// its metadata and that metadata's negative entry.
+pub fn dependent_keys(key: &str) -> Vec<String> {
+    if key.starts_with("negative:") {
+        return Vec::new();
+    }
+    let mut keys = vec![format!("negative:{}", key)];
+    if let Some(id) = key.strip_prefix("drep:") {
+        keys.push(format!("drep_metadata:{}", id));
+        keys.push(format!("negative:drep_metadata:{}", id));
+    }
+    keys
+}
+
+/// Prefix of the entries derived from `key` that are keyed by more than its
+/// id, i.e. an action's metadata validations.
+pub fn dependent_prefix(key: &str) -> Option<String> {
+    key.strip_prefix("action:")
+        .map(|id| format!("action_metadata:{}:", id))
+}
+
+/// The type of a cache key, without its parameters. Serializes to the same
+/// name as the key prefix.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKeyKind {
+    #[serde(rename = "dreps_page")]
+    DRepsPage,
+    #[serde(rename = "drep")]
+    DRep,
+    #[serde(rename = "drep_delegators")]
+    DRepDelegators,
+    #[serde(rename = "drep_votes")]
+    DRepVotingHistory,
+    #[serde(rename = "drep_metadata")]
+    DRepMetadata,
+    #[serde(rename = "dreps_stats")]
+    DRepStats,
+    ActionsPage,
+    Action,
+    ActionVotes,
+    ActionParticipation,
+    #[serde(rename = "action_metadata")]
+    ActionMetadataValidation,
+    StakeDelegation,
+    EpochStartTime,
+    LedgerState,
+    Negative,
+}
+
+impl CacheKeyKind {
+    pub const ALL: [CacheKeyKind; 15] = [
+        CacheKeyKind::DRepsPage,
+        CacheKeyKind::DRep,
+        CacheKeyKind::DRepDelegators,
+        CacheKeyKind::DRepVotingHistory,
+        CacheKeyKind::DRepMetadata,
+        CacheKeyKind::DRepStats,
+        CacheKeyKind::ActionsPage,
+        CacheKeyKind::Action,
+        CacheKeyKind::ActionVotes,
+        CacheKeyKind::ActionParticipation,
+        CacheKeyKind::ActionMetadataValidation,
+        CacheKeyKind::StakeDelegation,
+        CacheKeyKind::EpochStartTime,
+        CacheKeyKind::LedgerState,
+        CacheKeyKind::Negative,
+    ];
+
+    /// The kind of a key string produced by `CacheKey::to_string`.
+    pub fn of(key: &str) -> Option<CacheKeyKind> {
+        let prefix = key.split(':').next()?;
+        Self::ALL.into_iter().find(|kind| kind.as_str() == prefix)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheKeyKind::DRepsPage => "dreps_page",
+            CacheKeyKind::DRep => "drep",
+            CacheKeyKind::DRepDelegators => "drep_delegators",
+            CacheKeyKind::DRepVotingHistory => "drep_votes",
+            CacheKeyKind::DRepMetadata => "drep_metadata",
+            CacheKeyKind::DRepStats => "dreps_stats",
+            CacheKeyKind::ActionsPage => "actions_page",
+            CacheKeyKind::Action => "action",
+            CacheKeyKind::ActionVotes => "action_votes",
+            CacheKeyKind::ActionParticipation => "action_participation",
+            CacheKeyKind::ActionMetadataValidation => "action_metadata",
+            CacheKeyKind::StakeDelegation => "stake_delegation",
+            CacheKeyKind::EpochStartTime => "epoch_start_time",
+            CacheKeyKind::LedgerState => "ledger_state",
+            CacheKeyKind::Negative => "negative",
+        }
+    }
+
+    /// How long an expired entry may still be served while it is refreshed in
+    /// the background.
+    pub fn default_stale_seconds(&self) -> u64 {
+        match self {
+            // Lists and stats: 5 minutes
+            CacheKeyKind::DRepsPage | CacheKeyKind::ActionsPage | CacheKeyKind::DRepStats => 300,
+            // Single DRep/Action and their votes: 10 minutes
+            CacheKeyKind::DRep
+            | CacheKeyKind::Action
+            | CacheKeyKind::DRepDelegators
+            | CacheKeyKind::DRepVotingHistory
+            | CacheKeyKind::ActionVotes
+            | CacheKeyKind::ActionParticipation => 600,
+            // DRep metadata: 1 hour
+            CacheKeyKind::DRepMetadata => 3600,
+            // Metadata validation is only ever read fresh
+            CacheKeyKind::ActionMetadataValidation => 0,
+            // Stake delegation: 1 minute
+            CacheKeyKind::StakeDelegation => 60,
+            // Epoch start times never change
+            CacheKeyKind::EpochStartTime => 86400,
+            // Ledger state: 5 minutes
+            CacheKeyKind::LedgerState => 300,
+            // Negative entries are only ever read fresh
+            CacheKeyKind::Negative => 0,
+        }
+    }
+}
+
+impl fmt::Display for CacheKeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+impl CacheKey {
+    /// One key of every kind, for tests that have to cover each of them.
+    pub fn samples() -> Vec<CacheKey> {
+        vec![
+            CacheKey::DRepsPage {
+                page: 1,
+                count: 20,
+                filters: None,
+            },
+            CacheKey::DRepsPage {
+                page: 2,
+                count: 20,
+                filters: Some("status=active".to_string()),
+            },
+            CacheKey::DRep {
+                id: "drep1abc".to_string(),
+            },
+            CacheKey::DRepDelegators {
+                id: "drep1abc".to_string(),
+            },
+            CacheKey::DRepVotingHistory {
+                id: "drep1abc".to_string(),
+            },
+            CacheKey::DRepMetadata {
+                id: "drep1abc".to_string(),
+            },
+            CacheKey::DRepStats,
+            CacheKey::ActionsPage { page: 1, count: 20 },
+            CacheKey::ActionsPage { page: 3, count: 20 },
+            CacheKey::Action {
+                id: "gov_action1xyz".to_string(),
+            },
+            CacheKey::ActionVotes {
+                id: "gov_action1xyz".to_string(),
+            },
+            CacheKey::ActionParticipation {
+                id: "gov_action1xyz".to_string(),
+            },
+            CacheKey::ActionMetadataValidation {
+                action_id: "gov_action1xyz".to_string(),
+                meta_hash: Some("ABCD".to_string()),
+                verifier_enabled: true,
+                version: 1,
+            },
+            CacheKey::StakeDelegation {
+                stake_address: "stake1uxyz".to_string(),
+            },
+            CacheKey::EpochStartTime { epoch: 500 },
+            CacheKey::LedgerState,
+            CacheKey::Negative {
+                key: Box::new(CacheKey::DRep {
+                    id: "drep1missing".to_string(),
+                }),
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_cover_every_kind() {
+        let kinds: Vec<_> = CacheKey::samples().iter().map(|key| key.kind()).collect();
+        for kind in CacheKeyKind::ALL {
+            assert!(kinds.contains(&kind), "no sample key for {}", kind);
+        }
+    }
+
+    #[test]
+    fn key_strings_parse_back_to_their_kind() {
+        for key in CacheKey::samples() {
+            assert_eq!(
+                CacheKeyKind::of(&key.to_string()),
+                Some(key.kind()),
+                "{}",
+                key
+            );
         }
     }
 }
@@ -0,0 +1,245 @@
+use crate::cache::keys::CacheKeyKind;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+#[derive(Debug, Default)]
+struct KindCounters {
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+    invalidations: AtomicU64,
+    // Gauges for the in-memory tier, moved by inserts and removal notifications
+    entries: AtomicI64,
+    bytes: AtomicI64,
+}
+
+/// How a cache lookup was answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    Fresh,
+    Stale,
+    Miss,
+}
+
+/// Why an entry left the in-memory tier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removal {
+    /// Expired or pushed out by the size limit
+    Evicted,
+    /// Dropped by an invalidation or a clear
+    Invalidated,
+    /// Overwritten by a newer value for the same key
+    Replaced,
+}
+
+/// Cache counters broken down by key kind, so TTLs can be tuned per kind
+/// against real traffic.
+#[derive(Debug)]
+pub struct CacheMetrics {
+    kinds: HashMap<CacheKeyKind, KindCounters>,
+}
+
+impl Default for CacheMetrics {
+    fn default() -> Self {
+        Self {
+            kinds: CacheKeyKind::ALL
+                .into_iter()
+                .map(|kind| (kind, KindCounters::default()))
+                .collect(),
+        }
+    }
+}
+
+impl CacheMetrics {
+    fn counters(&self, kind: CacheKeyKind) -> &KindCounters {
+        // Every kind is registered up front
+        &self.kinds[&kind]
+    }
+
+    pub fn record_lookup(&self, kind: CacheKeyKind, lookup: Lookup) {
+        let counters = self.counters(kind);
+        match lookup {
+            Lookup::Fresh => counters.hits.fetch_add(1, Ordering::Relaxed),
+            Lookup::Stale => counters.stale_hits.fetch_add(1, Ordering::Relaxed),
+            Lookup::Miss => counters.misses.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    pub fn record_insert(&self, kind: CacheKeyKind) {
+        self.counters(kind).inserts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_stored(&self, kind: CacheKeyKind, bytes: usize) {
+        let counters = self.counters(kind);
+        counters.entries.fetch_add(1, Ordering::Relaxed);
+        counters.bytes.fetch_add(bytes as i64, Ordering::Relaxed);
+    }
+
+    pub fn record_removed(&self, kind: CacheKeyKind, bytes: usize, removal: Removal) {
+        let counters = self.counters(kind);
+        counters.entries.fetch_sub(1, Ordering::Relaxed);
+        counters.bytes.fetch_sub(bytes as i64, Ordering::Relaxed);
+        match removal {
+            Removal::Evicted => counters.evictions.fetch_add(1, Ordering::Relaxed),
+            Removal::Invalidated => counters.invalidations.fetch_add(1, Ordering::Relaxed),
+            Removal::Replaced => 0,
+        };
+    }
+
+    pub fn snapshot(&self) -> Vec<KindStats> {
+        CacheKeyKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let counters = self.counters(kind);
+                let hits = counters.hits.load(Ordering::Relaxed);
+                let stale_hits = counters.stale_hits.load(Ordering::Relaxed);
+                let misses = counters.misses.load(Ordering::Relaxed);
+                let lookups = hits + stale_hits + misses;
+                KindStats {
+                    kind,
+                    hits,
+                    stale_hits,
+                    misses,
+                    hit_rate: if lookups == 0 {
+                        0.0
+                    } else {
+                        (hits + stale_hits) as f64 / lookups as f64 * 100.0
+                    },
+                    inserts: counters.inserts.load(Ordering::Relaxed),
+                    evictions: counters.evictions.load(Ordering::Relaxed),
+                    invalidations: counters.invalidations.load(Ordering::Relaxed),
+                    entries: counters.entries.load(Ordering::Relaxed).max(0) as u64,
+                    bytes: counters.bytes.load(Ordering::Relaxed).max(0) as u64,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KindStats {
+    pub kind: CacheKeyKind,
+    pub hits: u64,
+    pub stale_hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub inserts: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys::CacheKey;
+    use crate::cache::{CacheEntry, CacheManager};
+    use std::time::Duration;
+
+    fn of_kind(snapshot: &[KindStats], kind: CacheKeyKind) -> &KindStats {
+        snapshot.iter().find(|stats| stats.kind == kind).unwrap()
+    }
+
+    fn drep(id: &str) -> CacheKey {
+        CacheKey::DRep { id: id.to_string() }
+    }
+
+    #[test]
+    fn counts_lookups_and_removals_per_kind() {
+        let metrics = CacheMetrics::default();
+        metrics.record_lookup(CacheKeyKind::DRep, Lookup::Fresh);
+        metrics.record_lookup(CacheKeyKind::DRep, Lookup::Stale);
+        metrics.record_lookup(CacheKeyKind::DRep, Lookup::Miss);
+        metrics.record_lookup(CacheKeyKind::DRep, Lookup::Miss);
+        metrics.record_insert(CacheKeyKind::DRep);
+        metrics.record_stored(CacheKeyKind::DRep, 100);
+        metrics.record_stored(CacheKeyKind::DRep, 40);
+        metrics.record_removed(CacheKeyKind::DRep, 100, Removal::Evicted);
+        metrics.record_removed(CacheKeyKind::DRep, 40, Removal::Invalidated);
+        metrics.record_stored(CacheKeyKind::DRep, 60);
+        metrics.record_stored(CacheKeyKind::DRep, 70);
+        metrics.record_removed(CacheKeyKind::DRep, 60, Removal::Replaced);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), CacheKeyKind::ALL.len());
+        let drep = of_kind(&snapshot, CacheKeyKind::DRep);
+        assert_eq!((drep.hits, drep.stale_hits, drep.misses), (1, 1, 2));
+        assert_eq!(drep.hit_rate, 50.0);
+        assert_eq!(drep.inserts, 1);
+        assert_eq!((drep.evictions, drep.invalidations), (1, 1));
+        assert_eq!((drep.entries, drep.bytes), (1, 70));
+
+        let action = of_kind(&snapshot, CacheKeyKind::Action);
+        assert_eq!((action.hits, action.misses, action.entries), (0, 0, 0));
+        assert_eq!(action.hit_rate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn manager_reports_lookups_sizes_and_removals() {
+        let cache = CacheManager::new(true, 100, Default::default(), None);
+        cache.set(&drep("drep1a"), &"a").await;
+        cache.set(&drep("drep1b"), &"bbbb").await;
+        assert_eq!(
+            cache.get::<String>(&drep("drep1a")).await.as_deref(),
+            Some("a")
+        );
+        assert_eq!(cache.get::<String>(&drep("drep1c")).await, None);
+
+        // Served past its TTL, within the stale window
+        let mut entry = cache.inspect("drep:drep1b").await.unwrap();
+        entry.inserted_at -= entry.ttl + Duration::from_secs(1);
+        cache.restore("drep:drep1b", &entry).await;
+        assert!(!cache.lookup::<String>(&drep("drep1b")).await.unwrap().fresh);
+
+        let snapshot = cache.kind_stats().await;
+        let stats = of_kind(&snapshot, CacheKeyKind::DRep);
+        assert_eq!((stats.hits, stats.stale_hits, stats.misses), (1, 1, 1));
+        assert_eq!(stats.inserts, 2);
+        assert_eq!(stats.entries, 2);
+        // Serialized JSON: `"a"` and `"bbbb"`
+        assert_eq!(stats.bytes, 3 + 6);
+        // Restoring over an entry replaces it; that isn't an eviction
+        assert_eq!((stats.evictions, stats.invalidations), (0, 0));
+
+        cache.set(&drep("drep1a"), &"aaaaaaaa").await;
+        cache.invalidate("drep:drep1b").await;
+        let snapshot = cache.kind_stats().await;
+        let stats = of_kind(&snapshot, CacheKeyKind::DRep);
+        assert_eq!((stats.entries, stats.bytes), (1, 10));
+        assert_eq!((stats.evictions, stats.invalidations), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn listener_counts_expiry_and_size_evictions() {
+        let cache = CacheManager::new(true, 100, Default::default(), None);
+        let entry = CacheEntry::new(
+            b"\"a\"".to_vec(),
+            Duration::from_millis(100),
+            Duration::ZERO,
+        );
+        cache.restore("drep:drep1a", &entry).await;
+        // Moka's timer wheel works in steps of about a second
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let snapshot = cache.kind_stats().await;
+        let stats = of_kind(&snapshot, CacheKeyKind::DRep);
+        assert_eq!((stats.entries, stats.bytes, stats.evictions), (0, 0, 1));
+
+        let cache = CacheManager::new(true, 1, Default::default(), None);
+        for id in ["drep1a", "drep1b", "drep1c"] {
+            cache.set(&drep(id), &id).await;
+            cache.kind_stats().await;
+        }
+        let snapshot = cache.kind_stats().await;
+        let stats = of_kind(&snapshot, CacheKeyKind::DRep);
+        assert_eq!(stats.inserts, 3);
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.evictions, 2);
+        // Every value serializes to the same 8 bytes, whichever one was kept
+        assert_eq!(stats.bytes, 8);
+    }
+}
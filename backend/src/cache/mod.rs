@@ -1,40 +1,151 @@
+pub mod backend;
 pub mod keys;
+pub mod metrics;
+pub mod negative;
+#[cfg(feature = "redb-cache")]
+pub mod redb_backend;
+#[cfg(feature = "redis-cache")]
+pub mod redis_backend;
+pub mod single_flight;
+pub mod snapshot;
+pub mod status;
 
-use crate::cache::keys::CacheKey;
-use moka::future::Cache;
+use crate::cache::backend::{CacheBackend, KeyPredicate, MokaBackend};
+use crate::cache::keys::{CacheKey, CacheKeyKind};
+use crate::cache::metrics::{CacheMetrics, KindStats, Lookup};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::debug;
 
 use std::sync::atomic::{AtomicU64, Ordering};
 
+/// Serialized cache value together with the lifetime it was stored with.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub bytes: Vec<u8>,
+    pub inserted_at: SystemTime,
+    pub ttl: Duration,
+    /// How long the entry may still be served after its TTL while it is refreshed.
+    pub stale_for: Duration,
+}
+
+impl CacheEntry {
+    fn new(bytes: Vec<u8>, ttl: Duration, stale_for: Duration) -> Self {
+        Self {
+            bytes,
+            inserted_at: SystemTime::now(),
+            ttl,
+            stale_for,
+        }
+    }
+
+    /// Rebuilds an entry from the millisecond fields an L2 backend or snapshot
+    /// stores.
+    pub fn from_millis(bytes: Vec<u8>, inserted_at: u64, ttl: u64, stale_for: u64) -> Self {
+        Self {
+            bytes,
+            inserted_at: UNIX_EPOCH + Duration::from_millis(inserted_at),
+            ttl: Duration::from_millis(ttl),
+            stale_for: Duration::from_millis(stale_for),
+        }
+    }
+
+    pub fn inserted_at_millis(&self) -> u64 {
+        self.inserted_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    pub fn age(&self) -> Duration {
+        self.inserted_at.elapsed().unwrap_or_default()
+    }
+
+    /// Time left before the entry goes stale.
+    pub fn remaining(&self) -> Duration {
+        self.ttl.saturating_sub(self.age())
+    }
+
+    /// Time left before the entry can no longer be served at all.
+    pub fn expires_in(&self) -> Duration {
+        (self.ttl + self.stale_for).saturating_sub(self.age())
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        !self.remaining().is_zero()
+    }
+}
+
+/// A value read from the cache, and whether it is still within its TTL.
+pub struct Cached<T> {
+    pub value: T,
+    pub fresh: bool,
+    /// When the entry that was read was stored, for `CacheManager::update`
+    pub inserted_at: SystemTime,
+}
+
 pub struct CacheManager {
-    cache: Arc<Cache<String, Vec<u8>>>,
+    l1: MokaBackend,
+    l2: Option<Arc<dyn CacheBackend>>,
     enabled: bool,
+    stale_windows: HashMap<CacheKeyKind, Duration>,
+    metrics: Arc<CacheMetrics>,
     hits: AtomicU64,
     misses: AtomicU64,
 }
 
 impl CacheManager {
-    pub fn new(enabled: bool, max_entries: usize) -> Self {
-        let cache = Arc::new(
-            Cache::builder()
-                .max_capacity(max_entries as u64)
-                .time_to_live(Duration::from_secs(600)) // Max TTL - individual entries use their own TTLs
-                .time_to_idle(Duration::from_secs(300))
-                .build(),
-        );
-
+    pub fn new(
+        enabled: bool,
+        max_entries: usize,
+        stale_windows: HashMap<CacheKeyKind, Duration>,
+        l2: Option<Arc<dyn CacheBackend>>,
+    ) -> Self {
+        let metrics = Arc::new(CacheMetrics::default());
         Self {
-            cache,
+            l1: MokaBackend::new(max_entries, metrics.clone()),
+            l2,
             enabled,
+            stale_windows,
+            metrics,
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
         }
     }
 
+    /// Returns the value for `key` only while it is fresh.
     pub async fn get<T>(&self, key: &CacheKey) -> Option<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let cached = self.read::<T>(key).await.filter(|cached| cached.fresh);
+        let lookup = match cached {
+            Some(_) => Lookup::Fresh,
+            None => Lookup::Miss,
+        };
+        self.record_lookup(key, lookup);
+        cached.map(|cached| cached.value)
+    }
+
+    /// Returns the value for `key`, including one past its TTL that is still
+    /// within the stale window of its kind.
+    pub async fn lookup<T>(&self, key: &CacheKey) -> Option<Cached<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let cached = self.read::<T>(key).await;
+        let lookup = match &cached {
+            Some(cached) if cached.fresh => Lookup::Fresh,
+            Some(_) => Lookup::Stale,
+            None => Lookup::Miss,
+        };
+        self.record_lookup(key, lookup);
+        cached
+    }
+
+    async fn read<T>(&self, key: &CacheKey) -> Option<Cached<T>>
     where
         T: for<'de> Deserialize<'de>,
     {
@@ -43,27 +154,75 @@ impl CacheManager {
         }
 
         let cache_key = key.to_string();
-        match self.cache.get(&cache_key).await {
-            Some(bytes) => match serde_json::from_slice::<T>(&bytes) {
-                Ok(value) => {
-                    self.hits.fetch_add(1, Ordering::Relaxed);
-                    debug!("Cache hit: {}", cache_key);
-                    Some(value)
-                }
-                Err(e) => {
-                    self.misses.fetch_add(1, Ordering::Relaxed);
-                    tracing::warn!("Failed to deserialize cache entry {}: {}", cache_key, e);
-                    None
-                }
-            },
-            None => {
-                self.misses.fetch_add(1, Ordering::Relaxed);
-                debug!("Cache miss: {}", cache_key);
+        let entry = self.entry(&cache_key).await?;
+        match serde_json::from_slice::<T>(&entry.bytes) {
+            Ok(value) => Some(Cached {
+                value,
+                fresh: entry.is_fresh(),
+                inserted_at: entry.inserted_at,
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to deserialize cache entry {}: {}", cache_key, e);
                 None
             }
         }
     }
 
+    // Reads L1, then L2; an L2 hit is copied into L1 for the rest of its lifetime
+    async fn entry(&self, cache_key: &str) -> Option<CacheEntry> {
+        if let Ok(Some(entry)) = self.l1.get(cache_key).await {
+            return Some(entry);
+        }
+
+        let l2 = self.l2.as_ref()?;
+        match l2.get(cache_key).await {
+            Ok(Some(entry)) if !entry.expires_in().is_zero() => {
+                debug!("L2 ({}) cache hit: {}", l2.name(), cache_key);
+                let _ = self.l1.insert(cache_key, &entry).await;
+                Some(entry)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(
+                    "L2 ({}) cache read failed for {}: {}",
+                    l2.name(),
+                    cache_key,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    async fn store(&self, cache_key: &str, entry: &CacheEntry) {
+        let _ = self.l1.insert(cache_key, entry).await;
+        if let Some(l2) = &self.l2 {
+            if let Err(e) = l2.insert(cache_key, entry).await {
+                tracing::warn!(
+                    "L2 ({}) cache write failed for {}: {}",
+                    l2.name(),
+                    cache_key,
+                    e
+                );
+            }
+        }
+    }
+
+    fn record_lookup(&self, key: &CacheKey, lookup: Lookup) {
+        if !self.enabled {
+            return;
+        }
+
+        self.metrics.record_lookup(key.kind(), lookup);
+        if lookup != Lookup::Miss {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            debug!("Cache hit: {}", key);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            debug!("Cache miss: {}", key);
+        }
+    }
+
     pub async fn set<T>(&self, key: &CacheKey, value: &T)
     where
         T: Serialize,
@@ -74,11 +233,19 @@ impl CacheManager {
 
         let cache_key = key.to_string();
         let ttl = key.ttl_seconds();
+        let stale_for = self.stale_window(key.kind());
 
         match serde_json::to_vec(value) {
             Ok(bytes) => {
-                self.cache.insert(cache_key.clone(), bytes).await;
-                debug!("Cached: {} (TTL: {}s)", cache_key, ttl);
+                let entry = CacheEntry::new(bytes, Duration::from_secs(ttl), stale_for);
+                self.store(&cache_key, &entry).await;
+                self.metrics.record_insert(key.kind());
+                debug!(
+                    "Cached: {} (TTL: {}s, stale: {}s)",
+                    cache_key,
+                    ttl,
+                    stale_for.as_secs()
+                );
             }
             Err(e) => {
                 tracing::warn!("Failed to serialize cache entry {}: {}", cache_key, e);
@@ -86,33 +253,182 @@ impl CacheManager {
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn invalidate(&self, key: &CacheKey) {
+    /// Replaces a cached value without extending its lifetime, e.g. after
+    /// re-enriching an entry that was served from the cache. `read_at` is the
+    /// `inserted_at` of the entry the value was derived from; if the entry has
+    /// been replaced since, e.g. by a background refresh, it is left alone.
+    pub async fn update<T>(&self, key: &CacheKey, value: &T, read_at: SystemTime)
+    where
+        T: Serialize,
+    {
         if !self.enabled {
             return;
         }
 
         let cache_key = key.to_string();
-        self.cache.invalidate(&cache_key).await;
-        debug!("Cache invalidated: {}", cache_key);
+        let Some(existing) = self.entry(&cache_key).await else {
+            return;
+        };
+        if existing.inserted_at != read_at {
+            debug!("Not updating {}: replaced since it was read", cache_key);
+            return;
+        }
+
+        match serde_json::to_vec(value) {
+            Ok(bytes) => {
+                let entry = CacheEntry { bytes, ..existing };
+                self.store(&cache_key, &entry).await;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to serialize cache entry {}: {}", cache_key, e);
+            }
+        }
+    }
+
+    /// Drops a single entry from both tiers, along with the entries derived
+    /// from it (see `keys::dependent_keys`), so that e.g. a refreshed DRep
+    /// doesn't keep answering from its negative entry. `key` is a `CacheKey`
+    /// string, e.g. `drep:drep1...`. Returns whether the in-memory tier held
+    /// the entry itself.
+    pub async fn invalidate(&self, key: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let existed = matches!(self.l1.get(key).await, Ok(Some(_)));
+        self.remove(key).await;
+        for dependent in keys::dependent_keys(key) {
+            self.remove(&dependent).await;
+        }
+        if let Some(prefix) = keys::dependent_prefix(key) {
+            self.remove_matching(Arc::new(move |key| key.starts_with(&prefix)))
+                .await;
+        }
+        debug!("Cache invalidated: {}", key);
+        existed
+    }
+
+    async fn remove(&self, key: &str) {
+        let _ = self.l1.remove(key).await;
+        if let Some(l2) = &self.l2 {
+            if let Err(e) = l2.remove(key).await {
+                tracing::warn!(
+                    "L2 ({}) cache invalidation failed for {}: {}",
+                    l2.name(),
+                    key,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Drops every entry whose key starts with `prefix` from both tiers.
+    /// Returns how many entries the in-memory tier held.
+    pub async fn invalidate_prefix(&self, prefix: &str) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+
+        let count = self.entries(prefix).len();
+        let prefix = prefix.to_string();
+        self.remove_matching(Arc::new(move |key| key.starts_with(&prefix)))
+            .await;
+        debug!("Cache invalidated {} entries by prefix", count);
+        count
+    }
+
+    /// Drops every entry of one of `kinds` whose key string matches `predicate`,
+    /// in both tiers.
+    pub async fn invalidate_where<P>(&self, kinds: &[CacheKeyKind], predicate: P)
+    where
+        P: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        if !self.enabled {
+            return;
+        }
+
+        let kinds = kinds.to_vec();
+        self.remove_matching(Arc::new(move |key| {
+            CacheKeyKind::of(key).is_some_and(|kind| kinds.contains(&kind)) && predicate(key)
+        }))
+        .await;
+    }
+
+    async fn remove_matching(&self, predicate: KeyPredicate) {
+        if let Err(e) = self.l1.remove_if(predicate.clone()).await {
+            tracing::warn!("Cache invalidation failed: {}", e);
+        }
+        if let Some(l2) = &self.l2 {
+            if let Err(e) = l2.remove_if(predicate).await {
+                tracing::warn!("L2 ({}) cache invalidation failed: {}", l2.name(), e);
+            }
+        }
     }
 
-    #[allow(dead_code)]
     pub async fn clear(&self) {
         if !self.enabled {
             return;
         }
 
-        self.cache.invalidate_all();
+        let _ = self.l1.clear().await;
+        if let Some(l2) = &self.l2 {
+            if let Err(e) = l2.clear().await {
+                tracing::warn!("L2 ({}) cache clear failed: {}", l2.name(), e);
+            }
+        }
         debug!("Cache cleared");
     }
 
+    /// In-memory entries whose key starts with `prefix`, sorted by key.
+    pub fn entries(&self, prefix: &str) -> Vec<(String, CacheEntry)> {
+        let mut entries: Vec<_> = self
+            .l1
+            .entries()
+            .filter(|(key, entry)| key.starts_with(prefix) && !entry.expires_in().is_zero())
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
+
+    /// Puts an entry back into the in-memory tier with its original lifetime.
+    /// The L2 tier is left alone; it keeps its own copy across restarts.
+    pub async fn restore(&self, key: &str, entry: &CacheEntry) {
+        if self.enabled {
+            let _ = self.l1.insert(key, entry).await;
+        }
+    }
+
+    /// The stored entry for a key string, from either tier.
+    pub async fn inspect(&self, key: &str) -> Option<CacheEntry> {
+        if !self.enabled {
+            return None;
+        }
+        self.entry(key).await
+    }
+
+    pub fn stale_window(&self, kind: CacheKeyKind) -> Duration {
+        self.stale_windows
+            .get(&kind)
+            .copied()
+            .unwrap_or_else(|| Duration::from_secs(kind.default_stale_seconds()))
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
 
     pub fn entry_count(&self) -> usize {
-        self.cache.weighted_size() as usize
+        self.l1.entry_count() as usize
+    }
+
+    /// Per-kind counters, after applying pending evictions.
+    pub async fn kind_stats(&self) -> Vec<KindStats> {
+        self.l1.sync().await;
+        self.metrics.snapshot()
+    }
+
+    pub fn l2_backend(&self) -> Option<&'static str> {
+        self.l2.as_ref().map(|l2| l2.name())
     }
 
     pub fn hit_count(&self) -> u64 {
@@ -134,3 +450,163 @@ impl CacheManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> CacheManager {
+        CacheManager::new(true, 1000, HashMap::new(), None)
+    }
+
+    #[tokio::test]
+    async fn entries_are_stored_with_their_key_ttl_and_stale_window() {
+        let cache = manager();
+        for key in CacheKey::samples() {
+            cache.set(&key, &"value").await;
+            let entry = cache.inspect(&key.to_string()).await.expect("stored");
+
+            assert_eq!(entry.ttl, Duration::from_secs(key.ttl_seconds()), "{}", key);
+            assert_eq!(
+                entry.stale_for,
+                Duration::from_secs(key.kind().default_stale_seconds()),
+                "{}",
+                key
+            );
+            assert!(
+                entry.remaining() + Duration::from_secs(1) >= entry.ttl,
+                "{}",
+                key
+            );
+            assert_eq!(cache.get::<String>(&key).await.as_deref(), Some("value"));
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_window_overrides_apply_per_kind() {
+        let windows = HashMap::from([(CacheKeyKind::DRep, Duration::from_secs(5))]);
+        let cache = CacheManager::new(true, 100, windows, None);
+        let drep = CacheKey::DRep { id: "drep1".into() };
+        let action = CacheKey::Action { id: "gov1".into() };
+        cache.set(&drep, &1).await;
+        cache.set(&action, &1).await;
+
+        let entry = cache.inspect(&drep.to_string()).await.unwrap();
+        assert_eq!(entry.stale_for, Duration::from_secs(5));
+        let entry = cache.inspect(&action.to_string()).await.unwrap();
+        assert_eq!(entry.stale_for, Duration::from_secs(600));
+    }
+
+    #[tokio::test]
+    async fn stale_entries_are_served_by_lookup_but_not_get() {
+        let cache = manager();
+        let key = CacheKey::DRep { id: "drep1".into() };
+        let entry = CacheEntry {
+            bytes: b"\"old\"".to_vec(),
+            inserted_at: SystemTime::now() - Duration::from_secs(key.ttl_seconds() + 1),
+            ttl: Duration::from_secs(key.ttl_seconds()),
+            stale_for: Duration::from_secs(600),
+        };
+        cache.restore(&key.to_string(), &entry).await;
+
+        assert_eq!(cache.get::<String>(&key).await, None);
+        let cached = cache.lookup::<String>(&key).await.expect("stale entry");
+        assert_eq!(cached.value, "old");
+        assert!(!cached.fresh);
+    }
+
+    #[tokio::test]
+    async fn update_keeps_the_original_expiry() {
+        let cache = manager();
+        for key in CacheKey::samples() {
+            let ttl = Duration::from_secs(key.ttl_seconds());
+            let original = CacheEntry {
+                bytes: b"\"before\"".to_vec(),
+                inserted_at: SystemTime::now() - ttl / 2,
+                ttl,
+                stale_for: Duration::from_secs(key.kind().default_stale_seconds()),
+            };
+            cache.restore(&key.to_string(), &original).await;
+
+            cache.update(&key, &"after", original.inserted_at).await;
+            let entry = cache.inspect(&key.to_string()).await.expect("still cached");
+            assert_eq!(entry.bytes, b"\"after\"", "{}", key);
+            assert_eq!(entry.inserted_at, original.inserted_at, "{}", key);
+            assert_eq!(entry.ttl, original.ttl, "{}", key);
+            assert_eq!(entry.stale_for, original.stale_for, "{}", key);
+        }
+    }
+
+    #[tokio::test]
+    async fn update_does_not_create_missing_entries() {
+        let cache = manager();
+        let key = CacheKey::DRep { id: "drep1".into() };
+        cache.update(&key, &"value", SystemTime::now()).await;
+        assert!(cache.inspect(&key.to_string()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn update_leaves_an_entry_replaced_since_it_was_read() {
+        let cache = manager();
+        let key = CacheKey::Action { id: "gov1".into() };
+        let stale = CacheEntry {
+            bytes: b"\"stale\"".to_vec(),
+            inserted_at: SystemTime::now() - Duration::from_secs(key.ttl_seconds() + 1),
+            ttl: Duration::from_secs(key.ttl_seconds()),
+            stale_for: Duration::from_secs(600),
+        };
+        cache.restore(&key.to_string(), &stale).await;
+        let read = cache.lookup::<String>(&key).await.unwrap();
+        assert!(!read.fresh);
+
+        // A background refresh lands before the stale value is re-enriched
+        cache.set(&key, &"fresh").await;
+        cache
+            .update(&key, &"stale, enriched", read.inserted_at)
+            .await;
+
+        let cached = cache.lookup::<String>(&key).await.unwrap();
+        assert_eq!(cached.value, "fresh");
+        assert!(cached.fresh);
+    }
+
+    #[tokio::test]
+    async fn invalidating_a_key_drops_what_was_derived_from_it() {
+        let cache = manager();
+        let drep = CacheKey::DRep { id: "drep1".into() };
+        let metadata = CacheKey::DRepMetadata { id: "drep1".into() };
+        let action = CacheKey::Action { id: "gov1".into() };
+        let validation = CacheKey::ActionMetadataValidation {
+            action_id: "gov1".into(),
+            meta_hash: Some("abc".into()),
+            verifier_enabled: false,
+            version: 1,
+        };
+        let other = CacheKey::DRep { id: "drep2".into() };
+        for key in [&drep, &metadata, &action, &validation, &other] {
+            cache.set(key, &"value").await;
+        }
+        for key in [&drep, &metadata, &other] {
+            cache.set(&key.negative().unwrap(), &"absent").await;
+        }
+
+        assert!(cache.invalidate(&drep.to_string()).await);
+        for key in [drep.clone(), drep.negative().unwrap(), metadata.clone()] {
+            assert!(cache.inspect(&key.to_string()).await.is_none(), "{}", key);
+        }
+        assert!(cache
+            .inspect(&metadata.negative().unwrap().to_string())
+            .await
+            .is_none());
+
+        assert!(cache.invalidate(&action.to_string()).await);
+        assert!(cache.inspect(&validation.to_string()).await.is_none());
+
+        // Other DReps keep both their entry and their negative entry
+        assert!(cache.inspect(&other.to_string()).await.is_some());
+        assert!(cache
+            .inspect(&other.negative().unwrap().to_string())
+            .await
+            .is_some());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::providers::ProviderError;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a lookup that produced nothing worth caching, stored under
+/// `CacheKey::Negative` so repeated requests don't go back upstream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum NegativeEntry {
+    /// The providers answered but had nothing for the key
+    Absent,
+    NotFound {
+        what: String,
+    },
+    InvalidInput {
+        message: String,
+    },
+}
+
+impl NegativeEntry {
+    /// Classifies `error`. Only not-found and invalid-input failures, which
+    /// asking again won't change, are remembered; everything else is left for
+    /// the retry and circuit-breaker layers.
+    pub fn from_error(error: &ProviderError) -> Option<Self> {
+        match error {
+            ProviderError::NotFound(what) => Some(NegativeEntry::NotFound { what: what.clone() }),
+            ProviderError::InvalidInput(message) => Some(NegativeEntry::InvalidInput {
+                message: message.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Replays the entry for a lookup that returns `Option<T>`.
+    pub fn into_option<T>(self) -> Result<Option<T>, ProviderError> {
+        match self {
+            NegativeEntry::Absent => Ok(None),
+            other => Err(other.into_error()),
+        }
+    }
+
+    /// Replays the entry for a lookup with no empty result; an absent entry
+    /// becomes `NotFound`.
+    pub fn into_error(self) -> ProviderError {
+        match self {
+            NegativeEntry::Absent => ProviderError::NotFound("Resource".to_string()),
+            NegativeEntry::NotFound { what } => ProviderError::NotFound(what),
+            NegativeEntry::InvalidInput { message } => ProviderError::InvalidInput(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn remembers_only_not_found_and_invalid_input() {
+        let remembered = [
+            ProviderError::NotFound("DRep".to_string()),
+            ProviderError::InvalidInput("bad bech32".to_string()),
+        ];
+        for error in remembered {
+            assert!(NegativeEntry::from_error(&error).is_some(), "{}", error);
+        }
+
+        let forgotten = [
+            ProviderError::Timeout,
+            ProviderError::Transport("connection reset".to_string()),
+            ProviderError::Unavailable("circuit open".to_string()),
+            ProviderError::RateLimited {
+                retry_after: Some(Duration::from_secs(1)),
+            },
+            ProviderError::Upstream {
+                status: 400,
+                message: "bad request".to_string(),
+            },
+            ProviderError::Upstream {
+                status: 503,
+                message: "down".to_string(),
+            },
+            ProviderError::Decode("unexpected field".to_string()),
+            ProviderError::NotRecorded("GET /drep_info".to_string()),
+        ];
+        for error in forgotten {
+            assert!(NegativeEntry::from_error(&error).is_none(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn replays_the_remembered_outcome() {
+        assert!(matches!(
+            NegativeEntry::Absent.into_option::<u32>(),
+            Ok(None)
+        ));
+        assert!(matches!(
+            NegativeEntry::Absent.into_error(),
+            ProviderError::NotFound(_)
+        ));
+
+        let entry = NegativeEntry::from_error(&ProviderError::NotFound("DRep".to_string()));
+        assert!(matches!(
+            entry.unwrap().into_option::<u32>(),
+            Err(ProviderError::NotFound(what)) if what == "DRep"
+        ));
+        let entry = NegativeEntry::from_error(&ProviderError::InvalidInput("bad".to_string()));
+        assert!(matches!(
+            entry.unwrap().into_error(),
+            ProviderError::InvalidInput(message) if message == "bad"
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::cache::backend::{CacheBackend, KeyPredicate};
+use crate::cache::CacheEntry;
+use async_trait::async_trait;
+use redb::{Database, ReadableTableMetadata, Table, TableDefinition};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+// key -> (inserted_at ms, ttl ms, stale window ms, serialized value)
+const ENTRIES: TableDefinition<&str, (u64, u64, u64, &[u8])> = TableDefinition::new("entries");
+
+// Inserts between sweeps for expired entries that were never read again
+const PURGE_EVERY: u64 = 1000;
+
+/// L2 tier in an embedded redb file, so a single instance keeps its cache
+/// across restarts. Expired entries are dropped when read, on startup and,
+/// so the file doesn't keep growing, every `PURGE_EVERY` inserts.
+pub struct RedbBackend {
+    db: Arc<Database>,
+    inserts: AtomicU64,
+    purge_every: u64,
+}
+
+impl RedbBackend {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let db = Database::create(path)?;
+
+        let txn = db.begin_write()?;
+        {
+            let mut table = txn.open_table(ENTRIES)?;
+            purge_expired(&mut table)?;
+        }
+        txn.commit()?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            inserts: AtomicU64::new(0),
+            purge_every: PURGE_EVERY,
+        })
+    }
+
+    // redb is synchronous; keep its disk I/O off the async workers
+    async fn blocking<T, F>(&self, op: F) -> Result<T, anyhow::Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T, anyhow::Error> + Send + 'static,
+    {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || op(&db)).await?
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedbBackend {
+    fn name(&self) -> &'static str {
+        "redb"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, anyhow::Error> {
+        let lookup = key.to_string();
+        let entry = self
+            .blocking(move |db| {
+                let txn = db.begin_read()?;
+                let table = match txn.open_table(ENTRIES) {
+                    Ok(table) => table,
+                    Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+                    Err(e) => return Err(e.into()),
+                };
+                Ok(table.get(lookup.as_str())?.map(|value| {
+                    let (inserted_at, ttl, stale_for, bytes) = value.value();
+                    CacheEntry::from_millis(bytes.to_vec(), inserted_at, ttl, stale_for)
+                }))
+            })
+            .await?;
+
+        match entry {
+            Some(entry) if entry.expires_in().is_zero() => {
+                self.remove(key).await?;
+                Ok(None)
+            }
+            entry => Ok(entry),
+        }
+    }
+
+    async fn insert(&self, key: &str, entry: &CacheEntry) -> Result<(), anyhow::Error> {
+        let key = key.to_string();
+        let entry = entry.clone();
+        let inserted = self.inserts.fetch_add(1, Ordering::Relaxed);
+        let purge = inserted % self.purge_every == self.purge_every - 1;
+        self.blocking(move |db| {
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(ENTRIES)?;
+                table.insert(
+                    key.as_str(),
+                    (
+                        entry.inserted_at_millis(),
+                        entry.ttl.as_millis() as u64,
+                        entry.stale_for.as_millis() as u64,
+                        entry.bytes.as_slice(),
+                    ),
+                )?;
+                if purge {
+                    let purged = purge_expired(&mut table)?;
+                    tracing::debug!("Purged {} expired redb cache entries", purged);
+                }
+            }
+            txn.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error> {
+        let key = key.to_string();
+        self.blocking(move |db| {
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(ENTRIES)?;
+                table.remove(key.as_str())?;
+            }
+            txn.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove_if(&self, predicate: KeyPredicate) -> Result<(), anyhow::Error> {
+        self.blocking(move |db| {
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(ENTRIES)?;
+                table.retain(|key, _| !predicate(key))?;
+            }
+            txn.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn clear(&self) -> Result<(), anyhow::Error> {
+        self.blocking(|db| {
+            let txn = db.begin_write()?;
+            txn.delete_table(ENTRIES)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+// Drops every entry past its TTL and stale window; returns how many
+fn purge_expired(table: &mut Table<&str, (u64, u64, u64, &[u8])>) -> Result<usize, anyhow::Error> {
+    let before = table.len()?;
+    table.retain(|_, (inserted_at, ttl, stale_for, _)| {
+        !CacheEntry::from_millis(Vec::new(), inserted_at, ttl, stale_for)
+            .expires_in()
+            .is_zero()
+    })?;
+    Ok((before - table.len()?) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn entry(expired: bool) -> CacheEntry {
+        let age = if expired {
+            Duration::from_secs(3600)
+        } else {
+            Duration::ZERO
+        };
+        CacheEntry {
+            bytes: b"{}".to_vec(),
+            inserted_at: SystemTime::now() - age,
+            ttl: Duration::from_secs(60),
+            stale_for: Duration::from_secs(60),
+        }
+    }
+
+    fn stored(backend: &RedbBackend) -> u64 {
+        let txn = backend.db.begin_read().unwrap();
+        txn.open_table(ENTRIES).unwrap().len().unwrap()
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_purged_on_write_and_open() {
+        let path = std::env::temp_dir().join(format!("govtwool-redb-{}.redb", fastrand::u64(..)));
+        let mut backend = RedbBackend::open(&path).unwrap();
+        backend.purge_every = 4;
+
+        // Never read again, so only a purge can drop them
+        for key in ["drep:a", "drep:b"] {
+            backend.insert(key, &entry(true)).await.unwrap();
+        }
+        backend.insert("drep:c", &entry(false)).await.unwrap();
+        assert_eq!(stored(&backend), 3);
+
+        backend.insert("drep:d", &entry(false)).await.unwrap();
+        assert_eq!(stored(&backend), 2);
+        assert!(backend.get("drep:c").await.unwrap().is_some());
+
+        backend.insert("drep:e", &entry(true)).await.unwrap();
+        drop(backend);
+        let backend = RedbBackend::open(&path).unwrap();
+        assert_eq!(stored(&backend), 2);
+
+        drop(backend);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::cache::backend::{CacheBackend, KeyPredicate};
+use crate::cache::CacheEntry;
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::sync::Arc;
+
+/// Shared L2 tier in Redis. Each entry is a hash holding the serialized value
+/// and its lifetime, and Redis expires it once its stale window has passed.
+pub struct RedisBackend {
+    connection: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisBackend {
+    pub async fn connect(url: &str, prefix: String) -> Result<Self, anyhow::Error> {
+        let client = redis::Client::open(url)?;
+        let connection = ConnectionManager::new(client).await?;
+        Ok(Self { connection, prefix })
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedisBackend {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, anyhow::Error> {
+        let mut connection = self.connection.clone();
+        let (bytes, inserted_at, ttl, stale_for): (
+            Option<Vec<u8>>,
+            Option<u64>,
+            Option<u64>,
+            Option<u64>,
+        ) = redis::cmd("HMGET")
+            .arg(self.key(key))
+            .arg(&["value", "inserted_at", "ttl_ms", "stale_ms"])
+            .query_async(&mut connection)
+            .await?;
+
+        Ok(match (bytes, inserted_at, ttl, stale_for) {
+            (Some(bytes), Some(inserted_at), Some(ttl), Some(stale_for)) => {
+                Some(CacheEntry::from_millis(bytes, inserted_at, ttl, stale_for))
+            }
+            _ => None,
+        })
+    }
+
+    async fn insert(&self, key: &str, entry: &CacheEntry) -> Result<(), anyhow::Error> {
+        let expires_in = entry.expires_in().as_millis() as u64;
+        if expires_in == 0 {
+            return Ok(());
+        }
+
+        let key = self.key(key);
+        let mut connection = self.connection.clone();
+        redis::pipe()
+            .atomic()
+            .cmd("HSET")
+            .arg(&key)
+            .arg("value")
+            .arg(entry.bytes.as_slice())
+            .arg("inserted_at")
+            .arg(entry.inserted_at_millis())
+            .arg("ttl_ms")
+            .arg(entry.ttl.as_millis() as u64)
+            .arg("stale_ms")
+            .arg(entry.stale_for.as_millis() as u64)
+            .ignore()
+            .cmd("PEXPIRE")
+            .arg(&key)
+            .arg(expires_in)
+            .ignore()
+            .query_async::<()>(&mut connection)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection.clone();
+        connection.del::<_, ()>(self.key(key)).await?;
+        Ok(())
+    }
+
+    async fn remove_if(&self, predicate: KeyPredicate) -> Result<(), anyhow::Error> {
+        // Only our own keys; the Redis instance may be shared
+        let mut connection = self.connection.clone();
+        let pattern = format!("{}*", escape_glob(&self.prefix));
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut connection)
+                .await?;
+            let matching: Vec<&String> = keys
+                .iter()
+                .filter(|key| {
+                    key.strip_prefix(self.prefix.as_str())
+                        .is_some_and(|key| predicate(key))
+                })
+                .collect();
+            if !matching.is_empty() {
+                connection.del::<_, ()>(matching).await?;
+            }
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
+        }
+    }
+
+    async fn clear(&self) -> Result<(), anyhow::Error> {
+        self.remove_if(Arc::new(|_| true)).await
+    }
+}
+
+// SCAN MATCH takes a glob; the prefix has to match literally
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_glob_syntax_in_the_prefix() {
+        assert_eq!(escape_glob("govtwool:"), "govtwool:");
+        assert_eq!(escape_glob("a*b?c[d]e"), "a\\*b\\?c\\[d\\]e");
+        assert_eq!(escape_glob("back\\slash"), "back\\\\slash");
+    }
+}
@@ -0,0 +1,216 @@
+use crate::cache::keys::CacheKey;
+use crate::providers::ProviderError;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+type Flight<T> = Shared<BoxFuture<'static, Result<T, ProviderError>>>;
+
+/// Deduplicates concurrent cache-miss fetches: callers asking for a key that is
+/// already being fetched await the same upstream request instead of starting
+/// their own.
+#[derive(Default)]
+pub struct SingleFlight {
+    flights: Arc<Mutex<HashMap<String, Box<dyn Any + Send + Sync>>>>,
+}
+
+impl SingleFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `fetch` for `key` unless a fetch for it is already in flight, in
+    /// which case its result is shared. The fetch runs as its own task, so it
+    /// completes (and fills the cache) even if every caller goes away.
+    pub async fn run<T, F, Fut>(&self, key: &CacheKey, fetch: F) -> Result<T, ProviderError>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>> + Send + 'static,
+    {
+        let key = key.to_string();
+
+        let flight = {
+            let mut flights = self.flights.lock().unwrap();
+            match flights.get(&key) {
+                Some(existing) => match existing.downcast_ref::<Flight<T>>() {
+                    Some(flight) => {
+                        debug!("Joining in-flight fetch for {}", key);
+                        Ok(flight.clone())
+                    }
+                    None => Err(fetch),
+                },
+                None => {
+                    let flight = self.start(key.clone(), fetch());
+                    flights.insert(key, Box::new(flight.clone()));
+                    Ok(flight)
+                }
+            }
+        };
+
+        match flight {
+            Ok(flight) => flight.await,
+            // Same key requested as a different type; don't share
+            Err(fetch) => fetch().await,
+        }
+    }
+
+    fn start<T, Fut>(&self, key: String, fetch: Fut) -> Flight<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ProviderError>> + Send + 'static,
+    {
+        let landing = Landing {
+            flights: self.flights.clone(),
+            key,
+        };
+        let task = tokio::spawn(async move {
+            // Dropped once the fetch (and its cache write) finished, or if it panicked
+            let _landing = landing;
+            fetch.await
+        });
+
+        async move {
+            task.await.unwrap_or_else(|error| {
+                Err(ProviderError::Unavailable(format!(
+                    "fetch task failed: {}",
+                    error
+                )))
+            })
+        }
+        .boxed()
+        .shared()
+    }
+}
+
+// Removes a flight from the in-flight map when its task ends
+struct Landing {
+    flights: Arc<Mutex<HashMap<String, Box<dyn Any + Send + Sync>>>>,
+    key: String,
+}
+
+impl Drop for Landing {
+    fn drop(&mut self) {
+        if let Ok(mut flights) = self.flights.lock() {
+            flights.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    const CALLERS: usize = 10;
+
+    fn key() -> CacheKey {
+        CacheKey::Action {
+            id: "gov_action1abc".to_string(),
+        }
+    }
+
+    fn in_flight(flight: &SingleFlight) -> usize {
+        flight.flights.lock().unwrap().len()
+    }
+
+    /// Starts `CALLERS` concurrent loads of the same key, each fetch taking a
+    /// second to answer with `result`, and returns what every caller got.
+    async fn load_together(
+        flight: &SingleFlight,
+        fetches: &Arc<AtomicUsize>,
+        result: Result<u32, ProviderError>,
+    ) -> Vec<Result<u32, ProviderError>> {
+        let key = key();
+        let loads = (0..CALLERS).map(|_| {
+            let fetches = fetches.clone();
+            let result = result.clone();
+            flight.run(&key, move || async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                result
+            })
+        });
+        join_all(loads).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_loads_share_one_fetch() {
+        let flight = SingleFlight::new();
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let results = load_together(&flight, &fetches, Ok(42)).await;
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|result| matches!(result, Ok(42))));
+        assert_eq!(in_flight(&flight), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn errors_reach_every_caller_and_clear_the_key() {
+        let flight = SingleFlight::new();
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let results = load_together(&flight, &fetches, Err(ProviderError::Timeout)).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Err(ProviderError::Timeout))));
+        assert_eq!(in_flight(&flight), 0);
+
+        // The failure isn't remembered: the next load fetches again
+        let results = load_together(&flight, &fetches, Ok(7)).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert!(results.iter().all(|result| matches!(result, Ok(7))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn panicking_fetch_does_not_poison_the_key() {
+        let flight = SingleFlight::new();
+        let key = key();
+
+        let loads = (0..CALLERS).map(|_| {
+            flight.run(&key, || async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                panic!("fetch blew up")
+            })
+        });
+        let results: Vec<Result<u32, _>> = join_all(loads).await;
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Err(ProviderError::Unavailable(_)))));
+        assert_eq!(in_flight(&flight), 0);
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let results = load_together(&flight, &fetches, Ok(1)).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|result| matches!(result, Ok(1))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fetch_finishes_after_every_caller_leaves() {
+        let flight = SingleFlight::new();
+        let fetched = Arc::new(AtomicUsize::new(0));
+
+        let key = key();
+        let done = fetched.clone();
+        let load = flight.run(&key, move || async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            done.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        // Give up on the load before the fetch answers
+        let abandoned = tokio::time::timeout(Duration::from_millis(100), load).await;
+        assert!(abandoned.is_err());
+        assert_eq!(in_flight(&flight), 1);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+        assert_eq!(in_flight(&flight), 0);
+    }
+}
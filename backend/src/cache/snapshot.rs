@@ -0,0 +1,250 @@
+use crate::cache::keys::CacheKeyKind;
+use crate::cache::{CacheEntry, CacheManager};
+use crate::network::Network;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bump whenever `CacheKey` or a cached model changes shape, so snapshots
+/// written by an older build are discarded instead of restored. The key
+/// schema is pinned by a test, so a `CacheKey` change fails until this is
+/// bumped; cached models are not, and still need remembering.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    written_at: u64,
+    entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    key: String,
+    inserted_at: u64,
+    ttl_ms: u64,
+    stale_ms: u64,
+    value: Value,
+}
+
+/// Snapshot file for a network other than the default one, e.g.
+/// `cache.preview.json` next to `cache.json`.
+pub fn network_path(path: &Path, network: &Network) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, network, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, network),
+    };
+    path.with_file_name(name)
+}
+
+/// Writes every live in-memory entry to `path`. The file is written next to
+/// `path` and renamed into place, so a crash mid-write leaves the previous
+/// snapshot intact.
+pub async fn save(cache: &CacheManager, path: &Path) -> Result<usize, anyhow::Error> {
+    let entries: Vec<SnapshotEntry> = cache
+        .entries("")
+        .into_iter()
+        .filter_map(|(key, entry)| {
+            let value = serde_json::from_slice(&entry.bytes).ok()?;
+            Some(SnapshotEntry {
+                key,
+                inserted_at: entry.inserted_at_millis(),
+                ttl_ms: entry.ttl.as_millis() as u64,
+                stale_ms: entry.stale_for.as_millis() as u64,
+                value,
+            })
+        })
+        .collect();
+    let count = entries.len();
+
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        written_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        entries,
+    };
+    let bytes = serde_json::to_vec(&snapshot)?;
+
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, bytes)
+        .await
+        .with_context(|| format!("writing {}", tmp.display()))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .with_context(|| format!("renaming {} to {}", tmp.display(), path.display()))?;
+    Ok(count)
+}
+
+/// Loads the snapshot at `path` into the in-memory tier, skipping entries that
+/// expired since it was written or whose key this build no longer knows.
+/// Returns how many entries were restored; a missing file restores nothing.
+pub async fn restore(cache: &CacheManager, path: &Path) -> Result<usize, anyhow::Error> {
+    let bytes = match tokio::fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+
+    let snapshot: Snapshot =
+        serde_json::from_slice(&bytes).with_context(|| format!("parsing {}", path.display()))?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        tracing::info!(
+            "Discarding cache snapshot {} (version {}, expected {})",
+            path.display(),
+            snapshot.version,
+            SNAPSHOT_VERSION
+        );
+        return Ok(0);
+    }
+
+    let mut restored = 0;
+    for item in snapshot.entries {
+        if CacheKeyKind::of(&item.key).is_none() {
+            continue;
+        }
+        let entry = CacheEntry::from_millis(
+            serde_json::to_vec(&item.value)?,
+            item.inserted_at,
+            item.ttl_ms,
+            item.stale_ms,
+        );
+        if entry.expires_in().is_zero() {
+            continue;
+        }
+        cache.restore(&item.key, &entry).await;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys::CacheKey;
+    use std::collections::HashMap;
+
+    // The key schema SNAPSHOT_VERSION was last bumped for. When this test
+    // fails, bump SNAPSHOT_VERSION and update both constants together.
+    const PINNED_VERSION: u32 = 1;
+    const PINNED_SCHEMA: &str = "\
+dreps_page drep drep_delegators drep_votes drep_metadata dreps_stats actions_page action \
+action_votes action_participation action_metadata stake_delegation epoch_start_time \
+ledger_state negative
+dreps_page:page=1:count=20
+dreps_page:page=2:count=20:filters=status=active
+drep:drep1abc
+drep_delegators:drep1abc
+drep_votes:drep1abc
+drep_metadata:drep1abc
+dreps_stats
+actions_page:page=1:count=20
+actions_page:page=3:count=20
+action:gov_action1xyz
+action_votes:gov_action1xyz
+action_participation:gov_action1xyz
+action_metadata:gov_action1xyz:hash=abcd:verifier=true:v=1
+stake_delegation:stake1uxyz
+epoch_start_time:500
+ledger_state
+negative:drep:drep1missing
+inserted_at key stale_ms ttl_ms value";
+
+    fn schema() -> String {
+        let kinds: Vec<_> = CacheKeyKind::ALL.iter().map(|kind| kind.as_str()).collect();
+        let keys: Vec<_> = CacheKey::samples()
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        let entry = serde_json::to_value(SnapshotEntry {
+            key: String::new(),
+            inserted_at: 0,
+            ttl_ms: 0,
+            stale_ms: 0,
+            value: Value::Null,
+        })
+        .unwrap();
+        let mut fields: Vec<_> = entry.as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        format!(
+            "{}\n{}\n{}",
+            kinds.join(" "),
+            keys.join("\n"),
+            fields.join(" ")
+        )
+    }
+
+    #[test]
+    fn snapshot_version_tracks_the_key_schema() {
+        assert_eq!(
+            (SNAPSHOT_VERSION, schema().as_str()),
+            (PINNED_VERSION, PINNED_SCHEMA),
+            "the cache key schema changed: bump SNAPSHOT_VERSION, then update \
+             PINNED_VERSION and PINNED_SCHEMA"
+        );
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("govtwool-snapshot-{}.json", fastrand::u64(..)))
+    }
+
+    #[tokio::test]
+    async fn saved_entries_are_restored_with_their_lifetime() {
+        let path = temp_path();
+        let cache = CacheManager::new(true, 100, HashMap::new(), None);
+        let key = CacheKey::DRep { id: "drep1".into() };
+        cache.set(&key, &"value").await;
+        let original = cache.inspect(&key.to_string()).await.unwrap();
+        assert_eq!(save(&cache, &path).await.unwrap(), 1);
+
+        let restored = CacheManager::new(true, 100, HashMap::new(), None);
+        assert_eq!(restore(&restored, &path).await.unwrap(), 1);
+        let entry = restored.inspect(&key.to_string()).await.unwrap();
+        assert_eq!(entry.inserted_at_millis(), original.inserted_at_millis());
+        assert_eq!(entry.ttl, original.ttl);
+        assert_eq!(restored.get::<String>(&key).await.as_deref(), Some("value"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn other_versions_and_unknown_keys_are_discarded() {
+        let path = temp_path();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let write = |version: u32, key: &str| {
+            let snapshot = Snapshot {
+                version,
+                written_at: now / 1000,
+                entries: vec![SnapshotEntry {
+                    key: key.to_string(),
+                    inserted_at: now,
+                    ttl_ms: 60_000,
+                    stale_ms: 0,
+                    value: Value::from(1),
+                }],
+            };
+            std::fs::write(&path, serde_json::to_vec(&snapshot).unwrap()).unwrap();
+        };
+        let cache = CacheManager::new(true, 100, HashMap::new(), None);
+
+        write(SNAPSHOT_VERSION + 1, "drep:drep1");
+        assert_eq!(restore(&cache, &path).await.unwrap(), 0);
+        write(SNAPSHOT_VERSION, "retired_kind:drep1");
+        assert_eq!(restore(&cache, &path).await.unwrap(), 0);
+        write(SNAPSHOT_VERSION, "drep:drep1");
+        assert_eq!(restore(&cache, &path).await.unwrap(), 1);
+
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(restore(&cache, &path).await.unwrap(), 0);
+    }
+}
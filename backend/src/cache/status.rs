@@ -0,0 +1,57 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::cell::Cell;
+
+static CACHE_STATUS_HEADER: HeaderName = HeaderName::from_static("x-cache-status");
+
+/// How the cache answered the request, reported in the `X-Cache-Status` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CacheStatus {
+    Fresh,
+    Miss,
+    Stale,
+}
+
+impl CacheStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheStatus::Fresh => "fresh",
+            CacheStatus::Miss => "miss",
+            CacheStatus::Stale => "stale",
+        }
+    }
+}
+
+tokio::task_local! {
+    static CACHE_STATUS: Cell<Option<CacheStatus>>;
+}
+
+/// Records a cache lookup for the current request. When a response is built
+/// from several entries the least fresh one wins (stale over miss over fresh).
+/// Lookups outside a request, e.g. in background refreshes, are ignored.
+pub fn record(status: CacheStatus) {
+    let _ = CACHE_STATUS.try_with(|current| {
+        current.set(Some(current.get().map_or(status, |c| c.max(status))));
+    });
+}
+
+/// Middleware that tracks cache lookups made while handling a request and
+/// reports them in the `X-Cache-Status` response header.
+pub async fn cache_status_header(request: Request, next: Next) -> Response {
+    CACHE_STATUS
+        .scope(Cell::new(None), async move {
+            let mut response = next.run(request).await;
+            if let Some(status) = CACHE_STATUS.with(|current| current.get()) {
+                response.headers_mut().insert(
+                    CACHE_STATUS_HEADER.clone(),
+                    HeaderValue::from_static(status.as_str()),
+                );
+            }
+            response
+        })
+        .await
+}
@@ -1,43 +1,96 @@
+use crate::cache::keys::CacheKeyKind;
+use crate::indexer::{BlockStream, IndexerConfig, Point};
+use crate::network::Network;
+use crate::providers::{FixtureConfig, FixtureMode};
+use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
 
+/// Providers for one served network.
 #[derive(Debug, Clone)]
-pub struct Config {
-    pub server_port: u16,
+pub struct NetworkConfig {
+    pub network: Network,
     pub blockfrost_api_key: String,
-    pub blockfrost_network: String,
+    pub blockfrost_base_url: String,
     pub koios_base_url: String,
+    pub koios_api_key: Option<String>,
+    pub govtools_base_url: String,
+    pub govtools_enabled: bool,
+    /// cardano-db-sync PostgreSQL database, queried before the hosted APIs
+    pub dbsync_url: Option<String>,
+    /// Ogmios endpoint of a Cardano node, for ledger-state queries
+    pub ogmios_url: Option<String>,
+    /// Chain-sync governance indexer, when INDEXER_ENABLED is set
+    pub indexer: Option<IndexerConfig>,
+    /// Recorded Koios, Blockfrost and GovTools traffic, when PROVIDER_FIXTURES is set
+    pub fixtures: Option<FixtureConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub server_port: u16,
+    /// Network served on the unprefixed `/api/...` routes
+    pub network: Network,
+    /// Every served network, the default one first
+    pub networks: Vec<NetworkConfig>,
     #[allow(dead_code)]
     pub cors_origins: Vec<String>,
     pub cache_enabled: bool,
     pub cache_max_entries: usize,
-    pub govtools_base_url: String,
-    pub govtools_enabled: bool,
+    pub cache_stale_secs: HashMap<CacheKeyKind, u64>,
+    pub cache_l2_backend: String,
+    #[cfg_attr(not(feature = "redis-cache"), allow(dead_code))]
+    pub cache_l2_redis_url: String,
+    #[cfg_attr(not(feature = "redb-cache"), allow(dead_code))]
+    pub cache_l2_path: String,
+    #[cfg_attr(not(feature = "redis-cache"), allow(dead_code))]
+    pub cache_l2_prefix: String,
+    pub cache_snapshot_path: Option<String>,
     pub cardano_verifier_enabled: bool,
     pub cardano_verifier_endpoint: String,
+    pub circuit_failure_threshold: u32,
+    pub circuit_error_rate_threshold: f64,
+    pub circuit_cooldown_secs: u64,
+    pub routing_config_path: Option<String>,
+    pub admin_api_token: Option<String>,
+    pub chain_tip_poll_secs: u64,
+    pub cache_warm_enabled: bool,
+    pub cache_warm_pages: u32,
+    pub cache_warm_epochs: u32,
+    pub cache_warm_interval_secs: u64,
+    pub provider_max_retries: u32,
+    pub provider_retry_base_ms: u64,
+    pub provider_retry_max_ms: u64,
+    pub provider_retry_budget_ratio: f64,
+    /// Share of DRep, action and vote-breakdown lookups compared across providers
+    pub consistency_sample_rate: f64,
+    #[cfg_attr(not(feature = "dbsync"), allow(dead_code))]
+    pub dbsync_pool_size: usize,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, anyhow::Error> {
         dotenv::dotenv().ok();
 
-        let blockfrost_network =
-            env::var("BLOCKFROST_NETWORK").unwrap_or_else(|_| "mainnet".to_string());
+        let network = Network::parse(
+            &env::var("BLOCKFROST_NETWORK").unwrap_or_else(|_| "mainnet".to_string()),
+        );
 
-        // Choose GovTools base URL by network (GovTools supports mainnet and preview)
-        let network_lc = blockfrost_network.to_lowercase();
-        let default_govtools_base_url = match network_lc.as_str() {
-            "mainnet" => "https://be.gov.tools",
-            "preview" => "https://be.preview.gov.tools",
-            // Unknown for preprod; allow override via GOVTOOLS_BASE_URL if available
-            _ => "https://be.gov.tools",
-        };
+        let fixtures = fixtures_from_env()?;
 
-        // Enable GovTools by default on networks we know are supported
-        let govtools_supported_by_default = matches!(network_lc.as_str(), "mainnet" | "preview");
-        let govtools_enabled = env::var("GOVTOOLS_ENABLED")
-            .ok()
-            .and_then(|s| s.parse::<bool>().ok())
-            .unwrap_or(govtools_supported_by_default);
+        // NETWORKS lists every network to serve; the default one is always served
+        let mut networks = vec![NetworkConfig::from_env(&network, true, fixtures.as_ref())?];
+        for name in env::var("NETWORKS").unwrap_or_default().split(',') {
+            if name.trim().is_empty() {
+                continue;
+            }
+            let extra = Network::parse(name);
+            if networks.iter().any(|n| n.network == extra) {
+                continue;
+            }
+            networks.push(NetworkConfig::from_env(&extra, false, fixtures.as_ref())?);
+        }
 
         Ok(Config {
             server_port: env::var("PORT")
@@ -45,11 +98,8 @@ impl Config {
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .unwrap_or(8080),
-            blockfrost_api_key: env::var("BLOCKFROST_API_KEY")
-                .map_err(|_| anyhow::anyhow!("BLOCKFROST_API_KEY not set"))?,
-            blockfrost_network: blockfrost_network.clone(),
-            koios_base_url: env::var("KOIOS_BASE_URL")
-                .unwrap_or_else(|_| "https://preview.koios.rest/api/v1".to_string()),
+            network,
+            networks,
             cors_origins: env::var("CORS_ORIGINS")
                 .unwrap_or_else(|_| "http://localhost:3000".to_string())
                 .split(',')
@@ -63,9 +113,16 @@ impl Config {
                 .unwrap_or_else(|_| "10000".to_string())
                 .parse()
                 .unwrap_or(10000),
-            govtools_base_url: env::var("GOVTOOLS_BASE_URL")
-                .unwrap_or_else(|_| default_govtools_base_url.to_string()),
-            govtools_enabled,
+            cache_stale_secs: stale_windows_from_env(),
+            cache_l2_backend: env::var("CACHE_L2_BACKEND")
+                .unwrap_or_else(|_| "none".to_string())
+                .trim()
+                .to_lowercase(),
+            cache_l2_redis_url: env::var("CACHE_L2_REDIS_URL")
+                .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
+            cache_l2_path: env::var("CACHE_L2_PATH").unwrap_or_else(|_| "./cache.redb".to_string()),
+            cache_l2_prefix: env::var("CACHE_L2_PREFIX")
+                .unwrap_or_else(|_| "govtwool:".to_string()),
             cardano_verifier_enabled: env::var("CARDANO_VERIFIER_ENABLED")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
@@ -73,14 +130,337 @@ impl Config {
             cardano_verifier_endpoint: env::var("CARDANO_VERIFIER_ENDPOINT").unwrap_or_else(|_| {
                 "https://verifycardanomessage.cardanofoundation.org/api/verify-cip100".to_string()
             }),
+            circuit_failure_threshold: env::var("CIRCUIT_FAILURE_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            circuit_error_rate_threshold: env::var("CIRCUIT_ERROR_RATE_THRESHOLD")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+            circuit_cooldown_secs: env::var("CIRCUIT_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            routing_config_path: env::var("ROUTING_CONFIG_PATH")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            cache_snapshot_path: env::var("CACHE_SNAPSHOT_PATH")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            admin_api_token: env::var("ADMIN_API_TOKEN")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            chain_tip_poll_secs: env::var("CHAIN_TIP_POLL_SECS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            cache_warm_enabled: env::var("CACHE_WARM_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            cache_warm_pages: env::var("CACHE_WARM_PAGES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            cache_warm_epochs: env::var("CACHE_WARM_EPOCHS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            cache_warm_interval_secs: env::var("CACHE_WARM_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            provider_max_retries: env::var("PROVIDER_MAX_RETRIES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            provider_retry_base_ms: env::var("PROVIDER_RETRY_BASE_MS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            provider_retry_max_ms: env::var("PROVIDER_RETRY_MAX_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            provider_retry_budget_ratio: env::var("PROVIDER_RETRY_BUDGET_RATIO")
+                .unwrap_or_else(|_| "0.2".to_string())
+                .parse()
+                .unwrap_or(0.2),
+            consistency_sample_rate: env::var("CONSISTENCY_SAMPLE_RATE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            dbsync_pool_size: env::var("DBSYNC_POOL_SIZE")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse()
+                .unwrap_or(8),
         })
     }
+}
 
-    pub fn blockfrost_base_url(&self) -> String {
-        if self.blockfrost_network == "mainnet" {
-            "https://cardano-mainnet.blockfrost.io/api/v0".to_string()
+impl NetworkConfig {
+    // Each setting is read from `<VAR>_<NETWORK>` (e.g. BLOCKFROST_API_KEY_PREVIEW),
+    // and for the default network also from the plain `<VAR>`
+    fn from_env(
+        network: &Network,
+        is_default: bool,
+        fixtures: Option<&FixtureConfig>,
+    ) -> Result<Self, anyhow::Error> {
+        let var = |name: &str| network_var(name, network, is_default);
+
+        let blockfrost_base_url = provider_base_url(
+            "BLOCKFROST_BASE_URL",
+            var("BLOCKFROST_BASE_URL"),
+            network,
+            |n| n.blockfrost_base_url(),
+        )?;
+        let koios_base_url =
+            provider_base_url("KOIOS_BASE_URL", var("KOIOS_BASE_URL"), network, |n| {
+                n.koios_base_url()
+            })?;
+
+        // GovTools is only hosted for mainnet and preview; elsewhere it stays off
+        // unless GOVTOOLS_BASE_URL and GOVTOOLS_ENABLED are set
+        let govtools_base_url = var("GOVTOOLS_BASE_URL")
+            .or_else(|| network.govtools_base_url().map(str::to_string))
+            .unwrap_or_default();
+        let govtools_enabled = var("GOVTOOLS_ENABLED")
+            .and_then(|s| s.parse::<bool>().ok())
+            .unwrap_or(network.govtools_base_url().is_some());
+        if govtools_enabled {
+            check_url_network("GOVTOOLS_BASE_URL", &govtools_base_url, network)?;
+        }
+
+        let ogmios_url = var("OGMIOS_URL");
+        let indexer = if var("INDEXER_ENABLED")
+            .and_then(|s| s.parse::<bool>().ok())
+            .unwrap_or(false)
+        {
+            Some(indexer_config(&var, ogmios_url.as_deref(), network)?)
         } else {
-            "https://cardano-preview.blockfrost.io/api/v0".to_string()
+            None
+        };
+
+        // Replayed fixtures need no key; recorded ones never contain it
+        let replaying = fixtures.is_some_and(|f| f.mode == FixtureMode::Replay);
+        let blockfrost_api_key = match var("BLOCKFROST_API_KEY") {
+            Some(key) => key,
+            None if replaying => String::new(),
+            None => {
+                return Err(anyhow::anyhow!(
+                    "BLOCKFROST_API_KEY not set for network '{}'",
+                    network
+                ))
+            }
+        };
+
+        Ok(NetworkConfig {
+            network: network.clone(),
+            blockfrost_api_key,
+            blockfrost_base_url,
+            koios_base_url,
+            koios_api_key: var("KOIOS_API_KEY"),
+            govtools_base_url,
+            govtools_enabled,
+            dbsync_url: var("DBSYNC_URL"),
+            ogmios_url,
+            indexer,
+            // Each network's fixtures live in their own subdirectory
+            fixtures: fixtures.map(|fixtures| FixtureConfig {
+                mode: fixtures.mode,
+                dir: fixtures.dir.join(network.as_str()),
+            }),
+        })
+    }
+}
+
+// PROVIDER_FIXTURES=record|replay, with the fixtures under PROVIDER_FIXTURES_DIR
+fn fixtures_from_env() -> Result<Option<FixtureConfig>, anyhow::Error> {
+    let mode = match env::var("PROVIDER_FIXTURES") {
+        Ok(mode) if !mode.trim().is_empty() && mode.trim() != "off" => mode,
+        _ => return Ok(None),
+    };
+    let mode = FixtureMode::parse(&mode).ok_or_else(|| {
+        anyhow::anyhow!(
+            "PROVIDER_FIXTURES '{}' is not one of off, record or replay",
+            mode
+        )
+    })?;
+    Ok(Some(FixtureConfig {
+        mode,
+        dir: PathBuf::from(
+            env::var("PROVIDER_FIXTURES_DIR")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "./fixtures/providers".to_string()),
+        ),
+    }))
+}
+
+// A recording in INDEXER_REPLAY_PATH takes the place of Ogmios chain-sync. The
+// indexer starts from INDEXER_START_POINT, `origin` or `<slot>.<block hash>`.
+fn indexer_config(
+    var: &impl Fn(&str) -> Option<String>,
+    ogmios_url: Option<&str>,
+    network: &Network,
+) -> Result<IndexerConfig, anyhow::Error> {
+    let stream = match (var("INDEXER_REPLAY_PATH"), ogmios_url) {
+        (Some(path), _) => BlockStream::Recorded(PathBuf::from(path)),
+        (None, Some(url)) => BlockStream::Ogmios(url.to_string()),
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "INDEXER_ENABLED needs OGMIOS_URL or INDEXER_REPLAY_PATH for network '{}'",
+                network
+            ))
+        }
+    };
+    let start = match var("INDEXER_START_POINT") {
+        Some(value) => Point::parse(&value).ok_or_else(|| {
+            anyhow::anyhow!(
+                "INDEXER_START_POINT '{}' is neither 'origin' nor '<slot>.<block hash>'",
+                value
+            )
+        })?,
+        None => Point::Origin,
+    };
+    Ok(IndexerConfig {
+        stream,
+        start,
+        store_path: var("INDEXER_STORE_PATH").map(PathBuf::from),
+        save_interval: Duration::from_secs(
+            var("INDEXER_SAVE_SECS")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+        ),
+    })
+}
+
+fn network_var(name: &str, network: &Network, is_default: bool) -> Option<String> {
+    let suffix: String = network
+        .as_str()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    env::var(format!("{}_{}", name, suffix))
+        .ok()
+        .or_else(|| is_default.then(|| env::var(name).ok()).flatten())
+        .filter(|s| !s.trim().is_empty())
+}
+
+// The configured URL if set, otherwise the network's public endpoint. Custom
+// networks have no default, and a URL for another known network is a
+// configuration error.
+fn provider_base_url(
+    var: &str,
+    configured: Option<String>,
+    network: &Network,
+    default: impl Fn(&Network) -> Option<&'static str>,
+) -> Result<String, anyhow::Error> {
+    let url = match configured {
+        Some(url) => url,
+        None => default(network).map(str::to_string).ok_or_else(|| {
+            anyhow::anyhow!("{} must be set for custom network '{}'", var, network)
+        })?,
+    };
+    check_url_network(var, &url, network)?;
+    Ok(url)
+}
+
+fn check_url_network(var: &str, url: &str, network: &Network) -> Result<(), anyhow::Error> {
+    match Network::of_url(url) {
+        Some(url_network) if &url_network != network => Err(anyhow::anyhow!(
+            "{} {} serves {} but it is configured for {}",
+            var,
+            url,
+            url_network,
+            network
+        )),
+        _ => Ok(()),
+    }
+}
+
+// CACHE_STALE_SECS applies to every key kind; CACHE_STALE_SECS_<KIND> (e.g.
+// CACHE_STALE_SECS_DREPS_PAGE) overrides a single kind
+fn stale_windows_from_env() -> HashMap<CacheKeyKind, u64> {
+    let default = env::var("CACHE_STALE_SECS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok());
+
+    CacheKeyKind::ALL
+        .iter()
+        .map(|kind| {
+            let var = format!("CACHE_STALE_SECS_{}", kind.as_str().to_uppercase());
+            let secs = env::var(var)
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .or(default)
+                .unwrap_or_else(|| kind.default_stale_seconds());
+            (*kind, secs)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_provider_urls_to_the_network() {
+        let url = provider_base_url("KOIOS_BASE_URL", None, &Network::Preprod, |n| {
+            n.koios_base_url()
+        })
+        .unwrap();
+        assert_eq!(url, "https://preprod.koios.rest/api/v1");
+
+        let custom = Network::Custom("devnet".to_string());
+        let error =
+            provider_base_url("KOIOS_BASE_URL", None, &custom, |n| n.koios_base_url()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "KOIOS_BASE_URL must be set for custom network 'devnet'"
+        );
+    }
+
+    #[test]
+    fn rejects_a_url_for_another_network() {
+        let error = provider_base_url(
+            "BLOCKFROST_BASE_URL",
+            Some("https://cardano-mainnet.blockfrost.io/api/v0".to_string()),
+            &Network::Preview,
+            |n| n.blockfrost_base_url(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "BLOCKFROST_BASE_URL https://cardano-mainnet.blockfrost.io/api/v0 serves mainnet \
+             but it is configured for preview"
+        );
+
+        assert!(check_url_network(
+            "GOVTOOLS_BASE_URL",
+            "https://be.gov.tools",
+            &Network::Preview
+        )
+        .is_err());
+        // Self-hosted and unknown hosts can't be judged, so they are accepted
+        for network in [Network::Preview, Network::Custom("devnet".to_string())] {
+            assert!(
+                check_url_network("KOIOS_BASE_URL", "http://localhost:8053/api/v1", &network)
+                    .is_ok()
+            );
         }
+        assert!(check_url_network(
+            "KOIOS_BASE_URL",
+            "https://preview.koios.rest/api/v1",
+            &Network::Preview
+        )
+        .is_ok());
     }
 }
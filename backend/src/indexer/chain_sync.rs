@@ -0,0 +1,286 @@
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+// nextBlock requests kept in flight, so syncing isn't bound by round trips
+const PIPELINE_DEPTH: usize = 100;
+// Ogmios error code for findIntersection when none of the points is on chain
+const NO_INTERSECTION: i64 = 1000;
+
+/// A point on the chain, in Ogmios' notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Point {
+    Origin,
+    Block { slot: u64, id: String },
+}
+
+impl Point {
+    /// Parses `origin` or `<slot>.<block hash>`, the form Ogmios and
+    /// cardano-cli print points in.
+    pub fn parse(value: &str) -> Option<Point> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("origin") {
+            return Some(Point::Origin);
+        }
+        let (slot, id) = value.split_once('.')?;
+        let id = id.to_lowercase();
+        (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then_some(Point::Block {
+            slot: slot.parse().ok()?,
+            id,
+        })
+    }
+
+    /// Slot of the point; `None` for the origin.
+    pub fn slot(&self) -> Option<u64> {
+        match self {
+            Point::Origin => None,
+            Point::Block { slot, .. } => Some(*slot),
+        }
+    }
+
+    fn from_json(value: &Value) -> Result<Point, anyhow::Error> {
+        if value == "origin" {
+            return Ok(Point::Origin);
+        }
+        Ok(Point::Block {
+            slot: value["slot"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("point without a slot: {}", value))?,
+            id: value["id"]
+                .as_str()
+                .ok_or_else(|| anyhow!("point without an id: {}", value))?
+                .to_string(),
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Point::Origin => json!("origin"),
+            Point::Block { slot, id } => json!({ "slot": slot, "id": id }),
+        }
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Point::Origin => f.write_str("origin"),
+            Point::Block { slot, id } => write!(f, "{}.{}", slot, id),
+        }
+    }
+}
+
+/// The node's tip as reported alongside every chain-sync response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tip {
+    pub slot: u64,
+    pub height: u64,
+}
+
+impl Tip {
+    fn from_json(value: &Value) -> Tip {
+        Tip {
+            slot: value["slot"].as_u64().unwrap_or_default(),
+            height: value["height"].as_u64().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ChainEvent {
+    /// A block extending the chain, in Ogmios' JSON representation
+    RollForward { block: Value, tip: Tip },
+    /// The node switched forks; everything after `point` is gone
+    RollBackward { point: Point, tip: Tip },
+}
+
+impl ChainEvent {
+    /// Decodes the result of a `nextBlock` request.
+    fn from_result(result: Value) -> Result<ChainEvent, anyhow::Error> {
+        let tip = Tip::from_json(&result["tip"]);
+        match result["direction"].as_str() {
+            Some("forward") => Ok(ChainEvent::RollForward {
+                block: result["block"].clone(),
+                tip,
+            }),
+            Some("backward") => Ok(ChainEvent::RollBackward {
+                point: Point::from_json(&result["point"])?,
+                tip,
+            }),
+            _ => bail!("unexpected nextBlock result: {}", result),
+        }
+    }
+}
+
+/// A stream of chain-sync events: a live Ogmios connection or a recording.
+#[async_trait]
+pub trait BlockSource: Send {
+    /// Positions the stream after the first of `points` it knows (newest
+    /// first), and returns that point.
+    async fn find_intersection(&mut self, points: &[Point]) -> Result<Point, anyhow::Error>;
+
+    /// The next event, or `None` once a recording runs out.
+    async fn next_event(&mut self) -> Result<Option<ChainEvent>, anyhow::Error>;
+}
+
+/// Ogmios' chain-sync protocol over its WebSocket endpoint.
+pub struct OgmiosChainSync {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    in_flight: usize,
+}
+
+impl OgmiosChainSync {
+    /// Connects to `url`, given as the same http(s):// or ws(s):// URL used for
+    /// ledger-state queries.
+    pub async fn connect(url: &str) -> Result<Self, anyhow::Error> {
+        let url = if let Some(rest) = url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else {
+            url.to_string()
+        };
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .with_context(|| format!("connecting to {}", url))?;
+        Ok(Self {
+            socket,
+            in_flight: 0,
+        })
+    }
+
+    async fn request(&mut self, method: &str, params: Option<Value>) -> Result<(), anyhow::Error> {
+        let mut request = json!({ "jsonrpc": "2.0", "method": method });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+        self.socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .with_context(|| format!("sending {}", method))
+    }
+
+    // The next JSON-RPC response; errors come back as `(code, message)`
+    async fn response(&mut self) -> Result<Result<Value, (i64, String)>, anyhow::Error> {
+        loop {
+            let message = self
+                .socket
+                .next()
+                .await
+                .ok_or_else(|| anyhow!("Ogmios closed the connection"))?
+                .context("reading from Ogmios")?;
+            let text = match message {
+                Message::Text(text) => text,
+                Message::Binary(bytes) => String::from_utf8(bytes)?,
+                Message::Close(_) => bail!("Ogmios closed the connection"),
+                // Pings are answered by the socket itself
+                _ => continue,
+            };
+            let mut response: Value = serde_json::from_str(&text)
+                .with_context(|| "decoding Ogmios response".to_string())?;
+            if let Some(error) = response.get("error") {
+                return Ok(Err((
+                    error["code"].as_i64().unwrap_or_default(),
+                    error["message"].as_str().unwrap_or_default().to_string(),
+                )));
+            }
+            return Ok(Ok(response["result"].take()));
+        }
+    }
+}
+
+#[async_trait]
+impl BlockSource for OgmiosChainSync {
+    async fn find_intersection(&mut self, points: &[Point]) -> Result<Point, anyhow::Error> {
+        let points: Vec<Value> = points.iter().map(Point::to_json).collect();
+        self.request("findIntersection", Some(json!({ "points": points })))
+            .await?;
+        match self.response().await? {
+            Ok(result) => Point::from_json(&result["intersection"]),
+            Err((NO_INTERSECTION, _)) => bail!("none of the known points is on the node's chain"),
+            Err((code, message)) => bail!("findIntersection failed ({}): {}", code, message),
+        }
+    }
+
+    async fn next_event(&mut self) -> Result<Option<ChainEvent>, anyhow::Error> {
+        while self.in_flight < PIPELINE_DEPTH {
+            self.request("nextBlock", None).await?;
+            self.in_flight += 1;
+        }
+        let result = self
+            .response()
+            .await?
+            .map_err(|(code, message)| anyhow!("nextBlock failed ({}): {}", code, message))?;
+        self.in_flight -= 1;
+        ChainEvent::from_result(result).map(Some)
+    }
+}
+
+/// Replays `nextBlock` responses recorded from Ogmios, one JSON document per
+/// line: either the whole JSON-RPC response or just its `result`.
+pub struct RecordedBlocks {
+    events: std::vec::IntoIter<ChainEvent>,
+}
+
+impl RecordedBlocks {
+    pub async fn open(path: &Path) -> Result<Self, anyhow::Error> {
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("reading {}", path.display()))?;
+        let events = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                let mut frame: Value = serde_json::from_str(line)
+                    .with_context(|| format!("{}:{}", path.display(), number + 1))?;
+                let result = match frame.get_mut("result") {
+                    Some(result) => result.take(),
+                    None => frame,
+                };
+                ChainEvent::from_result(result)
+                    .with_context(|| format!("{}:{}", path.display(), number + 1))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            events: events.into_iter(),
+        })
+    }
+}
+
+#[async_trait]
+impl BlockSource for RecordedBlocks {
+    // The recording starts wherever it was taken; resuming from a stored tip
+    // skips past the recorded block with that id
+    async fn find_intersection(&mut self, points: &[Point]) -> Result<Point, anyhow::Error> {
+        let events: Vec<ChainEvent> = self.events.by_ref().collect();
+        let position = |point: &Point| {
+            events.iter().position(|event| match (event, point) {
+                (ChainEvent::RollForward { block, .. }, Point::Block { id, .. }) => {
+                    block["id"].as_str() == Some(id.as_str())
+                }
+                _ => false,
+            })
+        };
+        let (start, intersection) = points
+            .iter()
+            .find_map(|point| position(point).map(|index| (index + 1, point.clone())))
+            .unwrap_or((0, Point::Origin));
+        self.events = events
+            .into_iter()
+            .skip(start)
+            .collect::<Vec<_>>()
+            .into_iter();
+        Ok(intersection)
+    }
+
+    async fn next_event(&mut self) -> Result<Option<ChainEvent>, anyhow::Error> {
+        Ok(self.events.next())
+    }
+}
@@ -0,0 +1,245 @@
+use super::store::{
+    Anchor, CommitteeCertificate, DRepCertificate, DRepCertificateKind, IndexedProposal,
+    IndexedVote, VoteDelegation,
+};
+use crate::models::VoteChoice;
+use crate::utils::drep_id::{hex_to_bech32, normalize_to_cip129};
+use anyhow::anyhow;
+use serde_json::Value;
+use tracing::debug;
+
+// Eras before Conway carry no governance certificates, proposals or votes
+const PRE_CONWAY_ERAS: [&str; 6] = ["byron", "shelley", "allegra", "mary", "alonzo", "babbage"];
+
+/// The governance content of one block, along with where the block sits.
+#[derive(Debug, Default)]
+pub struct BlockGovernance {
+    pub slot: u64,
+    pub id: String,
+    pub height: u64,
+    /// Keyed by DRep credential hash
+    pub dreps: Vec<(String, DRepCertificate)>,
+    /// Keyed by cold credential hash
+    pub committee: Vec<(String, CommitteeCertificate)>,
+    /// Keyed by stake credential hash
+    pub delegations: Vec<(String, VoteDelegation)>,
+    pub proposals: Vec<IndexedProposal>,
+    pub votes: Vec<IndexedVote>,
+}
+
+impl BlockGovernance {
+    /// Reads a block in Ogmios' JSON representation. Epoch boundary blocks
+    /// have no slot of their own and give `None`.
+    pub fn from_block(block: &Value) -> Result<Option<Self>, anyhow::Error> {
+        let Some(slot) = block["slot"].as_u64() else {
+            return Ok(None);
+        };
+        let mut governance = BlockGovernance {
+            slot,
+            id: block["id"]
+                .as_str()
+                .ok_or_else(|| anyhow!("block at slot {} has no id", slot))?
+                .to_string(),
+            height: block["height"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("block at slot {} has no height", slot))?,
+            ..Default::default()
+        };
+
+        let era = block["era"].as_str().unwrap_or_default();
+        if PRE_CONWAY_ERAS.contains(&era) {
+            return Ok(Some(governance));
+        }
+        for tx in block["transactions"].as_array().into_iter().flatten() {
+            // A failed script validation only takes the collateral; nothing
+            // else in the transaction applies
+            if tx["spends"] == "collateral" {
+                continue;
+            }
+            governance.add_transaction(tx);
+        }
+        Ok(Some(governance))
+    }
+
+    fn add_transaction(&mut self, tx: &Value) {
+        let Some(tx_hash) = tx["id"].as_str() else {
+            return;
+        };
+        for certificate in tx["certificates"].as_array().into_iter().flatten() {
+            self.add_certificate(tx_hash, certificate);
+        }
+        for (index, proposal) in tx["proposals"].as_array().into_iter().flatten().enumerate() {
+            self.add_proposal(tx_hash, index as u32, proposal);
+        }
+        for (index, vote) in tx["votes"].as_array().into_iter().flatten().enumerate() {
+            if !self.add_vote(tx_hash, index as u32, vote) {
+                debug!("Skipping unreadable vote in {}: {}", tx_hash, vote);
+            }
+        }
+    }
+
+    fn add_certificate(&mut self, tx_hash: &str, certificate: &Value) {
+        let slot = self.slot;
+        let drep_certificate = |kind| DRepCertificate {
+            slot,
+            tx_hash: tx_hash.to_string(),
+            kind,
+            deposit: lovelace(&certificate["deposit"]),
+            anchor: Anchor::from_json(&certificate["metadata"]),
+        };
+
+        match certificate["type"].as_str().unwrap_or_default() {
+            "delegateRepresentativeRegistration" => {
+                if let Some(id) = certificate["delegateRepresentative"]["id"].as_str() {
+                    self.dreps.push((
+                        id.to_string(),
+                        drep_certificate(DRepCertificateKind::Registration),
+                    ));
+                }
+            }
+            "delegateRepresentativeUpdate" => {
+                if let Some(id) = certificate["delegateRepresentative"]["id"].as_str() {
+                    self.dreps.push((
+                        id.to_string(),
+                        drep_certificate(DRepCertificateKind::Update),
+                    ));
+                }
+            }
+            "delegateRepresentativeRetirement" => {
+                if let Some(id) = certificate["delegateRepresentative"]["id"].as_str() {
+                    self.dreps.push((
+                        id.to_string(),
+                        drep_certificate(DRepCertificateKind::Retirement),
+                    ));
+                }
+            }
+            "constitutionalCommitteeDelegation" => {
+                if let Some(cold) = certificate["member"]["id"].as_str() {
+                    self.committee.push((
+                        cold.to_string(),
+                        CommitteeCertificate {
+                            slot,
+                            tx_hash: tx_hash.to_string(),
+                            hot_key: certificate["delegate"]["id"].as_str().map(str::to_string),
+                        },
+                    ));
+                }
+            }
+            "constitutionalCommitteeRetirement" => {
+                if let Some(cold) = certificate["member"]["id"].as_str() {
+                    self.committee.push((
+                        cold.to_string(),
+                        CommitteeCertificate {
+                            slot,
+                            tx_hash: tx_hash.to_string(),
+                            hot_key: None,
+                        },
+                    ));
+                }
+            }
+            // Stake delegations only matter here when they carry a vote delegation
+            "stakeDelegation" => {
+                let drep = &certificate["delegateRepresentative"];
+                let target = match drep["type"].as_str() {
+                    Some("registered") => drep["id"].as_str().map(str::to_string),
+                    Some("abstain") => Some("abstain".to_string()),
+                    Some("noConfidence") => Some("no_confidence".to_string()),
+                    _ => return,
+                };
+                if let (Some(credential), Some(drep)) = (certificate["credential"].as_str(), target)
+                {
+                    self.delegations.push((
+                        credential.to_string(),
+                        VoteDelegation {
+                            slot,
+                            tx_hash: tx_hash.to_string(),
+                            drep: Some(drep),
+                        },
+                    ));
+                }
+            }
+            // Deregistering a stake credential drops its vote delegation
+            "stakeCredentialDeregistration" => {
+                if let Some(credential) = certificate["credential"].as_str() {
+                    self.delegations.push((
+                        credential.to_string(),
+                        VoteDelegation {
+                            slot,
+                            tx_hash: tx_hash.to_string(),
+                            drep: None,
+                        },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_proposal(&mut self, tx_hash: &str, index: u32, proposal: &Value) {
+        self.proposals.push(IndexedProposal {
+            slot: self.slot,
+            tx_hash: tx_hash.to_string(),
+            index,
+            action_type: proposal["action"]["type"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            deposit: lovelace(&proposal["deposit"]),
+            return_account: proposal["returnAccount"].as_str().map(str::to_string),
+            anchor: Anchor::from_json(&proposal["metadata"]),
+        });
+    }
+
+    // False when the vote is missing a part the index needs
+    fn add_vote(&mut self, tx_hash: &str, index: u32, vote: &Value) -> bool {
+        let issuer = &vote["issuer"];
+        let Some(id) = issuer["id"].as_str() else {
+            return false;
+        };
+        let (voter_type, voter_id, credential) = match issuer["role"].as_str() {
+            Some("delegateRepresentative") => {
+                let view = hex_to_bech32(id, issuer["from"] == "script", false)
+                    .ok()
+                    .map(|view| normalize_to_cip129(&view).unwrap_or(view));
+                ("drep", view.unwrap_or_else(|| id.to_string()), Some(id))
+            }
+            Some("stakePoolOperator") => ("spo", id.to_string(), None),
+            Some("constitutionalCommittee") => ("cc", id.to_string(), None),
+            _ => return false,
+        };
+        let (Some(proposal_tx_hash), Some(proposal_index), Some(choice)) = (
+            vote["proposal"]["transaction"]["id"].as_str(),
+            vote["proposal"]["index"].as_u64(),
+            vote["vote"].as_str().and_then(VoteChoice::parse),
+        ) else {
+            return false;
+        };
+
+        self.votes.push(IndexedVote {
+            slot: self.slot,
+            height: self.height,
+            tx_hash: tx_hash.to_string(),
+            index,
+            proposal_tx_hash: proposal_tx_hash.to_string(),
+            proposal_index: proposal_index as u32,
+            voter_type: voter_type.to_string(),
+            voter_id,
+            credential: credential.map(str::to_string),
+            vote: choice,
+        });
+        true
+    }
+}
+
+impl Anchor {
+    fn from_json(value: &Value) -> Option<Anchor> {
+        Some(Anchor {
+            url: value["url"].as_str()?.to_string(),
+            hash: value["hash"].as_str()?.to_string(),
+        })
+    }
+}
+
+fn lovelace(value: &Value) -> Option<u64> {
+    value["ada"]["lovelace"].as_u64()
+}
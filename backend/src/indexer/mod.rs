@@ -0,0 +1,186 @@
+pub mod chain_sync;
+pub mod extract;
+pub mod store;
+
+pub use chain_sync::Point;
+pub use store::GovernanceIndex;
+
+use crate::providers::CachedProviderRouter;
+use crate::services::chain_tip::invalidate_votes;
+use anyhow::anyhow;
+use chain_sync::{BlockSource, ChainEvent, OgmiosChainSync, RecordedBlocks};
+use extract::BlockGovernance;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+// Blocks between progress lines while catching up
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// Where the indexer reads blocks from.
+#[derive(Debug, Clone)]
+pub enum BlockStream {
+    /// Ogmios chain-sync
+    Ogmios(String),
+    /// A recorded `nextBlock` stream, replayed once
+    Recorded(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    pub stream: BlockStream,
+    /// Where to start when the index is empty
+    pub start: Point,
+    /// File the index is saved to and loaded from
+    pub store_path: Option<PathBuf>,
+    pub save_interval: Duration,
+}
+
+/// Follows the chain and feeds each block's governance content into the
+/// `GovernanceIndex`, undoing blocks the node rolls back. Once caught up, the
+/// votes it sees also invalidate the cache entries they change.
+pub struct ChainIndexer {
+    router: CachedProviderRouter,
+    index: Arc<GovernanceIndex>,
+    config: IndexerConfig,
+    last_save: Instant,
+}
+
+impl ChainIndexer {
+    pub fn new(
+        router: CachedProviderRouter,
+        index: Arc<GovernanceIndex>,
+        config: IndexerConfig,
+    ) -> Self {
+        Self {
+            router,
+            index,
+            config,
+            last_save: Instant::now(),
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(mut self) {
+        let mut delay = MIN_RECONNECT_DELAY;
+        loop {
+            let started = Instant::now();
+            match self.follow().await {
+                Ok(()) => {
+                    info!(
+                        "Finished replaying recorded blocks for {}",
+                        self.router.network()
+                    );
+                    self.save().await;
+                    return;
+                }
+                Err(error) => {
+                    warn!(
+                        "Chain indexer for {} stopped: {:#}",
+                        self.router.network(),
+                        error
+                    );
+                    self.index.record_error(format!("{:#}", error));
+                    self.save().await;
+                }
+            }
+            // A connection that ran for a while earns a quick reconnect
+            if started.elapsed() > MAX_RECONNECT_DELAY {
+                delay = MIN_RECONNECT_DELAY;
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    // Runs until the connection fails, or a recording runs out
+    async fn follow(&mut self) -> Result<(), anyhow::Error> {
+        let mut source: Box<dyn BlockSource> = match &self.config.stream {
+            BlockStream::Ogmios(url) => Box::new(OgmiosChainSync::connect(url).await?),
+            BlockStream::Recorded(path) => Box::new(RecordedBlocks::open(path).await?),
+        };
+        let points = self.index.intersection_points(&self.config.start);
+        let intersection = source.find_intersection(&points).await?;
+        info!(
+            "Indexing {} governance from {}",
+            self.router.network(),
+            intersection
+        );
+
+        let mut was_synced = self.index.is_synced();
+        while let Some(event) = source.next_event().await? {
+            match event {
+                ChainEvent::RollForward { block, tip } => {
+                    let Some(governance) = BlockGovernance::from_block(&block)? else {
+                        continue;
+                    };
+                    let height = governance.height;
+                    let votes = self.index.apply(governance, tip);
+                    if was_synced && !votes.is_empty() {
+                        invalidate_votes(self.router.cache(), &votes).await;
+                    }
+                    if !was_synced && height % PROGRESS_INTERVAL == 0 {
+                        info!(
+                            "Chain index for {} at block {} of {}",
+                            self.router.network(),
+                            height,
+                            tip.height
+                        );
+                    }
+                }
+                ChainEvent::RollBackward { point, tip } => {
+                    match self.index.rollback_to(&point, tip) {
+                        Ok(votes) if was_synced && !votes.is_empty() => {
+                            invalidate_votes(self.router.cache(), &votes).await;
+                        }
+                        Ok(_) => {}
+                        Err(_) => {
+                            // Only the start point is safe to resume from now
+                            self.index.reset();
+                            return Err(anyhow!(
+                                "rollback to {} is past the undo window; re-indexing from {}",
+                                point,
+                                self.config.start
+                            ));
+                        }
+                    }
+                }
+            }
+
+            let synced = self.index.is_synced();
+            if synced && !was_synced {
+                info!(
+                    "Chain index for {} caught up with the chain tip",
+                    self.router.network()
+                );
+            }
+            was_synced = synced;
+
+            if self.last_save.elapsed() >= self.config.save_interval {
+                self.save().await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn save(&mut self) {
+        self.last_save = Instant::now();
+        let Some(path) = &self.config.store_path else {
+            return;
+        };
+        if let Err(e) = self.index.save(path).await {
+            warn!(
+                "Failed to save {} chain index: {:#}",
+                self.router.network(),
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,713 @@
+use super::chain_sync::{Point, Tip};
+use super::extract::BlockGovernance;
+use crate::models::{ActionVoteRecord, DRepVotingHistory, RecentVote, VoteChoice};
+use crate::network::Network;
+use crate::utils::drep_id::drep_credential_hash;
+use crate::utils::proposal_id::to_cip129_proposal_id;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bump whenever a stored record changes shape, so an index written by an
+/// older build is rebuilt instead of loaded.
+pub const INDEX_VERSION: u32 = 1;
+// Blocks whose writes are kept for undoing rollbacks. The node never rolls
+// back further than the security parameter k.
+const ROLLBACK_WINDOW: usize = 2160;
+// Within this many blocks of the node's tip the index counts as caught up
+const SYNC_TOLERANCE: u64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anchor {
+    pub url: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DRepCertificateKind {
+    Registration,
+    Update,
+    Retirement,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DRepCertificate {
+    pub slot: u64,
+    pub tx_hash: String,
+    pub kind: DRepCertificateKind,
+    pub deposit: Option<u64>,
+    pub anchor: Option<Anchor>,
+}
+
+/// A cold credential authorizing a hot key, or resigning when `hot_key` is
+/// `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitteeCertificate {
+    pub slot: u64,
+    pub tx_hash: String,
+    pub hot_key: Option<String>,
+}
+
+/// A stake credential's vote delegation: a DRep credential hash, `abstain` or
+/// `no_confidence`, or `None` once the credential is deregistered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteDelegation {
+    pub slot: u64,
+    pub tx_hash: String,
+    pub drep: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedProposal {
+    pub slot: u64,
+    pub tx_hash: String,
+    pub index: u32,
+    /// Ogmios' action type, e.g. `treasuryWithdrawals`
+    pub action_type: String,
+    pub deposit: Option<u64>,
+    pub return_account: Option<String>,
+    pub anchor: Option<Anchor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedVote {
+    pub slot: u64,
+    pub height: u64,
+    pub tx_hash: String,
+    /// Position among the transaction's votes
+    pub index: u32,
+    pub proposal_tx_hash: String,
+    pub proposal_index: u32,
+    /// `drep`, `spo` or `cc`, as in `ActionVoteRecord`
+    pub voter_type: String,
+    /// CIP-129 DRep ID, pool ID or committee hot key hash
+    pub voter_id: String,
+    /// DRep credential hash, for voting history lookups
+    pub credential: Option<String>,
+    pub vote: VoteChoice,
+}
+
+trait Slotted {
+    fn slot(&self) -> u64;
+}
+
+macro_rules! slotted {
+    ($($record:ty),*) => {
+        $(impl Slotted for $record {
+            fn slot(&self) -> u64 {
+                self.slot
+            }
+        })*
+    };
+}
+slotted!(
+    DRepCertificate,
+    CommitteeCertificate,
+    VoteDelegation,
+    IndexedVote
+);
+
+/// A block in the rollback window and the keys it wrote to.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BlockWrites {
+    slot: u64,
+    id: String,
+    height: u64,
+    dreps: Vec<String>,
+    committee: Vec<String>,
+    delegations: Vec<String>,
+    proposals: Vec<String>,
+    votes: Vec<String>,
+    drep_votes: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct IndexState {
+    /// Newest blocks, oldest first
+    blocks: VecDeque<BlockWrites>,
+    /// Slot of the newest block dropped from `blocks`
+    horizon: Option<u64>,
+    /// Histories keyed by credential hash, oldest first
+    dreps: HashMap<String, Vec<DRepCertificate>>,
+    committee: HashMap<String, Vec<CommitteeCertificate>>,
+    delegations: HashMap<String, Vec<VoteDelegation>>,
+    /// Keyed by `tx_hash#index`
+    proposals: HashMap<String, IndexedProposal>,
+    /// Votes by proposal (`tx_hash#index`) and by DRep credential hash
+    votes: HashMap<String, Vec<IndexedVote>>,
+    drep_votes: HashMap<String, Vec<IndexedVote>>,
+    #[serde(skip)]
+    chain_tip: Tip,
+    #[serde(skip)]
+    blocks_applied: u64,
+    #[serde(skip)]
+    rollbacks: u64,
+    #[serde(skip)]
+    last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct IndexFileRef<'a> {
+    version: u32,
+    network: &'a str,
+    written_at: u64,
+    state: &'a IndexState,
+}
+
+#[derive(Deserialize)]
+struct IndexHeader {
+    version: u32,
+    network: String,
+}
+
+#[derive(Deserialize)]
+struct IndexFile {
+    state: IndexState,
+}
+
+/// Progress of the chain index, as reported by `/health`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub synced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+    pub chain_tip_height: u64,
+    pub blocks_applied: u64,
+    pub rollbacks: u64,
+    pub proposals: usize,
+    pub votes: usize,
+    pub dreps: usize,
+    pub committee_members: usize,
+    pub vote_delegations: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// The rollback target is older than every block the index can undo.
+#[derive(Debug)]
+pub struct RollbackTooDeep;
+
+/// Governance certificates, proposals and votes read from the chain by the
+/// indexer, kept in memory and saved to disk between runs. Every record
+/// carries its slot, so a rollback truncates exactly what the undone blocks
+/// wrote.
+pub struct GovernanceIndex {
+    network: Network,
+    state: RwLock<IndexState>,
+}
+
+impl GovernanceIndex {
+    pub fn new(network: Network) -> Self {
+        Self {
+            network,
+            state: RwLock::new(IndexState::default()),
+        }
+    }
+
+    /// Loads the index saved at `path`. A missing file, or one written by
+    /// another build or for another network, gives an empty index.
+    pub async fn load(network: Network, path: &Path) -> Result<Self, anyhow::Error> {
+        let bytes = match tokio::fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new(network)),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        // Check the header first; an older build's records may not parse
+        let header: IndexHeader = serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing {}", path.display()))?;
+        if header.version != INDEX_VERSION || header.network != network.as_str() {
+            tracing::info!(
+                "Discarding chain index {} (version {} for {}, expected {} for {})",
+                path.display(),
+                header.version,
+                header.network,
+                INDEX_VERSION,
+                network
+            );
+            return Ok(Self::new(network));
+        }
+        let file: IndexFile = serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing {}", path.display()))?;
+        Ok(Self {
+            network,
+            state: RwLock::new(file.state),
+        })
+    }
+
+    /// Writes the index next to `path` and renames it into place, like the
+    /// cache snapshot.
+    pub async fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let bytes = {
+            let state = self.state.read().unwrap();
+            serde_json::to_vec(&IndexFileRef {
+                version: INDEX_VERSION,
+                network: self.network.as_str(),
+                written_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                state: &state,
+            })?
+        };
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, bytes)
+            .await
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        tokio::fs::rename(&tmp, path)
+            .await
+            .with_context(|| format!("renaming {} to {}", tmp.display(), path.display()))?;
+        Ok(())
+    }
+
+    /// Points to resume chain-sync from: recent blocks, newest first and
+    /// spaced out exponentially, then `start`.
+    pub fn intersection_points(&self, start: &Point) -> Vec<Point> {
+        let state = self.state.read().unwrap();
+        let mut points = Vec::new();
+        let mut offset = 0;
+        while let Some(block) = state
+            .blocks
+            .len()
+            .checked_sub(offset + 1)
+            .and_then(|index| state.blocks.get(index))
+        {
+            points.push(Point::Block {
+                slot: block.slot,
+                id: block.id.clone(),
+            });
+            offset = offset * 2 + 1;
+        }
+        points.push(start.clone());
+        points
+    }
+
+    /// Records a block's governance content and returns its votes in the form
+    /// cache invalidation takes.
+    pub fn apply(&self, block: BlockGovernance, tip: Tip) -> Vec<RecentVote> {
+        let mut state = self.state.write().unwrap();
+        state.chain_tip = tip;
+        state.blocks_applied += 1;
+        state.last_error = None;
+
+        let mut writes = BlockWrites {
+            slot: block.slot,
+            id: block.id,
+            height: block.height,
+            ..Default::default()
+        };
+        for (key, certificate) in block.dreps {
+            writes.dreps.push(key.clone());
+            state.dreps.entry(key).or_default().push(certificate);
+        }
+        for (key, certificate) in block.committee {
+            writes.committee.push(key.clone());
+            state.committee.entry(key).or_default().push(certificate);
+        }
+        for (key, delegation) in block.delegations {
+            writes.delegations.push(key.clone());
+            state.delegations.entry(key).or_default().push(delegation);
+        }
+        for proposal in block.proposals {
+            let key = proposal_key(&proposal.tx_hash, proposal.index);
+            writes.proposals.push(key.clone());
+            state.proposals.insert(key, proposal);
+        }
+        let recent = block.votes.iter().map(IndexedVote::as_recent).collect();
+        for vote in block.votes {
+            if let Some(credential) = &vote.credential {
+                writes.drep_votes.push(credential.clone());
+                state
+                    .drep_votes
+                    .entry(credential.clone())
+                    .or_default()
+                    .push(vote.clone());
+            }
+            let key = proposal_key(&vote.proposal_tx_hash, vote.proposal_index);
+            writes.votes.push(key.clone());
+            state.votes.entry(key).or_default().push(vote);
+        }
+
+        state.blocks.push_back(writes);
+        while state.blocks.len() > ROLLBACK_WINDOW {
+            state.horizon = state.blocks.pop_front().map(|block| block.slot);
+        }
+        recent
+    }
+
+    /// Undoes every block after `point` and returns the votes that went with
+    /// them.
+    pub fn rollback_to(&self, point: &Point, tip: Tip) -> Result<Vec<RecentVote>, RollbackTooDeep> {
+        let mut state = self.state.write().unwrap();
+        state.chain_tip = tip;
+        let target = point.slot();
+        let kept = |slot: u64| target.is_some_and(|target| slot <= target);
+
+        if state.horizon.is_some()
+            && !matches!(state.blocks.front(), Some(oldest) if kept(oldest.slot))
+        {
+            return Err(RollbackTooDeep);
+        }
+
+        let mut removed = Vec::new();
+        let mut undone = 0;
+        while state.blocks.back().is_some_and(|block| !kept(block.slot)) {
+            let Some(block) = state.blocks.pop_back() else {
+                break;
+            };
+            undone += 1;
+            for key in &block.dreps {
+                truncate(&mut state.dreps, key, target);
+            }
+            for key in &block.committee {
+                truncate(&mut state.committee, key, target);
+            }
+            for key in &block.delegations {
+                truncate(&mut state.delegations, key, target);
+            }
+            for key in &block.proposals {
+                state.proposals.remove(key);
+            }
+            for key in &block.drep_votes {
+                truncate(&mut state.drep_votes, key, target);
+            }
+            for key in &block.votes {
+                removed.extend(
+                    truncate(&mut state.votes, key, target)
+                        .iter()
+                        .map(IndexedVote::as_recent),
+                );
+            }
+        }
+        if undone > 0 {
+            state.rollbacks += 1;
+            tracing::info!("Chain index rolled back {} blocks to {}", undone, point);
+        }
+        Ok(removed)
+    }
+
+    /// Drops everything, to re-index from the start point.
+    pub fn reset(&self) {
+        let mut state = self.state.write().unwrap();
+        let chain_tip = state.chain_tip;
+        *state = IndexState {
+            chain_tip,
+            ..Default::default()
+        };
+    }
+
+    pub fn record_error(&self, error: String) {
+        self.state.write().unwrap().last_error = Some(error);
+    }
+
+    /// Whether the index has caught up with the node's tip, and so can answer
+    /// for votes cast up to now.
+    pub fn is_synced(&self) -> bool {
+        let state = self.state.read().unwrap();
+        is_synced(&state)
+    }
+
+    pub fn status(&self) -> IndexStatus {
+        let state = self.state.read().unwrap();
+        let tip = state.blocks.back();
+        IndexStatus {
+            synced: is_synced(&state),
+            slot: tip.map(|block| block.slot),
+            block_height: tip.map(|block| block.height),
+            chain_tip_height: state.chain_tip.height,
+            blocks_applied: state.blocks_applied,
+            rollbacks: state.rollbacks,
+            proposals: state.proposals.len(),
+            votes: state.votes.values().map(Vec::len).sum(),
+            dreps: state.dreps.len(),
+            committee_members: state.committee.len(),
+            vote_delegations: state.delegations.len(),
+            last_error: state.last_error.clone(),
+        }
+    }
+
+    /// The latest vote of each voter on a proposal, newest first. `None` when
+    /// the proposal was submitted before the index's start point, so its
+    /// votes may be incomplete.
+    pub fn action_votes(&self, tx_hash: &str, index: u32) -> Option<Vec<ActionVoteRecord>> {
+        let key = proposal_key(tx_hash, index);
+        let state = self.state.read().unwrap();
+        state.proposals.get(&key)?;
+
+        let genesis = self.network.genesis();
+        let mut seen = std::collections::HashSet::new();
+        let records = state
+            .votes
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .rev()
+            .filter(|vote| seen.insert((vote.voter_type.as_str(), vote.voter_id.as_str())))
+            .map(|vote| ActionVoteRecord {
+                voter_identifier: vote.voter_id.clone(),
+                voter_type: vote.voter_type.clone(),
+                vote: Some(vote.vote.clone()),
+                voting_power: None,
+                tx_hash: Some(vote.tx_hash.clone()),
+                cert_index: Some(vote.index),
+                block_time: genesis.and_then(|genesis| genesis.slot_time(vote.slot)),
+            })
+            .collect();
+        Some(records)
+    }
+
+    /// Every vote a DRep cast, newest first. `None` when the DRep registered
+    /// before the index's start point.
+    pub fn drep_votes(&self, id: &str) -> Option<Vec<DRepVotingHistory>> {
+        let credential = drep_credential_hash(id)?;
+        let state = self.state.read().unwrap();
+        state.dreps.get(&credential)?;
+
+        let genesis = self.network.genesis();
+        let history = state
+            .drep_votes
+            .get(&credential)
+            .into_iter()
+            .flatten()
+            .rev()
+            .map(|vote| {
+                let proposal_id =
+                    to_cip129_proposal_id(&vote.proposal_tx_hash, vote.proposal_index).ok();
+                DRepVotingHistory {
+                    tx_hash: Some(vote.tx_hash.clone()),
+                    cert_index: Some(vote.index),
+                    action_id: proposal_id.clone(),
+                    proposal_id,
+                    proposal_tx_hash: Some(vote.proposal_tx_hash.clone()),
+                    proposal_cert_index: Some(vote.proposal_index),
+                    vote: vote_str(&vote.vote).to_string(),
+                    voting_power: None,
+                    epoch: genesis.and_then(|genesis| genesis.slot_epoch(vote.slot)),
+                }
+            })
+            .collect();
+        Some(history)
+    }
+}
+
+impl IndexedVote {
+    fn as_recent(&self) -> RecentVote {
+        RecentVote {
+            proposal_id: to_cip129_proposal_id(&self.proposal_tx_hash, self.proposal_index).ok(),
+            proposal_tx_hash: Some(self.proposal_tx_hash.clone()),
+            voter_role: self.voter_type.clone(),
+            voter_id: self.voter_id.clone(),
+            block_height: self.height,
+        }
+    }
+}
+
+fn is_synced(state: &IndexState) -> bool {
+    state.chain_tip.height > 0
+        && state
+            .blocks
+            .back()
+            .is_some_and(|block| block.height + SYNC_TOLERANCE >= state.chain_tip.height)
+}
+
+fn proposal_key(tx_hash: &str, index: u32) -> String {
+    format!("{}#{}", tx_hash.to_lowercase(), index)
+}
+
+fn vote_str(vote: &VoteChoice) -> &'static str {
+    match vote {
+        VoteChoice::Yes => "yes",
+        VoteChoice::No => "no",
+        VoteChoice::Abstain => "abstain",
+    }
+}
+
+// Drops the records after `target` (all of them for the origin) from one
+// history, returning them
+fn truncate<T: Slotted>(
+    map: &mut HashMap<String, Vec<T>>,
+    key: &str,
+    target: Option<u64>,
+) -> Vec<T> {
+    let Some(records) = map.get_mut(key) else {
+        return Vec::new();
+    };
+    let keep = records
+        .iter()
+        .position(|record| !matches!(target, Some(target) if record.slot() <= target))
+        .unwrap_or(records.len());
+    let removed = records.split_off(keep);
+    if records.is_empty() {
+        map.remove(key);
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::chain_sync::{BlockSource, ChainEvent, RecordedBlocks};
+
+    const DREP: &str = "27228b027fa9c6f5cb5beacef67d70599eb8b720ddd2089e940a774d";
+    const COLD: &str = "e1c554243a0c006d50dcb196a8e6e3f25927cbc9e255b7d6055dae1b";
+    const STAKE: &str = "8f448a947d3413e6fc85f9a7f0d27b7502579eb1f7f7d44e4eb0de7a";
+    const PROPOSAL_TX: &str = "07981c27153a7f7b19b5d0f80f2b23e4ceb683285c28d2af001f750889b59315";
+
+    fn point(slot: u64) -> Point {
+        Point::Block {
+            slot,
+            id: "0".repeat(64),
+        }
+    }
+
+    fn tip() -> Tip {
+        Tip {
+            slot: 60566500,
+            height: 3000005,
+        }
+    }
+
+    fn proposal_votes(state: &IndexState) -> Vec<(&str, &str, u64)> {
+        state
+            .votes
+            .get(&proposal_key(PROPOSAL_TX, 0))
+            .into_iter()
+            .flatten()
+            .map(|vote| (vote.voter_type.as_str(), vote_str(&vote.vote), vote.slot))
+            .collect()
+    }
+
+    // Feeds the recorded stream into the index the way the indexer does, and
+    // returns what each rollback undid
+    async fn replay(index: &GovernanceIndex) -> Vec<Vec<RecentVote>> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/ogmios/chain-sync.jsonl");
+        let mut source = RecordedBlocks::open(&path).await.unwrap();
+        source.find_intersection(&[Point::Origin]).await.unwrap();
+        let mut rollbacks = Vec::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            match event {
+                ChainEvent::RollForward { block, tip } => {
+                    if let Some(governance) = BlockGovernance::from_block(&block).unwrap() {
+                        index.apply(governance, tip);
+                    }
+                }
+                ChainEvent::RollBackward { point, tip } => {
+                    let removed = index.rollback_to(&point, tip).unwrap();
+                    if point.slot() == Some(60566420) {
+                        // The fork dropped only the block at 60566440
+                        let state = index.state.read().unwrap();
+                        assert_eq!(
+                            proposal_votes(&state),
+                            vec![("drep", "yes", 60566420), ("spo", "abstain", 60566420),]
+                        );
+                        assert_eq!(state.drep_votes[DREP].len(), 1);
+                        assert!(state.proposals.contains_key(&proposal_key(PROPOSAL_TX, 0)));
+                        assert!(state.dreps.contains_key(DREP));
+                        assert!(state.committee.contains_key(COLD));
+                        assert!(state.delegations.is_empty());
+                        assert_eq!(state.blocks.back().unwrap().slot, 60566420);
+                    }
+                    rollbacks.push(removed);
+                }
+            }
+        }
+        rollbacks
+    }
+
+    #[tokio::test]
+    async fn recorded_fork_is_undone() {
+        let index = GovernanceIndex::new(Network::Preview);
+        let rollbacks = replay(&index).await;
+
+        // The stream opens with a rollback to its start point, which undoes
+        // nothing
+        assert_eq!(rollbacks.len(), 2);
+        assert!(rollbacks[0].is_empty());
+        let removed = &rollbacks[1];
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].voter_role, "drep");
+        assert_eq!(removed[0].proposal_tx_hash.as_deref(), Some(PROPOSAL_TX));
+        assert_eq!(removed[0].block_height, 3000003);
+
+        let state = index.state.read().unwrap();
+        assert_eq!(
+            proposal_votes(&state),
+            vec![
+                ("drep", "yes", 60566420),
+                ("spo", "abstain", 60566420),
+                ("cc", "yes", 60566450),
+            ]
+        );
+        assert_eq!(state.delegations[STAKE].len(), 1);
+        assert_eq!(state.rollbacks, 1);
+        drop(state);
+        assert!(index.is_synced());
+
+        // The DRep's second "no" is in a transaction that failed validation
+        let votes = index.action_votes(PROPOSAL_TX, 0).unwrap();
+        assert_eq!(votes.len(), 3);
+        assert_eq!(votes[0].voter_type, "cc");
+        assert!(matches!(votes[2].vote, Some(VoteChoice::Yes)));
+    }
+
+    #[tokio::test]
+    async fn rollback_truncates_every_history() {
+        let index = GovernanceIndex::new(Network::Preview);
+        replay(&index).await;
+
+        // Back past the replacement block: its delegation and votes go
+        let removed = index.rollback_to(&point(60566440), tip()).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].voter_role, "cc");
+        assert_eq!(removed[0].block_height, 3000003);
+        {
+            let state = index.state.read().unwrap();
+            assert!(state.delegations.is_empty());
+            assert_eq!(proposal_votes(&state).len(), 2);
+            assert_eq!(state.drep_votes[DREP].len(), 1);
+        }
+
+        // Back before the first block: the DRep, committee member and
+        // proposal go too
+        let removed = index.rollback_to(&point(60566390), tip()).unwrap();
+        assert_eq!(removed.len(), 2);
+        let state = index.state.read().unwrap();
+        assert!(state.blocks.is_empty());
+        assert!(state.dreps.is_empty());
+        assert!(state.committee.is_empty());
+        assert!(state.proposals.is_empty());
+        assert!(state.votes.is_empty());
+        assert!(state.drep_votes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rollback_past_the_window_is_refused() {
+        let index = GovernanceIndex::new(Network::Preview);
+        replay(&index).await;
+        for n in 1..=ROLLBACK_WINDOW as u64 {
+            index.apply(
+                BlockGovernance {
+                    slot: 60566500 + n,
+                    id: format!("{:064x}", n),
+                    height: 3000005 + n,
+                    ..Default::default()
+                },
+                tip(),
+            );
+        }
+        // Every recorded block has left the window
+        assert_eq!(index.state.read().unwrap().horizon, Some(60566500));
+
+        assert!(index.rollback_to(&point(60566420), tip()).is_err());
+        assert!(index.rollback_to(&Point::Origin, tip()).is_err());
+        // The oldest block still held can be rolled back to
+        let oldest = index.state.read().unwrap().blocks.front().unwrap().slot;
+        assert!(index.rollback_to(&point(oldest), tip()).is_ok());
+        assert_eq!(index.state.read().unwrap().blocks.len(), 1);
+    }
+}
@@ -0,0 +1,412 @@
+pub mod api;
+pub mod cache;
+pub mod config;
+pub mod indexer;
+pub mod models;
+pub mod network;
+pub mod providers;
+pub mod services;
+pub mod state;
+pub mod utils;
+
+use axum::{middleware, routing::get, Router};
+use cache::backend::{CacheBackend, NamespacedBackend};
+use cache::CacheManager;
+use config::Config;
+use indexer::{ChainIndexer, GovernanceIndex};
+use network::{Network, ServedNetworks};
+use providers::{
+    BlockfrostProvider, BreakerConfig, CachedProviderRouter, FixtureConfig, FixtureMode,
+    GovToolsApi, GovToolsProvider, KoiosProvider, OgmiosProvider, Provider, ProviderFixtures,
+    ProviderRouter, RecordingProvider, ReplayProvider, RetryConfig, RetryPolicy, RoutingPolicy,
+};
+use services::cache_warmer::{CacheWarmer, WarmerConfig, WarmerStatus};
+use services::chain_tip::ChainTipWatcher;
+use services::metadata_validation::VerifierConfig;
+use state::{AppState, NetworkState};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::{Layer, ServiceBuilder};
+use tower_http::{
+    cors::{Any, CorsLayer},
+    trace::TraceLayer,
+};
+
+/// Every served network's providers, cache and background tasks, built from
+/// the configuration.
+pub struct Backend {
+    state: AppState,
+    served: ServedNetworks,
+    admin_api_token: Option<String>,
+    // Cache snapshots and chain indexes written at shutdown
+    snapshots: Vec<(CachedProviderRouter, PathBuf)>,
+    indexes: Vec<(Arc<GovernanceIndex>, PathBuf)>,
+}
+
+impl Backend {
+    /// Connects each network's providers, restores its cache snapshot and
+    /// chain index, and spawns the indexer, chain tip watcher and cache warmer
+    /// where they are enabled.
+    pub async fn build(config: &Config) -> Result<Self, anyhow::Error> {
+        let retry = RetryConfig {
+            max_retries: config.provider_max_retries,
+            base_delay: Duration::from_millis(config.provider_retry_base_ms),
+            max_delay: Duration::from_millis(config.provider_retry_max_ms),
+            budget_ratio: config.provider_retry_budget_ratio,
+        };
+        let breaker = BreakerConfig {
+            failure_threshold: config.circuit_failure_threshold,
+            error_rate_threshold: config.circuit_error_rate_threshold,
+            cooldown: Duration::from_secs(config.circuit_cooldown_secs),
+        };
+        let routing_policy = RoutingPolicy::load(config.routing_config_path.as_deref())?;
+        let stale_windows: HashMap<_, _> = config
+            .cache_stale_secs
+            .iter()
+            .map(|(kind, secs)| (*kind, Duration::from_secs(*secs)))
+            .collect();
+        // One L2 connection shared by every network, each in its own namespace
+        let l2_cache = if config.cache_enabled {
+            cache::backend::open_l2(config).await?
+        } else {
+            None
+        };
+        let verifier_config = if config.cardano_verifier_enabled {
+            Some(VerifierConfig {
+                enabled: true,
+                endpoint: config.cardano_verifier_endpoint.clone(),
+            })
+        } else {
+            None
+        };
+
+        let mut networks = HashMap::new();
+        let mut snapshots = Vec::new();
+        let mut indexes = Vec::new();
+        for network_config in &config.networks {
+            let network = &network_config.network;
+            let blockfrost_provider = BlockfrostProvider::new(
+                network_config.blockfrost_base_url.clone(),
+                network_config.blockfrost_api_key.clone(),
+                RetryPolicy::new(retry.clone()),
+            );
+            let koios_provider = KoiosProvider::new(
+                network_config.koios_base_url.clone(),
+                network_config.koios_api_key.clone(),
+                RetryPolicy::new(retry.clone()),
+            );
+            let fixtures = network_config.fixtures.as_ref();
+            if let Some(fixtures) = fixtures {
+                tracing::info!(
+                    "Provider fixtures for network {}: {:?} in {}",
+                    network,
+                    fixtures.mode,
+                    fixtures.dir.display()
+                );
+            }
+            let provider_router = ProviderRouter::new(
+                with_fixtures(blockfrost_provider, fixtures, "blockfrost").await?,
+                with_fixtures(koios_provider, fixtures, "koios").await?,
+                breaker.clone(),
+                routing_policy.clone(),
+            )
+            .with_consistency_sampling(config.consistency_sample_rate);
+            let provider_router = with_dbsync(provider_router, network_config, config, &breaker)?;
+            let provider_router = match &network_config.ogmios_url {
+                Some(url) => {
+                    tracing::info!("Ogmios enabled for network: {} ({})", network, url);
+                    provider_router.with_ogmios(
+                        OgmiosProvider::new(url.clone(), RetryPolicy::new(retry.clone())),
+                        breaker.clone(),
+                    )
+                }
+                None => provider_router,
+            };
+            provider_router.check_network(network).await?;
+            let govtools_provider = if network_config.govtools_enabled {
+                tracing::info!(
+                    "GovTools enabled for network: {} (base URL: {})",
+                    network,
+                    network_config.govtools_base_url
+                );
+                let govtools = GovToolsProvider::new(network_config.govtools_base_url.clone());
+                Some(govtools_with_fixtures(govtools, fixtures).await?)
+            } else {
+                tracing::info!("GovTools disabled for network: {}", network);
+                None
+            };
+
+            let cache_manager = CacheManager::new(
+                config.cache_enabled,
+                config.cache_max_entries,
+                stale_windows.clone(),
+                l2_cache.clone().map(|l2| {
+                    Arc::new(NamespacedBackend::new(l2, network)) as Arc<dyn CacheBackend>
+                }),
+            );
+            let mut router = CachedProviderRouter::new(
+                provider_router,
+                cache_manager,
+                govtools_provider,
+                verifier_config.clone(),
+                network.clone(),
+            );
+            let index = match &network_config.indexer {
+                Some(indexer_config) => {
+                    let index = match &indexer_config.store_path {
+                        Some(path) => match GovernanceIndex::load(network.clone(), path).await {
+                            Ok(index) => index,
+                            Err(e) => {
+                                tracing::warn!("Failed to load {} chain index: {:#}", network, e);
+                                GovernanceIndex::new(network.clone())
+                            }
+                        },
+                        None => GovernanceIndex::new(network.clone()),
+                    };
+                    let index = Arc::new(index);
+                    router = router.with_index(index.clone());
+                    Some((index, indexer_config.clone()))
+                }
+                None => None,
+            };
+
+            // The default network keeps CACHE_SNAPSHOT_PATH itself; others get a
+            // file named after the network next to it
+            let snapshot_path = config
+                .cache_snapshot_path
+                .as_ref()
+                .filter(|_| config.cache_enabled)
+                .map(|path| {
+                    let path = PathBuf::from(path);
+                    if network == &config.network {
+                        path
+                    } else {
+                        cache::snapshot::network_path(&path, network)
+                    }
+                });
+            if let Some(path) = &snapshot_path {
+                match cache::snapshot::restore(router.cache(), path).await {
+                    Ok(restored) => tracing::info!(
+                        "Restored {} {} cache entries from {}",
+                        restored,
+                        network,
+                        path.display()
+                    ),
+                    Err(e) => {
+                        tracing::warn!("Failed to restore {} cache snapshot: {:#}", network, e)
+                    }
+                }
+                snapshots.push((router.clone(), path.clone()));
+            }
+
+            if let Some((index, indexer_config)) = index {
+                tracing::info!(
+                    "Chain indexer enabled for network: {} ({:?})",
+                    network,
+                    indexer_config.stream
+                );
+                if let Some(path) = &indexer_config.store_path {
+                    indexes.push((index.clone(), path.clone()));
+                }
+                ChainIndexer::new(router.clone(), index, indexer_config).spawn();
+            }
+
+            if config.cache_enabled && config.chain_tip_poll_secs > 0 {
+                ChainTipWatcher::new(
+                    router.clone(),
+                    Duration::from_secs(config.chain_tip_poll_secs),
+                )
+                .spawn();
+            }
+
+            let warmer = Arc::new(WarmerStatus::default());
+            if config.cache_enabled && config.cache_warm_enabled {
+                let warmer_config = WarmerConfig {
+                    pages: config.cache_warm_pages,
+                    epochs: config.cache_warm_epochs,
+                    interval: (config.cache_warm_interval_secs > 0)
+                        .then(|| Duration::from_secs(config.cache_warm_interval_secs)),
+                };
+                CacheWarmer::new(router.clone(), warmer_config, warmer.clone()).spawn();
+            }
+
+            networks.insert(network.clone(), NetworkState { router, warmer });
+        }
+
+        let served = ServedNetworks {
+            default: config.network.clone(),
+            served: Arc::new(config.networks.iter().map(|n| n.network.clone()).collect()),
+        };
+        tracing::info!(
+            "Serving networks: {} (default {})",
+            served
+                .served
+                .iter()
+                .map(Network::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            served.default
+        );
+
+        Ok(Self {
+            state: AppState {
+                default_network: config.network.clone(),
+                networks: Arc::new(networks),
+            },
+            served,
+            admin_api_token: config.admin_api_token.clone(),
+            snapshots,
+            indexes,
+        })
+    }
+
+    /// The HTTP API over every served network.
+    pub fn app(&self) -> Router {
+        let cors = CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any);
+
+        let mut app = Router::new()
+            .route("/health", get(api::health::health_check))
+            .route("/metrics", get(api::metrics::metrics))
+            .route("/api/dreps", get(api::dreps::get_dreps))
+            .route("/api/dreps/stats", get(api::dreps::get_drep_stats))
+            .route("/api/dreps/:id", get(api::dreps::get_drep))
+            .route(
+                "/api/dreps/:id/delegators",
+                get(api::dreps::get_drep_delegators),
+            )
+            .route("/api/dreps/:id/votes", get(api::dreps::get_drep_votes))
+            .route(
+                "/api/dreps/:id/metadata",
+                get(api::dreps::get_drep_metadata),
+            )
+            .route("/api/actions", get(api::actions::get_actions))
+            .route("/api/actions/:id", get(api::actions::get_action))
+            .route(
+                "/api/actions/:id/votes",
+                get(api::actions::get_action_votes),
+            )
+            .route(
+                "/api/actions/:id/participation",
+                get(api::actions::get_action_participation),
+            )
+            .route(
+                "/api/stake/:stake_address/delegation",
+                get(api::stake::get_stake_delegation),
+            )
+            .route("/api/ledger", get(api::ledger::get_ledger_state));
+
+        match &self.admin_api_token {
+            Some(token) => app = app.merge(api::admin::router(token)),
+            None => tracing::info!("Admin API disabled (ADMIN_API_TOKEN not set)"),
+        }
+
+        let app = app
+            .layer(
+                ServiceBuilder::new()
+                    .layer(TraceLayer::new_for_http())
+                    .layer(cors)
+                    .layer(middleware::from_fn(cache::status::cache_status_header)),
+            )
+            .with_state(self.state.clone());
+        // Runs before routing, so network-scoped paths reach the unprefixed
+        // routes; the outer router only hands every request to it
+        let scoped =
+            middleware::from_fn_with_state(self.served.clone(), network::scope_network).layer(app);
+        Router::new().fallback_service(scoped)
+    }
+
+    /// Writes the cache snapshots and chain indexes, for the next start to
+    /// resume from.
+    pub async fn save(&self) {
+        for (router, path) in &self.snapshots {
+            match cache::snapshot::save(router.cache(), path).await {
+                Ok(saved) => tracing::info!(
+                    "Saved {} {} cache entries to {}",
+                    saved,
+                    router.network(),
+                    path.display()
+                ),
+                Err(e) => tracing::warn!(
+                    "Failed to save {} cache snapshot: {:#}",
+                    router.network(),
+                    e
+                ),
+            }
+        }
+
+        for (index, path) in &self.indexes {
+            if let Err(e) = index.save(path).await {
+                tracing::warn!("Failed to save chain index to {}: {:#}", path.display(), e);
+            }
+        }
+    }
+}
+
+// The provider itself, or when PROVIDER_FIXTURES is set, a RecordingProvider
+// around it or a ReplayProvider in its place
+async fn with_fixtures<P: Provider + 'static>(
+    provider: P,
+    fixtures: Option<&FixtureConfig>,
+    name: &'static str,
+) -> Result<Arc<dyn Provider>, anyhow::Error> {
+    let Some(config) = fixtures else {
+        return Ok(Arc::new(provider));
+    };
+    let fixtures = ProviderFixtures::open(config, name).await?;
+    Ok(match config.mode {
+        FixtureMode::Record => Arc::new(RecordingProvider::new(provider, fixtures)),
+        FixtureMode::Replay => Arc::new(ReplayProvider::new(fixtures)),
+    })
+}
+
+// The same for GovTools, which is not a `Provider`
+async fn govtools_with_fixtures(
+    govtools: GovToolsProvider,
+    fixtures: Option<&FixtureConfig>,
+) -> Result<Arc<dyn GovToolsApi>, anyhow::Error> {
+    let Some(config) = fixtures else {
+        return Ok(Arc::new(govtools));
+    };
+    let fixtures = ProviderFixtures::open(config, "govtools").await?;
+    Ok(match config.mode {
+        FixtureMode::Record => Arc::new(RecordingProvider::new(govtools, fixtures)),
+        FixtureMode::Replay => Arc::new(ReplayProvider::new(fixtures)),
+    })
+}
+
+// Puts a network's db-sync database in front of the hosted providers when
+// DBSYNC_URL is set for it
+#[cfg(feature = "dbsync")]
+fn with_dbsync(
+    router: ProviderRouter,
+    network_config: &config::NetworkConfig,
+    config: &Config,
+    breaker: &BreakerConfig,
+) -> Result<ProviderRouter, anyhow::Error> {
+    let Some(url) = &network_config.dbsync_url else {
+        return Ok(router);
+    };
+    let dbsync = providers::DbSyncProvider::connect(url, config.dbsync_pool_size)?;
+    tracing::info!("db-sync enabled for network: {}", network_config.network);
+    Ok(router.with_dbsync(dbsync, breaker.clone()))
+}
+
+#[cfg(not(feature = "dbsync"))]
+fn with_dbsync(
+    router: ProviderRouter,
+    network_config: &config::NetworkConfig,
+    _config: &Config,
+    _breaker: &BreakerConfig,
+) -> Result<ProviderRouter, anyhow::Error> {
+    if network_config.dbsync_url.is_some() {
+        return Err(anyhow::anyhow!(
+            "DBSYNC_URL requires building with --features dbsync"
+        ));
+    }
+    Ok(router)
+}
@@ -1,24 +1,6 @@
-mod api;
-mod cache;
-mod config;
-mod models;
-mod providers;
-mod services;
-mod utils;
-
-use axum::{routing::get, Router};
-use cache::CacheManager;
-use config::Config;
-use providers::{
-    BlockfrostProvider, CachedProviderRouter, GovToolsProvider, KoiosProvider, ProviderRouter,
-};
-use services::metadata_validation::VerifierConfig;
+use govtwool_backend::config::Config;
+use govtwool_backend::Backend;
 use std::net::SocketAddr;
-use tower::ServiceBuilder;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -30,89 +12,44 @@ async fn main() -> Result<(), anyhow::Error> {
         .init();
 
     let config = Config::from_env()?;
-
-    let blockfrost_base_url = config.blockfrost_base_url();
-    let blockfrost_provider =
-        BlockfrostProvider::new(blockfrost_base_url, config.blockfrost_api_key);
-    let koios_provider = KoiosProvider::new(config.koios_base_url.clone());
-    let provider_router = ProviderRouter::new(blockfrost_provider, koios_provider);
-    let govtools_provider = if config.govtools_enabled {
-        tracing::info!(
-            "GovTools enabled for network: {} (base URL: {})",
-            config.blockfrost_network,
-            config.govtools_base_url
-        );
-        Some(GovToolsProvider::new(config.govtools_base_url.clone()))
-    } else {
-        tracing::info!(
-            "GovTools disabled for network: {}",
-            config.blockfrost_network
-        );
-        None
-    };
-
-    // Initialize cache
-    let cache_manager = CacheManager::new(config.cache_enabled, config.cache_max_entries);
-    let verifier_config = if config.cardano_verifier_enabled {
-        Some(VerifierConfig {
-            enabled: true,
-            endpoint: config.cardano_verifier_endpoint.clone(),
-        })
-    } else {
-        None
-    };
-    let router = CachedProviderRouter::new(
-        provider_router,
-        cache_manager,
-        govtools_provider,
-        verifier_config,
-    );
-
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    let app = Router::new()
-        .route("/health", get(api::health::health_check))
-        .route("/api/dreps", get(api::dreps::get_dreps))
-        .route("/api/dreps/stats", get(api::dreps::get_drep_stats))
-        .route("/api/dreps/:id", get(api::dreps::get_drep))
-        .route(
-            "/api/dreps/:id/delegators",
-            get(api::dreps::get_drep_delegators),
-        )
-        .route("/api/dreps/:id/votes", get(api::dreps::get_drep_votes))
-        .route(
-            "/api/dreps/:id/metadata",
-            get(api::dreps::get_drep_metadata),
-        )
-        .route("/api/actions", get(api::actions::get_actions))
-        .route("/api/actions/:id", get(api::actions::get_action))
-        .route(
-            "/api/actions/:id/votes",
-            get(api::actions::get_action_votes),
-        )
-        .route(
-            "/api/actions/:id/participation",
-            get(api::actions::get_action_participation),
-        )
-        .route(
-            "/api/stake/:stake_address/delegation",
-            get(api::stake::get_stake_delegation),
-        )
-        .layer(
-            ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(cors),
-        )
-        .with_state(router);
+    let backend = Backend::build(&config).await?;
+    let app = backend.app();
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server_port));
     tracing::info!("Starting server on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    backend.save().await;
 
     Ok(())
 }
+
+// Resolves on Ctrl+C or SIGTERM, so the server drains in-flight requests and
+// the cache snapshot gets written
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
+}
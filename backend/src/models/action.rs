@@ -86,7 +86,7 @@ pub struct Withdrawal {
     pub address: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ActionsPage {
     pub actions: Vec<GovernanceAction>,
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Latest block as reported by Koios `/tip`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainTip {
+    pub hash: String,
+    pub epoch_no: u32,
+    pub abs_slot: u64,
+    pub block_height: u64,
+    pub block_time: Option<u64>,
+}
+
+/// A governance vote cast on-chain, as listed by Koios `/vote_list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentVote {
+    pub proposal_id: Option<String>,
+    pub proposal_tx_hash: Option<String>,
+    pub voter_role: String,
+    pub voter_id: String,
+    pub block_height: u64,
+}
+
+/// Governance figures from the node's ledger state, as queried through Ogmios.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LedgerState {
+    pub epoch: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constitution: Option<Constitution>,
+    /// Lovelace in the treasury
+    pub treasury: String,
+    /// Lovelace in the reserves
+    pub reserves: String,
+    /// Protocol parameters in force, in Ogmios' representation
+    pub protocol_parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Constitution {
+    pub url: String,
+    pub hash: String,
+    /// Hash of the guardrails script, if one is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guardrails_script: Option<String>,
+}
@@ -131,7 +131,7 @@ pub struct DRepVotingHistory {
     pub epoch: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct DRepsPage {
     pub dreps: Vec<DRep>,
@@ -140,7 +140,7 @@ pub struct DRepsPage {
     pub total: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct DRepStats {
     pub active_dreps_count: Option<u32>,
@@ -174,7 +174,7 @@ pub struct DRepExternalReference {
     pub uri: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DRepsQuery {
     pub page: u32,
     pub count: u32,
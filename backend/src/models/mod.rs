@@ -1,10 +1,12 @@
 pub mod action;
+pub mod chain;
 pub mod common;
 pub mod drep;
 pub mod participation;
 pub mod stake;
 
 pub use action::*;
+pub use chain::*;
 pub use drep::*;
 pub use participation::*;
 pub use stake::*;
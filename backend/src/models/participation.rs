@@ -9,7 +9,7 @@ pub enum VoteChoice {
 }
 
 impl VoteChoice {
-    pub fn from_str(value: &str) -> Option<Self> {
+    pub fn parse(value: &str) -> Option<Self> {
         match value.to_ascii_lowercase().as_str() {
             "yes" => Some(Self::Yes),
             "no" => Some(Self::No),
@@ -166,4 +166,3 @@ pub fn calculate_summary<T>(participants: &[T], voted: usize) -> ParticipationSu
         turnout_percentage,
     }
 }
-
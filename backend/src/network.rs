@@ -0,0 +1,464 @@
+use crate::models::common::ErrorResponse;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::sync::Arc;
+
+static NETWORK_HEADER: HeaderName = HeaderName::from_static("x-cardano-network");
+
+/// Cardano network the backend serves, selected with `BLOCKFROST_NETWORK`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Network {
+    Mainnet,
+    Preprod,
+    Preview,
+    Sanchonet,
+    /// Any other network; provider URLs must be configured explicitly
+    Custom(String),
+}
+
+/// Shelley-era epoch timing. Epochs have had a fixed length since the Shelley
+/// hard fork, so any later epoch's start follows from the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ShelleyGenesis {
+    /// First Shelley epoch
+    pub epoch: u32,
+    /// Unix time that epoch started
+    pub start_time: u64,
+    /// Absolute slot that epoch started at
+    pub start_slot: u64,
+    /// Epoch length in seconds
+    pub epoch_length: u64,
+}
+
+impl ShelleyGenesis {
+    pub fn epoch_start_time(&self, epoch: u32) -> Option<u64> {
+        let offset = u64::from(epoch.checked_sub(self.epoch)?).checked_mul(self.epoch_length)?;
+        self.start_time.checked_add(offset)
+    }
+
+    /// Unix time of a Shelley-era slot. Slots have lasted one second since the
+    /// Shelley hard fork.
+    pub fn slot_time(&self, slot: u64) -> Option<u64> {
+        self.start_time
+            .checked_add(slot.checked_sub(self.start_slot)?)
+    }
+
+    pub fn slot_epoch(&self, slot: u64) -> Option<u32> {
+        let epochs = slot.checked_sub(self.start_slot)? / self.epoch_length;
+        self.epoch.checked_add(u32::try_from(epochs).ok()?)
+    }
+}
+
+impl Network {
+    pub fn parse(value: &str) -> Network {
+        match value.trim().to_lowercase().as_str() {
+            "mainnet" => Network::Mainnet,
+            "preprod" => Network::Preprod,
+            "preview" => Network::Preview,
+            "sanchonet" | "sancho" => Network::Sanchonet,
+            other => Network::Custom(other.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Preprod => "preprod",
+            Network::Preview => "preview",
+            Network::Sanchonet => "sanchonet",
+            Network::Custom(name) => name,
+        }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        matches!(self, Network::Mainnet)
+    }
+
+    /// Protocol magic reported by the providers' genesis endpoints.
+    pub fn magic(&self) -> Option<u64> {
+        match self {
+            Network::Mainnet => Some(764_824_073),
+            Network::Preprod => Some(1),
+            Network::Preview => Some(2),
+            Network::Sanchonet => Some(4),
+            Network::Custom(_) => None,
+        }
+    }
+
+    /// Bech32 prefix of stake (reward) addresses.
+    pub fn stake_hrp(&self) -> &'static str {
+        if self.is_mainnet() {
+            "stake"
+        } else {
+            "stake_test"
+        }
+    }
+
+    pub fn genesis(&self) -> Option<ShelleyGenesis> {
+        let (epoch, start_time, start_slot, epoch_length) = match self {
+            Network::Mainnet => (208, 1_596_059_091, 4_492_800, 432_000),
+            Network::Preprod => (4, 1_655_769_600, 86_400, 432_000),
+            Network::Preview => (0, 1_666_656_000, 0, 86_400),
+            Network::Sanchonet => (0, 1_686_789_000, 0, 86_400),
+            Network::Custom(_) => return None,
+        };
+        Some(ShelleyGenesis {
+            epoch,
+            start_time,
+            start_slot,
+            epoch_length,
+        })
+    }
+
+    pub fn blockfrost_base_url(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("https://cardano-mainnet.blockfrost.io/api/v0"),
+            Network::Preprod => Some("https://cardano-preprod.blockfrost.io/api/v0"),
+            Network::Preview => Some("https://cardano-preview.blockfrost.io/api/v0"),
+            Network::Sanchonet => Some("https://cardano-sanchonet.blockfrost.io/api/v0"),
+            Network::Custom(_) => None,
+        }
+    }
+
+    pub fn koios_base_url(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("https://api.koios.rest/api/v1"),
+            Network::Preprod => Some("https://preprod.koios.rest/api/v1"),
+            Network::Preview => Some("https://preview.koios.rest/api/v1"),
+            Network::Sanchonet => Some("https://sancho.koios.rest/api/v1"),
+            Network::Custom(_) => None,
+        }
+    }
+
+    /// GovTools is only hosted for mainnet and preview.
+    pub fn govtools_base_url(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("https://be.gov.tools"),
+            Network::Preview => Some("https://be.preview.gov.tools"),
+            _ => None,
+        }
+    }
+
+    /// The known network a provider URL points at, judged by its host.
+    pub fn of_url(url: &str) -> Option<Network> {
+        let host = url.split("://").nth(1)?.split('/').next()?;
+        match host {
+            "api.koios.rest" | "cardano-mainnet.blockfrost.io" | "be.gov.tools" => {
+                Some(Network::Mainnet)
+            }
+            "preprod.koios.rest" | "cardano-preprod.blockfrost.io" => Some(Network::Preprod),
+            "preview.koios.rest" | "cardano-preview.blockfrost.io" | "be.preview.gov.tools" => {
+                Some(Network::Preview)
+            }
+            "sancho.koios.rest" | "cardano-sanchonet.blockfrost.io" => Some(Network::Sanchonet),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Network {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Networks this process serves, and the one unprefixed routes use.
+#[derive(Debug, Clone)]
+pub struct ServedNetworks {
+    pub default: Network,
+    pub served: Arc<Vec<Network>>,
+}
+
+impl ServedNetworks {
+    // The network named by the segment after `/api` or `/admin`, and the path
+    // with that segment removed
+    fn scope(&self, path: &str) -> Option<Result<(Network, String), Network>> {
+        let rest = path.strip_prefix('/')?;
+        let (area, rest) = rest.split_once('/')?;
+        if !SCOPED_AREAS.contains(&area) {
+            return None;
+        }
+        let (segment, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        let network = Network::parse(segment);
+        if self.served.contains(&network) {
+            Some(Ok((network, format!("/{}/{}", area, rest))))
+        } else if matches!(network, Network::Custom(_)) {
+            // Not a network name, e.g. `/api/dreps`
+            None
+        } else {
+            Some(Err(network))
+        }
+    }
+}
+
+// Path prefixes that can be scoped to a network, e.g. `/api/preview/dreps`
+const SCOPED_AREAS: [&str; 2] = ["api", "admin"];
+
+/// Middleware that picks the network a request is for. `/api/{network}/...`
+/// and `/admin/{network}/...` are rewritten to the unprefixed route with the
+/// network stored as a request extension; other paths use the default
+/// network. Every response names its network in the `X-Cardano-Network`
+/// header.
+pub async fn scope_network(
+    State(networks): State<ServedNetworks>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let network = match networks.scope(request.uri().path()) {
+        Some(Ok((network, path))) => {
+            let path_and_query = match request.uri().query() {
+                Some(query) => format!("{}?{}", path, query),
+                None => path,
+            };
+            let mut parts = request.uri().clone().into_parts();
+            parts.path_and_query = path_and_query.parse().ok();
+            if let Ok(uri) = Uri::from_parts(parts) {
+                *request.uri_mut() = uri;
+            }
+            network
+        }
+        Some(Err(network)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Network {} is not served here", network),
+                    code: "not_found".to_string(),
+                }),
+            )
+                .into_response();
+        }
+        None => networks.default.clone(),
+    };
+
+    request.extensions_mut().insert(network.clone());
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(network.as_str()) {
+        response.headers_mut().insert(NETWORK_HEADER.clone(), value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::providers::routing::{ProviderKind, RoutingPolicy};
+    use crate::providers::testing::StubProvider;
+    use crate::providers::{CachedProviderRouter, ProviderRouter};
+    use crate::state::{AppState, NetworkState};
+    use axum::body::Body;
+    use axum::{middleware, routing::get, Router};
+    use std::collections::HashMap;
+    use tower::{Layer, ServiceExt};
+
+    fn served() -> ServedNetworks {
+        ServedNetworks {
+            default: Network::Mainnet,
+            served: Arc::new(vec![Network::Mainnet, Network::Preview]),
+        }
+    }
+
+    #[test]
+    fn scopes_paths_that_name_a_served_network() {
+        let served = served();
+        assert_eq!(
+            served.scope("/api/preview/dreps/drep1abc"),
+            Some(Ok((Network::Preview, "/api/dreps/drep1abc".to_string())))
+        );
+        assert_eq!(
+            served.scope("/admin/mainnet/cache"),
+            Some(Ok((Network::Mainnet, "/admin/cache".to_string())))
+        );
+        assert_eq!(
+            served.scope("/api/Preview"),
+            Some(Ok((Network::Preview, "/api/".to_string())))
+        );
+        assert_eq!(
+            served.scope("/api/preprod/dreps"),
+            Some(Err(Network::Preprod))
+        );
+        assert_eq!(served.scope("/api/dreps"), None);
+        assert_eq!(served.scope("/health"), None);
+        assert_eq!(served.scope("/other/preview/dreps"), None);
+    }
+
+    #[test]
+    fn parses_network_names() {
+        assert_eq!(Network::parse("mainnet"), Network::Mainnet);
+        assert_eq!(Network::parse(" PreProd "), Network::Preprod);
+        assert_eq!(Network::parse("preview"), Network::Preview);
+        assert_eq!(Network::parse("sancho"), Network::Sanchonet);
+        assert_eq!(Network::parse("SanchoNet"), Network::Sanchonet);
+        assert_eq!(
+            Network::parse("Devnet"),
+            Network::Custom("devnet".to_string())
+        );
+        for network in [
+            Network::Mainnet,
+            Network::Preprod,
+            Network::Preview,
+            Network::Sanchonet,
+        ] {
+            assert_eq!(Network::parse(network.as_str()), network);
+        }
+    }
+
+    #[test]
+    fn genesis_constants_match_each_network() {
+        let mainnet = Network::Mainnet.genesis().unwrap();
+        assert_eq!(mainnet.epoch_start_time(208), Some(1_596_059_091));
+        assert_eq!(mainnet.epoch_start_time(209), Some(1_596_059_091 + 432_000));
+        assert_eq!(mainnet.epoch_start_time(207), None);
+        assert_eq!(mainnet.slot_time(4_492_800), Some(1_596_059_091));
+        assert_eq!(mainnet.slot_epoch(4_492_800), Some(208));
+        assert_eq!(mainnet.slot_epoch(4_492_800 + 432_000 - 1), Some(208));
+        assert_eq!(mainnet.slot_epoch(4_492_800 + 432_000), Some(209));
+        assert_eq!(mainnet.slot_epoch(4_492_799), None);
+
+        let preprod = Network::Preprod.genesis().unwrap();
+        assert_eq!(preprod.slot_epoch(86_400), Some(4));
+        assert_eq!(preprod.epoch_start_time(4), Some(1_655_769_600));
+        assert_eq!(preprod.slot_time(86_400), Some(1_655_769_600));
+        assert_eq!(preprod.slot_time(86_400 + 60), Some(1_655_769_660));
+        assert_eq!(preprod.slot_epoch(86_400 + 432_000), Some(5));
+
+        let preview = Network::Preview.genesis().unwrap();
+        assert_eq!(preview.epoch_start_time(0), Some(1_666_656_000));
+        assert_eq!(preview.epoch_start_time(1), Some(1_666_656_000 + 86_400));
+        assert_eq!(preview.slot_epoch(86_399), Some(0));
+        assert_eq!(preview.slot_epoch(86_400), Some(1));
+
+        let sanchonet = Network::Sanchonet.genesis().unwrap();
+        assert_eq!(sanchonet.epoch_start_time(0), Some(1_686_789_000));
+        assert_eq!(sanchonet.epoch_length, 86_400);
+
+        assert_eq!(Network::Custom("devnet".to_string()).genesis(), None);
+    }
+
+    #[test]
+    fn only_mainnet_uses_the_stake_prefix() {
+        assert_eq!(Network::Mainnet.stake_hrp(), "stake");
+        for network in [Network::Preprod, Network::Preview, Network::Sanchonet] {
+            assert_eq!(network.stake_hrp(), "stake_test", "{}", network);
+        }
+    }
+
+    #[test]
+    fn default_urls_belong_to_their_network() {
+        for network in [
+            Network::Mainnet,
+            Network::Preprod,
+            Network::Preview,
+            Network::Sanchonet,
+        ] {
+            let urls = [
+                network.blockfrost_base_url(),
+                network.koios_base_url(),
+                network.govtools_base_url(),
+            ];
+            for url in urls.into_iter().flatten() {
+                assert_eq!(Network::of_url(url), Some(network.clone()), "{}", url);
+            }
+        }
+        assert_eq!(
+            Network::of_url("https://preprod.koios.rest"),
+            Some(Network::Preprod)
+        );
+        assert_eq!(Network::of_url("http://localhost:8080/api/v1"), None);
+        assert_eq!(Network::of_url("preview.koios.rest/api/v1"), None);
+    }
+
+    // The routes of `Backend::app` reduced to one handler that names the
+    // network whose state it was given
+    fn app() -> Router {
+        let networks = served()
+            .served
+            .iter()
+            .map(|network| {
+                let router = ProviderRouter::stubbed(
+                    RoutingPolicy::default(),
+                    &[(ProviderKind::Koios, Arc::new(StubProvider::new()))],
+                );
+                let cache = CacheManager::new(true, 10, HashMap::new(), None);
+                let router = CachedProviderRouter::new(router, cache, None, None, network.clone());
+                let state = NetworkState {
+                    router,
+                    warmer: Arc::default(),
+                };
+                (network.clone(), state)
+            })
+            .collect();
+        let state = AppState {
+            default_network: Network::Mainnet,
+            networks: Arc::new(networks),
+        };
+        let app = Router::new()
+            .route(
+                "/api/dreps",
+                get(|network: NetworkState| async move { network.router.network().to_string() }),
+            )
+            .with_state(state);
+        let scoped = middleware::from_fn_with_state(served(), scope_network).layer(app);
+        Router::new().fallback_service(scoped)
+    }
+
+    async fn get_network(uri: &str) -> (StatusCode, Option<String>, String) {
+        let response = app()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let header = response
+            .headers()
+            .get(&NETWORK_HEADER)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn scoped_route_reaches_that_networks_state() {
+        let (status, header, body) = get_network("/api/preview/dreps?page=2").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "preview");
+        assert_eq!(header.as_deref(), Some("preview"));
+    }
+
+    #[tokio::test]
+    async fn unprefixed_route_uses_the_default_network() {
+        let (status, header, body) = get_network("/api/dreps").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "mainnet");
+        assert_eq!(header.as_deref(), Some("mainnet"));
+
+        let (status, header, _) = get_network("/api/nowhere").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(header.as_deref(), Some("mainnet"));
+    }
+
+    #[tokio::test]
+    async fn known_network_not_served_here_is_not_found() {
+        let (status, header, body) = get_network("/api/preprod/dreps").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(header, None);
+        assert!(
+            body.contains("Network preprod is not served here"),
+            "{}",
+            body
+        );
+    }
+}
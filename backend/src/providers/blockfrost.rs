@@ -1,4 +1,7 @@
 use crate::models::*;
+use crate::providers::error::ProviderError;
+use crate::providers::quota::{QuotaSnapshot, QuotaTracker};
+use crate::providers::retry::RetryPolicy;
 use crate::providers::Provider;
 use crate::utils::drep_id::convert_to_cip105;
 use async_trait::async_trait;
@@ -9,19 +12,29 @@ pub struct BlockfrostProvider {
     client: Client,
     base_url: String,
     api_key: String,
+    retry: RetryPolicy,
+    quota: QuotaTracker,
 }
 
 impl BlockfrostProvider {
-    pub fn new(base_url: String, api_key: String) -> Self {
+    pub fn new(base_url: String, api_key: String, retry: RetryPolicy) -> Self {
         let client = Client::new();
+        let quota = QuotaTracker::new("blockfrost", base_url.clone(), true);
         Self {
             client,
             base_url,
             api_key,
+            retry,
+            quota,
         }
     }
 
-    async fn fetch(&self, path: &str) -> Result<Option<Value>, anyhow::Error> {
+    async fn fetch(&self, path: &str) -> Result<Option<Value>, ProviderError> {
+        let label = format!("Blockfrost {}", path);
+        self.retry.run(&label, || self.fetch_once(path)).await
+    }
+
+    async fn fetch_once(&self, path: &str) -> Result<Option<Value>, ProviderError> {
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .client
@@ -29,38 +42,38 @@ impl BlockfrostProvider {
             .header("project_id", &self.api_key)
             .send()
             .await?;
+        self.quota.observe(response.headers());
 
         if response.status() == 404 {
             return Ok(None);
         }
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            if status == 400 {
-                if error_text.contains("Invalid path") || error_text.contains("not found") {
+            let error = ProviderError::from_response(response).await;
+
+            if let ProviderError::Upstream {
+                status: 400,
+                message,
+            } = &error
+            {
+                if message.contains("Invalid path") || message.contains("not found") {
                     tracing::warn!("Blockfrost endpoint not available: {}", path);
                     return Ok(None);
                 }
             }
 
-            return Err(anyhow::anyhow!(
-                "Blockfrost API error: {} {}",
-                status,
-                error_text
-            ));
+            return Err(error);
         }
 
         let json: Value = response.json().await?;
         Ok(Some(json))
     }
 
-    fn map_drep(&self, drep: &Value) -> Result<DRep, anyhow::Error> {
+    fn map_drep(&self, drep: &Value) -> Result<DRep, ProviderError> {
         let mut result = DRep {
             drep_id: drep["drep_id"]
                 .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Missing drep_id"))?
+                .ok_or_else(|| ProviderError::Decode("Missing drep_id".to_string()))?
                 .to_string(),
             drep_hash: drep["drep_hash"].as_str().map(|s| s.to_string()),
             hex: drep["hex"].as_str().map(|s| s.to_string()),
@@ -132,15 +145,15 @@ impl BlockfrostProvider {
         Ok(result)
     }
 
-    fn map_governance_action(&self, action: &Value) -> Result<GovernanceAction, anyhow::Error> {
+    fn map_governance_action(&self, action: &Value) -> Result<GovernanceAction, ProviderError> {
         Ok(GovernanceAction {
             tx_hash: action["tx_hash"]
                 .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Missing tx_hash"))?
+                .ok_or_else(|| ProviderError::Decode("Missing tx_hash".to_string()))?
                 .to_string(),
             action_id: action["action_id"]
                 .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Missing action_id"))?
+                .ok_or_else(|| ProviderError::Decode("Missing action_id".to_string()))?
                 .to_string(),
             proposal_id: action["proposal_id"].as_str().map(|s| s.to_string()),
             proposal_tx_hash: action["proposal_tx_hash"].as_str().map(|s| s.to_string()),
@@ -154,7 +167,7 @@ impl BlockfrostProvider {
             return_address: action["return_address"].as_str().map(|s| s.to_string()),
             r#type: action["type"]
                 .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Missing type"))?
+                .ok_or_else(|| ProviderError::Decode("Missing type".to_string()))?
                 .to_string(),
             description: action["description"].as_str().map(|s| s.to_string()),
             status: action["status"].as_str().map(|s| s.to_string()),
@@ -249,15 +262,13 @@ impl BlockfrostProvider {
                 "cold_key",
             ],
         )
-        .unwrap_or_else(|| "".to_string());
+        .unwrap_or_default();
 
         if voter_identifier.is_empty() {
             return None;
         }
 
-        let vote_choice = vote["vote"]
-            .as_str()
-            .and_then(|value| VoteChoice::from_str(value));
+        let vote_choice = vote["vote"].as_str().and_then(VoteChoice::parse);
 
         let voting_power = Self::extract_string(vote, &["voting_power", "power", "weight"]);
 
@@ -272,13 +283,11 @@ impl BlockfrostProvider {
             })
             .map(|value| value as u32);
 
-        let block_time = vote["block_time"]
-            .as_u64()
-            .or_else(|| {
-                vote["block_time"]
-                    .as_str()
-                    .and_then(|s| s.parse::<u64>().ok())
-            });
+        let block_time = vote["block_time"].as_u64().or_else(|| {
+            vote["block_time"]
+                .as_str()
+                .and_then(|s| s.parse::<u64>().ok())
+        });
 
         Some(ActionVoteRecord {
             voter_identifier,
@@ -290,97 +299,11 @@ impl BlockfrostProvider {
             block_time,
         })
     }
-
-    pub async fn get_stake_pools_page(
-        &self,
-        page: u32,
-        count: u32,
-    ) -> Result<StakePoolPage, anyhow::Error> {
-        let path = format!("/pools/extended?page={}&count={}", page, count);
-        let json = self.fetch(&path).await?;
-
-        let pools = if let Some(Value::Array(arr)) = json {
-            arr.iter()
-                .filter_map(|pool| self.map_stake_pool(pool))
-                .collect()
-        } else {
-            Vec::new()
-        };
-
-        let has_more = pools.len() == count as usize;
-
-        Ok(StakePoolPage {
-            pools,
-            has_more,
-            total: None,
-        })
-    }
-
-    pub async fn get_action_vote_records(
-        &self,
-        tx_hash: &str,
-        cert_index: u32,
-    ) -> Result<Vec<ActionVoteRecord>, anyhow::Error> {
-        let mut records = Vec::new();
-        let mut page = 1u32;
-
-        loop {
-            let path = format!(
-                "/governance/proposals/{}/{}/votes?page={}&count=100",
-                tx_hash, cert_index, page
-            );
-            let json = self.fetch(&path).await?;
-
-            let Some(Value::Array(arr)) = json else {
-                break;
-            };
-
-            if arr.is_empty() {
-                break;
-            }
-
-            let mut batch_count = 0usize;
-            for vote in arr {
-                if let Some(record) = self.map_vote_record(&vote) {
-                    records.push(record);
-                    batch_count += 1;
-                }
-            }
-
-            if batch_count < 100 {
-                break;
-            }
-
-            page = page.saturating_add(1);
-        }
-
-        Ok(records)
-    }
-
-    pub async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, anyhow::Error> {
-        let path = format!("/epochs/{}", epoch);
-        let json = self.fetch(&path).await?;
-
-        if let Some(Value::Object(obj)) = json {
-            if let Some(start_time_value) = obj.get("start_time") {
-                if let Some(ts) = start_time_value.as_u64() {
-                    return Ok(Some(ts));
-                }
-                if let Some(ts_str) = start_time_value.as_str() {
-                    if let Ok(parsed) = ts_str.parse::<u64>() {
-                        return Ok(Some(parsed));
-                    }
-                }
-            }
-        }
-
-        Ok(None)
-    }
 }
 
 #[async_trait]
 impl Provider for BlockfrostProvider {
-    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
         let page = query.normalized_page();
         let count = query.count;
         let path = format!("/governance/dreps?page={}&count={}", page, count);
@@ -403,8 +326,9 @@ impl Provider for BlockfrostProvider {
         })
     }
 
-    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, anyhow::Error> {
-        let cip105_id = convert_to_cip105(id)?;
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        let cip105_id =
+            convert_to_cip105(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
         let path = format!("/governance/dreps/{}", cip105_id);
         let json = self.fetch(&path).await?;
 
@@ -415,8 +339,9 @@ impl Provider for BlockfrostProvider {
         }
     }
 
-    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, anyhow::Error> {
-        let cip105_id = convert_to_cip105(id)?;
+    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        let cip105_id =
+            convert_to_cip105(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
         let mut all_delegators = Vec::new();
         let mut page = 1;
 
@@ -459,8 +384,9 @@ impl Provider for BlockfrostProvider {
     async fn get_drep_voting_history(
         &self,
         id: &str,
-    ) -> Result<Vec<DRepVotingHistory>, anyhow::Error> {
-        let cip105_id = convert_to_cip105(id)?;
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        let cip105_id =
+            convert_to_cip105(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
         let mut all_votes = Vec::new();
         let mut page = 1;
 
@@ -514,7 +440,7 @@ impl Provider for BlockfrostProvider {
         &self,
         page: u32,
         count: u32,
-    ) -> Result<ActionsPage, anyhow::Error> {
+    ) -> Result<ActionsPage, ProviderError> {
         let path = format!("/governance/actions?page={}&count={}", page, count);
         let json = self.fetch(&path).await?;
 
@@ -538,7 +464,7 @@ impl Provider for BlockfrostProvider {
     async fn get_governance_action(
         &self,
         id: &str,
-    ) -> Result<Option<GovernanceAction>, anyhow::Error> {
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
         let path = format!("/governance/actions/{}", id);
         let json = self.fetch(&path).await?;
 
@@ -552,7 +478,7 @@ impl Provider for BlockfrostProvider {
     async fn get_action_voting_results(
         &self,
         id: &str,
-    ) -> Result<ActionVotingBreakdown, anyhow::Error> {
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
         let mut all_votes = Vec::new();
         let mut page = 1;
 
@@ -706,14 +632,15 @@ impl Provider for BlockfrostProvider {
         Ok(breakdown)
     }
 
-    async fn get_drep_metadata(&self, id: &str) -> Result<Option<Value>, anyhow::Error> {
-        let cip105_id = convert_to_cip105(id)?;
+    async fn get_drep_metadata(&self, id: &str) -> Result<Option<Value>, ProviderError> {
+        let cip105_id =
+            convert_to_cip105(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
         let path = format!("/governance/dreps/{}/metadata", cip105_id);
         let json = self.fetch(&path).await?;
         Ok(json)
     }
 
-    async fn get_total_active_dreps(&self) -> Result<Option<u32>, anyhow::Error> {
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
         // Blockfrost doesn't have a direct endpoint for this
         // We'd need to count all DReps, which is expensive
         Ok(None)
@@ -722,7 +649,7 @@ impl Provider for BlockfrostProvider {
     async fn get_stake_delegation(
         &self,
         stake_address: &str,
-    ) -> Result<Option<StakeDelegation>, anyhow::Error> {
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
         let path = format!("/accounts/{}", stake_address);
         let json = self.fetch(&path).await?;
 
@@ -744,15 +671,121 @@ impl Provider for BlockfrostProvider {
         Ok(None)
     }
 
-    async fn health_check(&self) -> Result<bool, anyhow::Error> {
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<StakePoolPage, ProviderError> {
+        let path = format!("/pools/extended?page={}&count={}", page, count);
+        let json = self.fetch(&path).await?;
+
+        let pools = if let Some(Value::Array(arr)) = json {
+            arr.iter()
+                .filter_map(|pool| self.map_stake_pool(pool))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let has_more = pools.len() == count as usize;
+
+        Ok(StakePoolPage {
+            pools,
+            has_more,
+            total: None,
+        })
+    }
+
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        // Blockfrost has no endpoint listing the constitutional committee
+        Ok(Vec::new())
+    }
+
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        // Blockfrost addresses proposals by transaction and certificate index
+        let Some(cert_index) = action.cert_index else {
+            return Ok(Vec::new());
+        };
+        let tx_hash = &action.tx_hash;
+        let mut records = Vec::new();
+        let mut page = 1u32;
+
+        loop {
+            let path = format!(
+                "/governance/proposals/{}/{}/votes?page={}&count=100",
+                tx_hash, cert_index, page
+            );
+            let json = self.fetch(&path).await?;
+
+            let Some(Value::Array(arr)) = json else {
+                break;
+            };
+
+            if arr.is_empty() {
+                break;
+            }
+
+            let mut batch_count = 0usize;
+            for vote in arr {
+                if let Some(record) = self.map_vote_record(&vote) {
+                    records.push(record);
+                    batch_count += 1;
+                }
+            }
+
+            if batch_count < 100 {
+                break;
+            }
+
+            page = page.saturating_add(1);
+        }
+
+        Ok(records)
+    }
+
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        let path = format!("/epochs/{}", epoch);
+        let json = self.fetch(&path).await?;
+
+        if let Some(Value::Object(obj)) = json {
+            if let Some(start_time_value) = obj.get("start_time") {
+                if let Some(ts) = start_time_value.as_u64() {
+                    return Ok(Some(ts));
+                }
+                if let Some(ts_str) = start_time_value.as_str() {
+                    if let Ok(parsed) = ts_str.parse::<u64>() {
+                        return Ok(Some(parsed));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn health_check(&self) -> Result<bool, ProviderError> {
         let path = "/health";
         let response = self
             .client
-            .get(&format!("{}{}", self.base_url, path))
+            .get(format!("{}{}", self.base_url, path))
             .header("project_id", &self.api_key)
             .send()
             .await?;
+        self.quota.observe(response.headers());
 
         Ok(response.status().is_success())
     }
+
+    fn quota(&self) -> Option<QuotaSnapshot> {
+        Some(self.quota.snapshot())
+    }
+
+    /// Protocol magic of the network this endpoint serves.
+    async fn get_network_magic(&self) -> Result<Option<u64>, ProviderError> {
+        let json = self.fetch("/genesis").await?;
+        Ok(json.and_then(|genesis| genesis["network_magic"].as_u64()))
+    }
 }
@@ -1,41 +1,162 @@
-use crate::cache::{keys::CacheKey, CacheManager};
+use crate::cache::metrics::KindStats;
+use crate::cache::negative::NegativeEntry;
+use crate::cache::status::{self, CacheStatus};
+use crate::cache::{keys::CacheKey, single_flight::SingleFlight, CacheManager, Cached};
+use crate::indexer::GovernanceIndex;
 use crate::models::*;
-use crate::providers::{GovToolsEnrichment, GovToolsProvider, ProviderRouter};
+use crate::network::Network;
+use crate::providers::consistency::{ConsistencyKind, ConsistencyReport, KindCounts};
+use crate::providers::{
+    GovToolsApi, GovToolsEnrichment, ProviderError, ProviderHealthSnapshot, ProviderRouter,
+    QuotaSnapshot,
+};
 use crate::services::metadata_validation::{MetadataValidator, VerifierConfig};
+use crate::utils::bech32::decode_bech32;
 use crate::utils::drep_id::decode_drep_id_to_hex;
 use futures::future::join_all;
+use serde::de::DeserializeOwned;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
 use tracing::debug;
 
-const CARDANO_EPOCH_DURATION_SECONDS: u64 = 432_000;
+// Epoch length used to extrapolate start times on networks without known genesis
+// parameters (mainnet/preprod value)
+const DEFAULT_EPOCH_DURATION_SECONDS: u64 = 432_000;
 
 #[derive(Clone)]
 pub struct CachedProviderRouter {
     router: Arc<ProviderRouter>,
     cache: Arc<CacheManager>,
-    govtools: Option<Arc<GovToolsProvider>>,
+    govtools: Option<Arc<dyn GovToolsApi>>,
     metadata_validator: Arc<MetadataValidator>,
+    in_flight: Arc<SingleFlight>,
+    network: Network,
+    index: Option<Arc<GovernanceIndex>>,
 }
 
 impl CachedProviderRouter {
     pub fn new(
         router: ProviderRouter,
         cache: CacheManager,
-        govtools: Option<GovToolsProvider>,
+        govtools: Option<Arc<dyn GovToolsApi>>,
         verifier: Option<VerifierConfig>,
+        network: Network,
     ) -> Self {
         let cache = Arc::new(cache);
         let metadata_validator = Arc::new(MetadataValidator::new(cache.clone(), verifier));
         Self {
             router: Arc::new(router),
             cache,
-            govtools: govtools.map(Arc::new),
+            govtools,
             metadata_validator,
+            in_flight: Arc::new(SingleFlight::new()),
+            network,
+            index: None,
         }
     }
 
-    pub async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
+    /// Answers vote lookups from the chain index once it has caught up.
+    pub fn with_index(mut self, index: Arc<GovernanceIndex>) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    pub fn index(&self) -> Option<&GovernanceIndex> {
+        self.index.as_deref()
+    }
+
+    // The chain index, if it is following the tip closely enough to answer
+    fn synced_index(&self) -> Option<&GovernanceIndex> {
+        self.index().filter(|index| index.is_synced())
+    }
+
+    /// Runs a cache-miss fetch for `key`, shared by every concurrent caller of the
+    /// same key. `fetch` gets its own handle on the router and is responsible for
+    /// writing the cache; failures that `NegativeEntry` classifies are remembered
+    /// here.
+    async fn load<T, F, Fut>(&self, key: CacheKey, fetch: F) -> Result<T, ProviderError>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce(Self, CacheKey) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>> + Send + 'static,
+    {
+        status::record(CacheStatus::Miss);
+        let router = self.clone();
+        let flight_key = key.clone();
+        self.in_flight
+            .run(&flight_key, move || {
+                let fetched = fetch(router.clone(), key.clone());
+                async move {
+                    let result = fetched.await;
+                    if let Err(error) = &result {
+                        if let Some(entry) = NegativeEntry::from_error(error) {
+                            router.remember_negative(&key, entry).await;
+                        }
+                    }
+                    result
+                }
+            })
+            .await
+    }
+
+    /// Looks up a remembered not-found or invalid result for `key`.
+    async fn negative(&self, key: &CacheKey) -> Option<NegativeEntry> {
+        let entry = self.cache.get::<NegativeEntry>(&key.negative()?).await?;
+        status::record(CacheStatus::Fresh);
+        debug!("Negative cache hit for {}", key);
+        Some(entry)
+    }
+
+    async fn remember_negative(&self, key: &CacheKey, entry: NegativeEntry) {
+        if let Some(negative_key) = key.negative() {
+            self.cache.set(&negative_key, &entry).await;
+        }
+    }
+
+    /// Looks `key` up in the cache. An entry past its TTL but within its stale
+    /// window is still returned, and `refresh` re-fetches it in the background
+    /// the same way `load` would on a miss.
+    async fn cached<C, T, F, Fut>(&self, key: &CacheKey, refresh: F) -> Option<C>
+    where
+        C: DeserializeOwned,
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce(Self, CacheKey) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, ProviderError>> + Send + 'static,
+    {
+        self.cached_entry(key, refresh)
+            .await
+            .map(|cached| cached.value)
+    }
+
+    /// Like `cached`, keeping when the entry was stored so a value derived
+    /// from it can be written back with `CacheManager::update`.
+    async fn cached_entry<C, T, F, Fut>(&self, key: &CacheKey, refresh: F) -> Option<Cached<C>>
+    where
+        C: DeserializeOwned,
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce(Self, CacheKey) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, ProviderError>> + Send + 'static,
+    {
+        let cached = self.cache.lookup::<C>(key).await?;
+        if cached.fresh {
+            status::record(CacheStatus::Fresh);
+            return Some(cached);
+        }
+
+        status::record(CacheStatus::Stale);
+        debug!("Serving stale {} while it refreshes", key);
+        let router = self.clone();
+        let key = key.clone();
+        tokio::spawn(async move {
+            if let Err(e) = router.load(key.clone(), refresh).await {
+                debug!("Background refresh of {} failed: {}", key, e);
+            }
+        });
+        Some(cached)
+    }
+
+    pub async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
         let mut normalized = query.clone().with_defaults();
         // Default behavior: include active and inactive, exclude retired (unless caller specifies statuses)
         if self.govtools.is_some() && normalized.normalized_statuses().is_empty() {
@@ -47,29 +168,41 @@ impl CachedProviderRouter {
             filters: normalized.cache_descriptor(),
         };
 
+        let page = normalized.normalized_page();
+        let count = normalized.count;
+        let fetch = move |this: Self, cache_key: CacheKey| async move {
+            this.fetch_dreps_page(&normalized, &cache_key).await
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<DRepsPage>(&cache_key).await {
-            debug!(
-                "Cache hit for DReps page {}:{}",
-                normalized.normalized_page(),
-                normalized.count
-            );
+        if let Some(cached) = self
+            .cached::<DRepsPage, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
+            debug!("Cache hit for DReps page {}:{}", page, count);
             return Ok(cached);
         }
 
         // Cache miss - fetch from provider
         debug!(
             "Cache miss for DReps page {}:{}, fetching from provider",
-            normalized.normalized_page(),
-            normalized.count
+            page, count
         );
+        self.load(cache_key, fetch).await
+    }
+
+    async fn fetch_dreps_page(
+        &self,
+        normalized: &DRepsQuery,
+        cache_key: &CacheKey,
+    ) -> Result<DRepsPage, ProviderError> {
         let mut used_fallback = false;
         let mut result = if let Some(provider) = &self.govtools {
-            match provider.list_dreps(&normalized).await {
+            match provider.list_dreps(normalized).await {
                 Ok(page_result) => {
                     if page_result.dreps.is_empty() && !normalized.has_filters() {
                         used_fallback = true;
-                        self.router.get_dreps_page(&normalized).await?
+                        self.router.get_dreps_page(normalized).await?
                     } else {
                         page_result
                     }
@@ -83,12 +216,12 @@ impl CachedProviderRouter {
                         error
                     );
                     used_fallback = true;
-                    self.router.get_dreps_page(&normalized).await?
+                    self.router.get_dreps_page(normalized).await?
                 }
             }
         } else {
             used_fallback = true;
-            self.router.get_dreps_page(&normalized).await?
+            self.router.get_dreps_page(normalized).await?
         };
 
         if used_fallback && self.govtools.is_some() && !result.dreps.is_empty() {
@@ -98,20 +231,27 @@ impl CachedProviderRouter {
         }
 
         // Store in cache
-        self.cache.set(&cache_key, &result).await;
+        self.cache.set(cache_key, &result).await;
         Ok(result)
     }
 
-    pub async fn get_drep_stats(&self) -> Result<DRepStats, anyhow::Error> {
+    pub async fn get_drep_stats(&self) -> Result<DRepStats, ProviderError> {
         let cache_key = CacheKey::DRepStats;
 
-        if let Some(cached) = self.cache.get::<DRepStats>(&cache_key).await {
+        let fetch = |this: Self, cache_key: CacheKey| async move {
+            this.aggregate_drep_stats(&cache_key).await
+        };
+
+        if let Some(cached) = self.cached::<DRepStats, _, _, _>(&cache_key, fetch).await {
             debug!("Cache hit for DRep stats");
             return Ok(cached);
         }
 
         debug!("Cache miss for DRep stats, aggregating metrics");
+        self.load(cache_key, fetch).await
+    }
 
+    async fn aggregate_drep_stats(&self, cache_key: &CacheKey) -> Result<DRepStats, ProviderError> {
         let active_count = self.router.get_total_active_dreps().await?;
         let mut total_count: u64 = 0;
         let mut reported_total: Option<u64> = None;
@@ -203,90 +343,155 @@ impl CachedProviderRouter {
             top_drep,
         };
 
-        self.cache.set(&cache_key, &stats).await;
+        self.cache.set(cache_key, &stats).await;
         Ok(stats)
     }
 
-    pub async fn get_drep(&self, id: &str) -> Result<Option<DRep>, anyhow::Error> {
+    pub async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
         let cache_key = CacheKey::DRep { id: id.to_string() };
 
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                match this.router.get_drep(&id).await? {
+                    Some(drep) => {
+                        let enriched = this.enrich_drep(drep).await;
+                        // Store in cache
+                        this.cache.set(&cache_key, &enriched).await;
+                        Ok(Some(enriched))
+                    }
+                    None => {
+                        this.remember_negative(&cache_key, NegativeEntry::Absent)
+                            .await;
+                        Ok(None)
+                    }
+                }
+            }
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<DRep>(&cache_key).await {
+        if let Some(cached) = self
+            .cached::<DRep, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for DRep {}", id);
             return Ok(Some(cached));
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return negative.into_option();
+        }
+
         // Cache miss - fetch from provider
         debug!("Cache miss for DRep {}, fetching from provider", id);
-        match self.router.get_drep(id).await? {
-            Some(drep) => {
-                let enriched = self.enrich_drep(drep).await;
-                // Store in cache
-                self.cache.set(&cache_key, &enriched).await;
-                Ok(Some(enriched))
-            }
-            None => Ok(None),
-        }
+        self.load(cache_key, fetch).await
     }
 
-    pub async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, anyhow::Error> {
+    pub async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
         let cache_key = CacheKey::DRepDelegators { id: id.to_string() };
 
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                let result = this.router.get_drep_delegators(&id).await?;
+
+                // Store in cache
+                this.cache.set(&cache_key, &result).await;
+                Ok(result)
+            }
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<Vec<DRepDelegator>>(&cache_key).await {
+        if let Some(cached) = self
+            .cached::<Vec<DRepDelegator>, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for DRep delegators {}", id);
             return Ok(cached);
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return Err(negative.into_error());
+        }
+
         // Cache miss - fetch from provider
         debug!(
             "Cache miss for DRep delegators {}, fetching from provider",
             id
         );
-        let result = self.router.get_drep_delegators(id).await?;
-
-        // Store in cache
-        self.cache.set(&cache_key, &result).await;
-        Ok(result)
+        self.load(cache_key, fetch).await
     }
 
     pub async fn get_drep_voting_history(
         &self,
         id: &str,
-    ) -> Result<Vec<DRepVotingHistory>, anyhow::Error> {
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
         let cache_key = CacheKey::DRepVotingHistory { id: id.to_string() };
 
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                let indexed = this.synced_index().and_then(|index| index.drep_votes(&id));
+                let result = match indexed {
+                    Some(history) => history,
+                    None => this.router.get_drep_voting_history(&id).await?,
+                };
+
+                // Store in cache
+                this.cache.set(&cache_key, &result).await;
+                Ok(result)
+            }
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<Vec<DRepVotingHistory>>(&cache_key).await {
+        if let Some(cached) = self
+            .cached::<Vec<DRepVotingHistory>, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for DRep voting history {}", id);
             return Ok(cached);
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return Err(negative.into_error());
+        }
+
         // Cache miss - fetch from provider
         debug!(
             "Cache miss for DRep voting history {}, fetching from provider",
             id
         );
-        let result = self.router.get_drep_voting_history(id).await?;
-
-        // Store in cache
-        self.cache.set(&cache_key, &result).await;
-        Ok(result)
+        self.load(cache_key, fetch).await
     }
 
     pub async fn get_governance_actions_page(
         &self,
         page: u32,
         count: u32,
-    ) -> Result<ActionsPage, anyhow::Error> {
+    ) -> Result<ActionsPage, ProviderError> {
         let cache_key = CacheKey::ActionsPage { page, count };
 
+        let fetch = move |this: Self, cache_key: CacheKey| async move {
+            let mut result = this.router.get_governance_actions_page(page, count).await?;
+            result.actions = this.with_metadata_checks_for_list(result.actions).await;
+
+            // Store in cache
+            this.cache.set(&cache_key, &result).await;
+            Ok(result)
+        };
+
         // Check cache first
-        if let Some(mut cached) = self.cache.get::<ActionsPage>(&cache_key).await {
+        if let Some(cached) = self
+            .cached_entry::<ActionsPage, _, _, _>(&cache_key, fetch)
+            .await
+        {
             debug!("Cache hit for actions page {}:{}", page, count);
-            cached.actions = self.with_metadata_checks_for_list(cached.actions).await;
-            self.cache.set(&cache_key, &cached).await;
-            return Ok(cached);
+            let mut page = cached.value;
+            page.actions = self.with_metadata_checks_for_list(page.actions).await;
+            self.cache
+                .update(&cache_key, &page, cached.inserted_at)
+                .await;
+            return Ok(page);
         }
 
         // Cache miss - fetch from provider
@@ -294,92 +499,150 @@ impl CachedProviderRouter {
             "Cache miss for actions page {}:{}, fetching from provider",
             page, count
         );
-        let mut result = self.router.get_governance_actions_page(page, count).await?;
-        result.actions = self.with_metadata_checks_for_list(result.actions).await;
-
-        // Store in cache
-        self.cache.set(&cache_key, &result).await;
-        Ok(result)
+        self.load(cache_key, fetch).await
     }
 
     pub async fn get_governance_action(
         &self,
         id: &str,
-    ) -> Result<Option<GovernanceAction>, anyhow::Error> {
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
         let cache_key = CacheKey::Action { id: id.to_string() };
 
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                match this.router.get_governance_action(&id).await? {
+                    Some(action) => {
+                        let enriched = this.metadata_validator.attach_checks(action).await;
+                        let enriched = this.enrich_action_with_epoch_times(enriched).await;
+                        // Store in cache
+                        this.cache.set(&cache_key, &enriched).await;
+                        Ok(Some(enriched))
+                    }
+                    None => {
+                        this.remember_negative(&cache_key, NegativeEntry::Absent)
+                            .await;
+                        Ok(None)
+                    }
+                }
+            }
+        };
+
         // Check cache first
-        if let Some(mut cached) = self.cache.get::<GovernanceAction>(&cache_key).await {
+        if let Some(cached) = self
+            .cached_entry::<GovernanceAction, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for action {}", id);
-            cached = self.metadata_validator.attach_checks(cached).await;
-            cached = self.enrich_action_with_epoch_times(cached).await;
-            self.cache.set(&cache_key, &cached).await;
-            return Ok(Some(cached));
+            let action = self.metadata_validator.attach_checks(cached.value).await;
+            let action = self.enrich_action_with_epoch_times(action).await;
+            self.cache
+                .update(&cache_key, &action, cached.inserted_at)
+                .await;
+            return Ok(Some(action));
+        }
+
+        if let Some(negative) = self.negative(&cache_key).await {
+            return negative.into_option();
         }
 
         // Cache miss - fetch from provider
         debug!("Cache miss for action {}, fetching from provider", id);
-        match self.router.get_governance_action(id).await? {
-            Some(action) => {
-                let enriched = self.metadata_validator.attach_checks(action).await;
-                let enriched = self.enrich_action_with_epoch_times(enriched).await;
-                // Store in cache
-                self.cache.set(&cache_key, &enriched).await;
-                Ok(Some(enriched))
-            }
-            None => Ok(None),
-        }
+        self.load(cache_key, fetch).await
     }
 
     pub async fn get_action_voting_results(
         &self,
         id: &str,
-    ) -> Result<ActionVotingBreakdown, anyhow::Error> {
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
         let cache_key = CacheKey::ActionVotes { id: id.to_string() };
 
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                let result = this.router.get_action_voting_results(&id).await?;
+
+                // Store in cache
+                this.cache.set(&cache_key, &result).await;
+                Ok(result)
+            }
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<ActionVotingBreakdown>(&cache_key).await {
+        if let Some(cached) = self
+            .cached::<ActionVotingBreakdown, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for action votes {}", id);
             return Ok(cached);
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return Err(negative.into_error());
+        }
+
         // Cache miss - fetch from provider
         debug!("Cache miss for action votes {}, fetching from provider", id);
-        let result = self.router.get_action_voting_results(id).await?;
-
-        // Store in cache
-        self.cache.set(&cache_key, &result).await;
-        Ok(result)
+        self.load(cache_key, fetch).await
     }
 
     pub async fn get_action_voter_participation(
         &self,
         id: &str,
-    ) -> Result<ActionVoterParticipation, anyhow::Error> {
-        let cache_key = CacheKey::ActionParticipation {
-            id: id.to_string(),
+    ) -> Result<ActionVoterParticipation, ProviderError> {
+        let cache_key = CacheKey::ActionParticipation { id: id.to_string() };
+
+        // Walks every DRep and pool page, so concurrent requests must share one walk
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                this.build_action_voter_participation(&id, &cache_key).await
+            }
         };
 
         if let Some(cached) = self
-            .cache
-            .get::<ActionVoterParticipation>(&cache_key)
+            .cached::<ActionVoterParticipation, _, _, _>(&cache_key, fetch.clone())
             .await
         {
             debug!("Cache hit for action participation {}", id);
             return Ok(cached);
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return Err(negative.into_error());
+        }
+
+        self.load(cache_key, fetch).await
+    }
+
+    async fn build_action_voter_participation(
+        &self,
+        id: &str,
+        cache_key: &CacheKey,
+    ) -> Result<ActionVoterParticipation, ProviderError> {
         let action = match self.get_governance_action(id).await? {
             Some(action) => action,
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Governance action {} not found for participation lookup",
-                    id
-                ))
-            }
+            None => return Err(ProviderError::NotFound(format!("Governance action {}", id))),
         };
 
-        let vote_records = self.router.get_action_vote_records(&action).await?;
+        let indexed = self
+            .synced_index()
+            .zip(action.cert_index)
+            .and_then(|(index, cert_index)| index.action_votes(&action.tx_hash, cert_index));
+        // Participation is secondary to the action itself, so a failed lookup
+        // still gives an answer, just not one worth caching
+        let mut complete = true;
+        let vote_records = match indexed {
+            Some(records) => records,
+            None => match self.router.get_action_vote_records(&action).await {
+                Ok(records) => records,
+                Err(error) => {
+                    tracing::debug!("Vote records failed for {}: {}", id, error);
+                    complete = false;
+                    Vec::new()
+                }
+            },
+        };
 
         let mut drep_page = 1u32;
         let mut drep_participants: Vec<DRepParticipation> = Vec::new();
@@ -454,11 +717,7 @@ impl CachedProviderRouter {
         const MAX_POOL_PAGES: u32 = 80;
 
         while pool_page <= MAX_POOL_PAGES {
-            let pools_page = match self
-                .router
-                .get_stake_pools_page(pool_page, PAGE_SIZE)
-                .await
-            {
+            let pools_page = match self.router.get_stake_pools_page(pool_page, PAGE_SIZE).await {
                 Ok(page) => page,
                 Err(error) => {
                     tracing::debug!(
@@ -466,6 +725,7 @@ impl CachedProviderRouter {
                         pool_page,
                         error
                     );
+                    complete = false;
                     break;
                 }
             };
@@ -504,11 +764,14 @@ impl CachedProviderRouter {
             pool_page = pool_page.saturating_add(1);
         }
 
-        let committee_members = self
-            .router
-            .get_committee_members()
-            .await
-            .unwrap_or_default();
+        let committee_members = match self.router.get_committee_members().await {
+            Ok(members) => members,
+            Err(error) => {
+                tracing::debug!("Failed to fetch committee for participation: {}", error);
+                complete = false;
+                Vec::new()
+            }
+        };
 
         let mut committee_participants: Vec<CommitteeParticipation> = Vec::new();
         let mut committee_lookup: HashMap<String, usize> = HashMap::new();
@@ -737,7 +1000,9 @@ impl CachedProviderRouter {
             },
         };
 
-        self.cache.set(&cache_key, &participation).await;
+        if complete {
+            self.cache.set(cache_key, &participation).await;
+        }
 
         Ok(participation)
     }
@@ -796,7 +1061,7 @@ impl CachedProviderRouter {
         let results = join_all(futures).await;
 
         let mut epoch_time_map: HashMap<u32, Option<u64>> = HashMap::new();
-        for (epoch, time) in epoch_list.into_iter().zip(results.into_iter()) {
+        for (epoch, time) in epoch_list.into_iter().zip(results) {
             if let Some(value) = time {
                 known_times.insert(epoch, value);
             } else {
@@ -814,7 +1079,7 @@ impl CachedProviderRouter {
                         .get(&epoch)
                         .copied()
                         .flatten()
-                        .or_else(|| Self::infer_epoch_start_time(epoch, &known_times));
+                        .or_else(|| self.infer_epoch_start_time(epoch, &known_times));
                     if let Some(time) = resolved {
                         *field = Some(time);
                         known_times.insert(epoch, time);
@@ -840,23 +1105,36 @@ impl CachedProviderRouter {
         action
     }
 
-    async fn get_epoch_start_time_cached(&self, epoch: u32) -> Option<u64> {
+    pub async fn get_epoch_start_time_cached(&self, epoch: u32) -> Option<u64> {
+        // Shelley-era start times follow from the network's genesis parameters
+        if let Some(start_time) = self
+            .network
+            .genesis()
+            .and_then(|genesis| genesis.epoch_start_time(epoch))
+        {
+            return Some(start_time);
+        }
+
         let cache_key = CacheKey::EpochStartTime { epoch };
 
-        if let Some(cached) = self.cache.get::<Option<u64>>(&cache_key).await {
-            return cached;
-        }
+        let fetch = move |this: Self, cache_key: CacheKey| async move {
+            let start_time = match this.router.get_epoch_start_time(epoch).await {
+                Ok(value) => value,
+                Err(error) => {
+                    tracing::debug!("Error fetching epoch {} start time: {}", epoch, error);
+                    None
+                }
+            };
 
-        let start_time = match self.router.get_epoch_start_time(epoch).await {
-            Ok(value) => value,
-            Err(error) => {
-                tracing::debug!("Error fetching epoch {} start time: {}", epoch, error);
-                None
-            }
+            this.cache.set(&cache_key, &start_time).await;
+            Ok(start_time)
         };
 
-        self.cache.set(&cache_key, &start_time).await;
-        start_time
+        if let Some(cached) = self.cached::<Option<u64>, _, _, _>(&cache_key, fetch).await {
+            return cached;
+        }
+
+        self.load(cache_key, fetch).await.unwrap_or_default()
     }
 
     fn collect_known_epoch_times(action: &GovernanceAction) -> HashMap<u32, u64> {
@@ -891,11 +1169,17 @@ impl CachedProviderRouter {
         known
     }
 
-    fn infer_epoch_start_time(epoch: u32, known: &HashMap<u32, u64>) -> Option<u64> {
+    fn infer_epoch_start_time(&self, epoch: u32, known: &HashMap<u32, u64>) -> Option<u64> {
         if let Some(time) = known.get(&epoch) {
             return Some(*time);
         }
 
+        let epoch_length = self
+            .network
+            .genesis()
+            .map(|genesis| genesis.epoch_length)
+            .unwrap_or(DEFAULT_EPOCH_DURATION_SECONDS);
+
         let mut best: Option<(u64, u64)> = None;
 
         for (&known_epoch, &known_time) in known.iter() {
@@ -905,8 +1189,7 @@ impl CachedProviderRouter {
                 (known_epoch - epoch) as u64
             };
 
-            let Some(offset_seconds) = delta_epochs.checked_mul(CARDANO_EPOCH_DURATION_SECONDS)
-            else {
+            let Some(offset_seconds) = delta_epochs.checked_mul(epoch_length) else {
                 continue;
             };
 
@@ -930,70 +1213,185 @@ impl CachedProviderRouter {
     pub async fn get_drep_metadata(
         &self,
         id: &str,
-    ) -> Result<Option<serde_json::Value>, anyhow::Error> {
+    ) -> Result<Option<serde_json::Value>, ProviderError> {
         let cache_key = CacheKey::DRepMetadata { id: id.to_string() };
 
+        let fetch = {
+            let id = id.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                match this.router.get_drep_metadata(&id).await? {
+                    Some(metadata) => {
+                        // Store in cache
+                        this.cache.set(&cache_key, &metadata).await;
+                        Ok(Some(metadata))
+                    }
+                    None => {
+                        this.remember_negative(&cache_key, NegativeEntry::Absent)
+                            .await;
+                        Ok(None)
+                    }
+                }
+            }
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<serde_json::Value>(&cache_key).await {
+        if let Some(cached) = self
+            .cached::<serde_json::Value, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for DRep metadata {}", id);
             return Ok(Some(cached));
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return negative.into_option();
+        }
+
         // Cache miss - fetch from provider
         debug!(
             "Cache miss for DRep metadata {}, fetching from provider",
             id
         );
-        match self.router.get_drep_metadata(id).await? {
-            Some(metadata) => {
-                // Store in cache
-                self.cache.set(&cache_key, &metadata).await;
-                Ok(Some(metadata))
-            }
-            None => Ok(None),
-        }
+        self.load(cache_key, fetch).await
     }
 
     pub async fn get_stake_delegation(
         &self,
         stake_address: &str,
-    ) -> Result<Option<StakeDelegation>, anyhow::Error> {
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
+        self.check_stake_address(stake_address)?;
         let cache_key = CacheKey::StakeDelegation {
             stake_address: stake_address.to_string(),
         };
 
+        let fetch = {
+            let stake_address = stake_address.to_string();
+            move |this: Self, cache_key: CacheKey| async move {
+                match this.router.get_stake_delegation(&stake_address).await? {
+                    Some(delegation) => {
+                        // Store in cache
+                        this.cache.set(&cache_key, &delegation).await;
+                        Ok(Some(delegation))
+                    }
+                    None => {
+                        this.remember_negative(&cache_key, NegativeEntry::Absent)
+                            .await;
+                        Ok(None)
+                    }
+                }
+            }
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.get::<StakeDelegation>(&cache_key).await {
+        if let Some(cached) = self
+            .cached::<StakeDelegation, _, _, _>(&cache_key, fetch.clone())
+            .await
+        {
             debug!("Cache hit for stake delegation {}", stake_address);
             return Ok(Some(cached));
         }
 
+        if let Some(negative) = self.negative(&cache_key).await {
+            return negative.into_option();
+        }
+
         // Cache miss - fetch from provider
         debug!(
             "Cache miss for stake delegation {}, fetching from provider",
             stake_address
         );
-        match self.router.get_stake_delegation(stake_address).await? {
-            Some(delegation) => {
-                // Store in cache
-                self.cache.set(&cache_key, &delegation).await;
-                Ok(Some(delegation))
+        self.load(cache_key, fetch).await
+    }
+
+    /// Ledger governance state from Ogmios; `None` when no Ogmios endpoint
+    /// is configured for this network.
+    pub async fn get_ledger_state(&self) -> Result<Option<LedgerState>, ProviderError> {
+        let cache_key = CacheKey::LedgerState;
+
+        let fetch = |this: Self, cache_key: CacheKey| async move {
+            let state = this.router.get_ledger_state().await?;
+            if let Some(state) = &state {
+                this.cache.set(&cache_key, state).await;
             }
-            None => Ok(None),
+            Ok(state)
+        };
+
+        if let Some(cached) = self.cached::<LedgerState, _, _, _>(&cache_key, fetch).await {
+            debug!("Cache hit for ledger state");
+            return Ok(Some(cached));
+        }
+
+        debug!("Cache miss for ledger state, querying Ogmios");
+        self.load(cache_key, fetch).await
+    }
+
+    // Rejects stake addresses that are malformed or belong to another network
+    // before they cost an upstream call
+    fn check_stake_address(&self, stake_address: &str) -> Result<(), ProviderError> {
+        let (hrp, _) = decode_bech32(stake_address).map_err(|e| {
+            ProviderError::InvalidInput(format!("stake address {}: {}", stake_address, e))
+        })?;
+        let expected = self.network.stake_hrp();
+        if hrp != expected {
+            return Err(ProviderError::InvalidInput(format!(
+                "stake address {} is not a {} address (expected prefix {})",
+                stake_address, self.network, expected
+            )));
         }
+        Ok(())
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
     }
 
-    pub async fn health_check(&self) -> Result<bool, anyhow::Error> {
+    pub async fn health_check(&self) -> Result<bool, ProviderError> {
         self.router.health_check().await
     }
 
+    pub fn provider_health(&self) -> Vec<ProviderHealthSnapshot> {
+        self.router.provider_health()
+    }
+
+    pub fn provider_quotas(&self) -> Vec<QuotaSnapshot> {
+        self.router.provider_quotas()
+    }
+
+    /// Compares Koios and Blockfrost directly; the cache is neither read nor filled.
+    pub async fn check_consistency(&self, kind: ConsistencyKind, id: &str) -> ConsistencyReport {
+        self.router.check_consistency(kind, id).await
+    }
+
+    pub fn consistency_stats(&self) -> Vec<(ConsistencyKind, KindCounts)> {
+        self.router.consistency_stats()
+    }
+
+    pub async fn get_chain_tip(&self) -> Result<Option<ChainTip>, ProviderError> {
+        self.router.get_chain_tip().await
+    }
+
+    pub async fn get_votes_since(
+        &self,
+        block_height: u64,
+        limit: u32,
+    ) -> Result<Vec<RecentVote>, ProviderError> {
+        self.router.get_votes_since(block_height, limit).await
+    }
+
+    pub fn cache(&self) -> &CacheManager {
+        &self.cache
+    }
+
     pub async fn cache_stats(&self) -> CacheStats {
+        let by_kind = self.cache.kind_stats().await;
         CacheStats {
             enabled: self.cache.is_enabled(),
             entries: self.cache.entry_count(),
+            l2: self.cache.l2_backend(),
             hits: self.cache.hit_count(),
             misses: self.cache.miss_count(),
             hit_rate: self.cache.hit_rate(),
+            by_kind,
         }
     }
 
@@ -1093,14 +1491,14 @@ impl CachedProviderRouter {
                 if !identity_references.is_empty() {
                     has_profile_data = true;
                     match &mut drep.identity_references {
-                        Some(existing) => existing.extend(identity_references.into_iter()),
+                        Some(existing) => existing.extend(identity_references),
                         None => drep.identity_references = Some(identity_references),
                     }
                 }
 
                 if !link_references.is_empty() {
                     match &mut drep.link_references {
-                        Some(existing) => existing.extend(link_references.into_iter()),
+                        Some(existing) => existing.extend(link_references),
                         None => drep.link_references = Some(link_references),
                     }
                 }
@@ -1118,7 +1516,7 @@ impl CachedProviderRouter {
         drep
     }
 
-    async fn fetch_stats_page(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
+    async fn fetch_stats_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
         if let Some(provider) = &self.govtools {
             match provider.list_dreps(query).await {
                 Ok(result) => return Ok(result),
@@ -1142,11 +1540,9 @@ impl CachedProviderRouter {
             drep.voting_power.as_deref(),
         ];
 
-        for candidate in candidates {
-            if let Some(value) = candidate {
-                if let Ok(parsed) = value.parse::<u128>() {
-                    return Some(parsed);
-                }
+        for value in candidates.into_iter().flatten() {
+            if let Ok(parsed) = value.parse::<u128>() {
+                return Some(parsed);
             }
         }
 
@@ -1186,7 +1582,217 @@ impl CachedProviderRouter {
 pub struct CacheStats {
     pub enabled: bool,
     pub entries: usize,
+    pub l2: Option<&'static str>,
     pub hits: u64,
     pub misses: u64,
     pub hit_rate: f64,
+    pub by_kind: Vec<KindStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::routing::{ProviderKind, RoutingPolicy};
+    use crate::providers::testing::StubProvider;
+    use serde_json::json;
+    use std::time::Duration;
+
+    const DREP: &str = "drep1test";
+
+    // Routes through a Koios and a Blockfrost stub with the default policy
+    fn cached(koios: &Arc<StubProvider>, blockfrost: &Arc<StubProvider>) -> CachedProviderRouter {
+        let router = ProviderRouter::stubbed(
+            RoutingPolicy::default(),
+            &[
+                (ProviderKind::Koios, koios.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+            ],
+        );
+        let cache = CacheManager::new(true, 100, HashMap::new(), None);
+        CachedProviderRouter::new(router, cache, None, None, Network::Preview)
+    }
+
+    fn calls(stubs: &[&Arc<StubProvider>]) -> usize {
+        stubs.iter().map(|stub| stub.calls()).sum()
+    }
+
+    fn negative_key() -> String {
+        CacheKey::DRep {
+            id: DREP.to_string(),
+        }
+        .negative()
+        .unwrap()
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn absent_result_is_replayed_without_going_upstream() {
+        let (koios, blockfrost) = (Arc::new(StubProvider::new()), Arc::new(StubProvider::new()));
+        let router = cached(&koios, &blockfrost);
+
+        assert!(router.get_drep(DREP).await.unwrap().is_none());
+        let upstream = calls(&[&koios, &blockfrost]);
+        assert!(upstream > 0);
+
+        assert!(router.get_drep(DREP).await.unwrap().is_none());
+        assert_eq!(calls(&[&koios, &blockfrost]), upstream);
+    }
+
+    #[tokio::test]
+    async fn not_found_and_invalid_input_are_replayed() {
+        let errors = [
+            ProviderError::NotFound("DRep".to_string()),
+            ProviderError::InvalidInput("bad id".to_string()),
+        ];
+        for error in errors {
+            let stub = || Arc::new(StubProvider::new().failing_with(error.clone()));
+            let (koios, blockfrost) = (stub(), stub());
+            let router = cached(&koios, &blockfrost);
+
+            let first = router.get_drep(DREP).await.unwrap_err();
+            let upstream = calls(&[&koios, &blockfrost]);
+            let second = router.get_drep(DREP).await.unwrap_err();
+
+            assert_eq!(second.to_string(), first.to_string());
+            assert_eq!(calls(&[&koios, &blockfrost]), upstream, "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_errors_go_back_upstream() {
+        let errors = [
+            ProviderError::Timeout,
+            ProviderError::Transport("connection reset".to_string()),
+            ProviderError::Unavailable("circuit open".to_string()),
+        ];
+        for error in errors {
+            let stub = || Arc::new(StubProvider::new().failing_with(error.clone()));
+            let (koios, blockfrost) = (stub(), stub());
+            let router = cached(&koios, &blockfrost);
+
+            assert!(router.get_drep(DREP).await.is_err());
+            let upstream = calls(&[&koios, &blockfrost]);
+            assert!(router.get_drep(DREP).await.is_err());
+
+            assert_eq!(calls(&[&koios, &blockfrost]), 2 * upstream, "{}", error);
+            assert!(router.cache.inspect(&negative_key()).await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn negative_entry_expires_after_its_ttl() {
+        let (koios, blockfrost) = (Arc::new(StubProvider::new()), Arc::new(StubProvider::new()));
+        let router = cached(&koios, &blockfrost);
+
+        router.get_drep(DREP).await.unwrap();
+        let upstream = calls(&[&koios, &blockfrost]);
+        let mut entry = router.cache.inspect(&negative_key()).await.unwrap();
+        assert_eq!(entry.ttl, Duration::from_secs(30));
+
+        // Pretend it was stored 31 seconds ago
+        entry.inserted_at -= Duration::from_secs(31);
+        router.cache.restore(&negative_key(), &entry).await;
+
+        router.get_drep(DREP).await.unwrap();
+        assert_eq!(calls(&[&koios, &blockfrost]), 2 * upstream);
+    }
+
+    #[tokio::test]
+    async fn cached_value_replaces_a_negative_entry() {
+        let (koios, blockfrost) = (Arc::new(StubProvider::new()), Arc::new(StubProvider::new()));
+        let router = cached(&koios, &blockfrost);
+
+        assert!(router.get_drep(DREP).await.unwrap().is_none());
+        let upstream = calls(&[&koios, &blockfrost]);
+
+        // E.g. the warm-up job or a background refresh finding the DRep
+        let key = CacheKey::DRep {
+            id: DREP.to_string(),
+        };
+        let drep: DRep = serde_json::from_value(json!({ "drep_id": DREP })).unwrap();
+        router.cache.set(&key, &drep).await;
+
+        let found = router.get_drep(DREP).await.unwrap().unwrap();
+        assert_eq!(found.drep_id, DREP);
+        assert_eq!(calls(&[&koios, &blockfrost]), upstream);
+    }
+
+    #[tokio::test]
+    async fn stale_entry_is_served_then_replaced_by_its_refresh() {
+        use crate::state::{AppState, NetworkState};
+        use axum::body::Body;
+        use axum::http::Request;
+        use axum::{middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        const ACTION: &str = "gov_action1test";
+        let action = |status: &str| {
+            json!({
+                "tx_hash": format!("{:064x}", 1),
+                "action_id": ACTION,
+                "type": "info_action",
+                "status": status,
+            })
+        };
+        let koios = Arc::new(StubProvider::new().answer(ACTION, action("ratified")));
+        let blockfrost = Arc::new(StubProvider::new());
+        let router = cached(&koios, &blockfrost);
+
+        // Cached as `voting`, and past its TTL but within its stale window
+        let key = CacheKey::Action {
+            id: ACTION.to_string(),
+        };
+        let voting: GovernanceAction = serde_json::from_value(action("voting")).unwrap();
+        router.cache.set(&key, &voting).await;
+        let mut entry = router.cache.inspect(&key.to_string()).await.unwrap();
+        entry.inserted_at -= entry.ttl + Duration::from_secs(1);
+        router.cache.restore(&key.to_string(), &entry).await;
+
+        let state = AppState {
+            default_network: Network::Preview,
+            networks: Arc::new(HashMap::from([(
+                Network::Preview,
+                NetworkState {
+                    router: router.clone(),
+                    warmer: Arc::default(),
+                },
+            )])),
+        };
+        let app = Router::new()
+            .route("/api/actions/:id", get(crate::api::actions::get_action))
+            .layer(middleware::from_fn(status::cache_status_header))
+            .with_state(state);
+        let get_action = || async {
+            let uri = format!("/api/actions/{}", ACTION);
+            let response = app
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.headers()["x-cache-status"].clone();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            (status, body["status"].clone())
+        };
+
+        let (cache_status, action_status) = get_action().await;
+        assert_eq!(cache_status, "stale");
+        assert_eq!(action_status, "voting");
+
+        // Wait for the background refresh to store the new value
+        for _ in 0..100 {
+            let entry = router.cache.inspect(&key.to_string()).await.unwrap();
+            if entry.is_fresh() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let (cache_status, action_status) = get_action().await;
+        assert_eq!(cache_status, "fresh");
+        assert_eq!(action_status, "ratified");
+        assert_eq!(koios.calls(), 1);
+    }
 }
@@ -0,0 +1,538 @@
+use crate::providers::ProviderError;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entities that can be compared between Koios and Blockfrost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsistencyKind {
+    Drep,
+    Action,
+    Votes,
+}
+
+impl ConsistencyKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "drep" => Some(Self::Drep),
+            "action" => Some(Self::Action),
+            "votes" => Some(Self::Votes),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Drep => "drep",
+            Self::Action => "action",
+            Self::Votes => "votes",
+        }
+    }
+
+    // Fields that are off-chain content or a provider's own presentation
+    // rather than chain data, so they differ without either being wrong
+    fn ignored(&self) -> &'static [&'static str] {
+        match self {
+            Self::Drep => &["metadata"],
+            Self::Action => &["meta_json", "description"],
+            Self::Votes => &["vote_timeline"],
+        }
+    }
+}
+
+impl fmt::Display for ConsistencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What one provider answered.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProviderOutcome {
+    Found,
+    NotFound,
+    Error { message: String },
+}
+
+/// A field both providers filled in with different values, or that only one
+/// of them filled in (the other side is `null`).
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub koios: Value,
+    pub blockfrost: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsistencyReport {
+    pub kind: ConsistencyKind,
+    pub id: String,
+    pub checked_at: u64,
+    pub koios: ProviderOutcome,
+    pub blockfrost: ProviderOutcome,
+    /// `None` when a provider failed and there was nothing to compare
+    pub consistent: Option<bool>,
+    /// Fields both providers filled in
+    pub compared_fields: usize,
+    pub diffs: Vec<FieldDiff>,
+    /// Fields only one provider filled in; reported, but not a discrepancy
+    pub only_koios: Vec<String>,
+    pub only_blockfrost: Vec<String>,
+}
+
+impl ConsistencyReport {
+    /// Compares two providers' answers for the same entity, field by field
+    /// after normalization.
+    pub fn compare<T: Serialize>(
+        kind: ConsistencyKind,
+        id: &str,
+        koios: Result<Option<T>, ProviderError>,
+        blockfrost: Result<Option<T>, ProviderError>,
+    ) -> Self {
+        let (koios_outcome, koios) = outcome(koios);
+        let (blockfrost_outcome, blockfrost) = outcome(blockfrost);
+        let mut report = ConsistencyReport {
+            kind,
+            id: id.to_string(),
+            checked_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            koios: koios_outcome,
+            blockfrost: blockfrost_outcome,
+            consistent: None,
+            compared_fields: 0,
+            diffs: Vec::new(),
+            only_koios: Vec::new(),
+            only_blockfrost: Vec::new(),
+        };
+
+        let (koios, blockfrost) = match (koios, blockfrost) {
+            (Some(Some(koios)), Some(Some(blockfrost))) => (koios, blockfrost),
+            (Some(koios), Some(blockfrost)) => {
+                // One provider knows the entity and the other doesn't
+                let found = koios.is_some() != blockfrost.is_some();
+                if found {
+                    report.diffs.push(FieldDiff {
+                        field: "(exists)".to_string(),
+                        koios: Value::Bool(koios.is_some()),
+                        blockfrost: Value::Bool(blockfrost.is_some()),
+                    });
+                }
+                report.consistent = Some(!found);
+                return report;
+            }
+            _ => return report,
+        };
+
+        let mut koios_fields = BTreeMap::new();
+        let mut blockfrost_fields = BTreeMap::new();
+        flatten(&koios, String::new(), kind.ignored(), &mut koios_fields);
+        flatten(
+            &blockfrost,
+            String::new(),
+            kind.ignored(),
+            &mut blockfrost_fields,
+        );
+
+        let fields: BTreeSet<&String> = koios_fields
+            .keys()
+            .chain(blockfrost_fields.keys())
+            .collect();
+        for field in fields {
+            match (koios_fields.get(field), blockfrost_fields.get(field)) {
+                (Some(k), Some(b)) => {
+                    report.compared_fields += 1;
+                    if !same(k, b) {
+                        report.diffs.push(FieldDiff {
+                            field: field.clone(),
+                            koios: k.clone(),
+                            blockfrost: b.clone(),
+                        });
+                    }
+                }
+                (Some(_), None) => report.only_koios.push(field.clone()),
+                (None, Some(_)) => report.only_blockfrost.push(field.clone()),
+                (None, None) => {}
+            }
+        }
+        report.consistent = Some(report.diffs.is_empty());
+        report
+    }
+}
+
+// The outcome to report, and the answer as JSON when there was one
+fn outcome<T: Serialize>(
+    result: Result<Option<T>, ProviderError>,
+) -> (ProviderOutcome, Option<Option<Value>>) {
+    match result {
+        Ok(Some(value)) => match serde_json::to_value(value) {
+            Ok(value) => (ProviderOutcome::Found, Some(Some(value))),
+            Err(e) => (
+                ProviderOutcome::Error {
+                    message: e.to_string(),
+                },
+                None,
+            ),
+        },
+        Ok(None) | Err(ProviderError::NotFound(_)) => (ProviderOutcome::NotFound, Some(None)),
+        Err(e) => (
+            ProviderOutcome::Error {
+                message: e.to_string(),
+            },
+            None,
+        ),
+    }
+}
+
+// Collects the non-empty leaves of `value` under dotted paths, e.g.
+// `drep_votes.yes`; array elements get their index, e.g. `votes[2].vote`
+fn flatten(value: &Value, path: String, ignored: &[&str], out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                if path.is_empty() && ignored.contains(&key.as_str()) {
+                    continue;
+                }
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                flatten(value, path, ignored, out);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(item, format!("{}[{}]", path, index), ignored, out);
+            }
+        }
+        Value::Null => {}
+        Value::String(s) if s.trim().is_empty() => {}
+        leaf => {
+            out.insert(path, leaf.clone());
+        }
+    }
+}
+
+// Providers disagree on representation more than on content: lovelace comes
+// as strings or numbers, hashes in either case. Integers are compared exactly,
+// as lovelace amounts go past what an f64 holds
+fn same(a: &Value, b: &Value) -> bool {
+    match (normalize(a), normalize(b)) {
+        (Normalized::Integer(a), Normalized::Integer(b)) => a == b,
+        (Normalized::Integer(a), Normalized::Number(b))
+        | (Normalized::Number(b), Normalized::Integer(a)) => close(a as f64, b),
+        (Normalized::Number(a), Normalized::Number(b)) => close(a, b),
+        (a, b) => a == b,
+    }
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= f64::EPSILON * a.abs().max(b.abs()).max(1.0)
+}
+
+#[derive(PartialEq)]
+enum Normalized {
+    Integer(i128),
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Other,
+}
+
+fn normalize(value: &Value) -> Normalized {
+    match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(|n| Normalized::Integer(n.into()))
+            .or_else(|| n.as_u64().map(|n| Normalized::Integer(n.into())))
+            .or_else(|| n.as_f64().map(Normalized::Number))
+            .unwrap_or(Normalized::Other),
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(n) = s.parse::<i128>() {
+                return Normalized::Integer(n);
+            }
+            // Digit strings too long for an i128 are identifiers, not amounts
+            let digits = s.strip_prefix('-').unwrap_or(s);
+            if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Normalized::Text(s.to_string());
+            }
+            match s.parse::<f64>() {
+                Ok(n) if n.is_finite() => Normalized::Number(n),
+                _ => Normalized::Text(s.to_lowercase()),
+            }
+        }
+        Value::Bool(b) => Normalized::Bool(*b),
+        _ => Normalized::Other,
+    }
+}
+
+/// Check and discrepancy counts since startup, exported on `/metrics`.
+#[derive(Debug, Default)]
+pub struct ConsistencyMonitor {
+    sample_rate: f64,
+    counts: Mutex<HashMap<ConsistencyKind, KindCounts>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KindCounts {
+    /// Checks where both providers answered
+    pub checks: u64,
+    /// Of those, checks with at least one differing field
+    pub inconsistent: u64,
+    /// Differing fields, with array indices collapsed to `[]`
+    pub fields: BTreeMap<String, u64>,
+}
+
+impl ConsistencyMonitor {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether to shadow this request with a consistency check.
+    pub fn sample(&self) -> bool {
+        self.sample_rate > 0.0 && fastrand::f64() < self.sample_rate
+    }
+
+    /// Counts a finished check and logs its discrepancies.
+    pub fn record(&self, report: &ConsistencyReport) {
+        let Some(consistent) = report.consistent else {
+            return;
+        };
+        {
+            let mut counts = self.counts.lock().unwrap();
+            let counts = counts.entry(report.kind).or_default();
+            counts.checks += 1;
+            if !consistent {
+                counts.inconsistent += 1;
+            }
+            for diff in &report.diffs {
+                *counts
+                    .fields
+                    .entry(collapse_indices(&diff.field))
+                    .or_default() += 1;
+            }
+        }
+
+        if !consistent {
+            tracing::warn!(
+                "Koios and Blockfrost disagree on {} {}: {}",
+                report.kind,
+                report.id,
+                report
+                    .diffs
+                    .iter()
+                    .map(|diff| format!("{} ({} vs {})", diff.field, diff.koios, diff.blockfrost))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<(ConsistencyKind, KindCounts)> {
+        let mut counts: Vec<_> = self
+            .counts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(kind, counts)| (*kind, counts.clone()))
+            .collect();
+        counts.sort_by_key(|(kind, _)| *kind);
+        counts
+    }
+}
+
+// `votes[3].vote` -> `votes[].vote`, to keep metric labels bounded
+fn collapse_indices(field: &str) -> String {
+    let mut collapsed = String::with_capacity(field.len());
+    let mut in_index = false;
+    for c in field.chars() {
+        match c {
+            '[' => {
+                in_index = true;
+                collapsed.push('[');
+            }
+            ']' => {
+                in_index = false;
+                collapsed.push(']');
+            }
+            _ if in_index => {}
+            c => collapsed.push(c),
+        }
+    }
+    collapsed
+}
+
+/// Wraps a value in `Some` for `ConsistencyReport::compare`, for operations
+/// that answer with a plain value rather than an `Option`.
+pub fn found<T>(result: Result<T, ProviderError>) -> Result<Option<T>, ProviderError> {
+    result.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn compare(kind: ConsistencyKind, koios: Value, blockfrost: Value) -> ConsistencyReport {
+        ConsistencyReport::compare(kind, "id", Ok(Some(koios)), Ok(Some(blockfrost)))
+    }
+
+    fn diff_fields(report: &ConsistencyReport) -> Vec<&str> {
+        report
+            .diffs
+            .iter()
+            .map(|diff| diff.field.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn reports_an_entity_only_one_provider_knows() {
+        let report = ConsistencyReport::compare(
+            ConsistencyKind::Drep,
+            "drep1abc",
+            Ok(Some(json!({ "drep_id": "drep1abc" }))),
+            Ok(None),
+        );
+        assert_eq!(report.consistent, Some(false));
+        assert_eq!(diff_fields(&report), ["(exists)"]);
+        assert_eq!(report.diffs[0].koios, json!(true));
+        assert_eq!(report.diffs[0].blockfrost, json!(false));
+        assert!(matches!(report.blockfrost, ProviderOutcome::NotFound));
+
+        // A NotFound error is the same as an empty answer
+        let report = ConsistencyReport::compare::<Value>(
+            ConsistencyKind::Drep,
+            "drep1abc",
+            Ok(None),
+            Err(ProviderError::NotFound("DRep".to_string())),
+        );
+        assert_eq!(report.consistent, Some(true));
+        assert!(report.diffs.is_empty());
+
+        // Nothing to compare when a provider failed
+        let report = ConsistencyReport::compare(
+            ConsistencyKind::Drep,
+            "drep1abc",
+            Ok(Some(json!({}))),
+            Err(ProviderError::Timeout),
+        );
+        assert_eq!(report.consistent, None);
+        assert!(matches!(report.blockfrost, ProviderOutcome::Error { .. }));
+    }
+
+    #[test]
+    fn skips_ignored_and_empty_fields() {
+        let report = compare(
+            ConsistencyKind::Action,
+            json!({
+                "action_id": "gov_action1abc",
+                "description": "Koios summary",
+                "meta_json": { "title": "A" },
+                "deposit": "100000000000",
+                "status": "",
+            }),
+            json!({
+                "action_id": "gov_action1abc",
+                "description": "Blockfrost summary",
+                "meta_json": { "title": "B" },
+                "deposit": "100000000000",
+                "status": null,
+                "block_time": 1700000000,
+            }),
+        );
+        assert_eq!(report.consistent, Some(true));
+        assert_eq!(report.compared_fields, 2);
+        assert!(report.only_koios.is_empty());
+        assert_eq!(report.only_blockfrost, ["block_time"]);
+
+        // Only top-level fields are ignored
+        let report = compare(
+            ConsistencyKind::Drep,
+            json!({ "metadata": { "name": "A" }, "extra": { "metadata": "A" } }),
+            json!({ "metadata": { "name": "B" }, "extra": { "metadata": "B" } }),
+        );
+        assert_eq!(diff_fields(&report), ["extra.metadata"]);
+    }
+
+    #[test]
+    fn lovelace_as_string_or_number_is_compared_exactly() {
+        let report = compare(
+            ConsistencyKind::Votes,
+            json!({ "drep_votes": { "yes": "12500000000000", "no": 3 } }),
+            json!({ "drep_votes": { "yes": 12500000000000u64, "no": "3" } }),
+        );
+        assert_eq!(report.consistent, Some(true));
+        assert_eq!(report.compared_fields, 2);
+
+        // Past 2^53, a few lovelace apart
+        let report = compare(
+            ConsistencyKind::Drep,
+            json!({ "amount": "36028797018963971" }),
+            json!({ "amount": 36028797018963968u64 }),
+        );
+        assert_eq!(report.consistent, Some(false));
+        assert_eq!(diff_fields(&report), ["amount"]);
+
+        let report = compare(
+            ConsistencyKind::Drep,
+            json!({ "amount": "340282366920938463463374607431768211455" }),
+            json!({ "amount": "340282366920938463463374607431768211454" }),
+        );
+        assert_eq!(report.consistent, Some(false));
+
+        let report = compare(
+            ConsistencyKind::Action,
+            json!({ "threshold": "0.67", "ratio": 2 }),
+            json!({ "threshold": 0.67, "ratio": "2.0" }),
+        );
+        assert_eq!(report.consistent, Some(true));
+    }
+
+    #[test]
+    fn hashes_differing_only_in_case_are_the_same() {
+        let hash = "07981c27153a7f7b19b5d0f80f2b23e4ceb683285c28d2af001f750889b59315";
+        let report = compare(
+            ConsistencyKind::Action,
+            json!({ "tx_hash": hash }),
+            json!({ "tx_hash": hash.to_uppercase() }),
+        );
+        assert_eq!(report.consistent, Some(true));
+
+        let report = compare(
+            ConsistencyKind::Action,
+            json!({ "tx_hash": hash }),
+            json!({ "tx_hash": hash.replace('0', "1") }),
+        );
+        assert_eq!(diff_fields(&report), ["tx_hash"]);
+    }
+
+    #[test]
+    fn array_indices_are_collapsed_for_metrics() {
+        let report = compare(
+            ConsistencyKind::Votes,
+            json!({ "votes": [{ "vote": "yes" }, { "vote": "no" }] }),
+            json!({ "votes": [{ "vote": "yes" }, { "vote": "abstain" }] }),
+        );
+        assert_eq!(diff_fields(&report), ["votes[1].vote"]);
+        assert_eq!(collapse_indices("votes[1].vote"), "votes[].vote");
+        assert_eq!(collapse_indices("a[10].b[2]"), "a[].b[]");
+
+        let monitor = ConsistencyMonitor::new(1.0);
+        monitor.record(&report);
+        let (kind, counts) = &monitor.snapshot()[0];
+        assert_eq!(*kind, ConsistencyKind::Votes);
+        assert_eq!((counts.checks, counts.inconsistent), (1, 1));
+        assert_eq!(counts.fields["votes[].vote"], 1);
+    }
+}
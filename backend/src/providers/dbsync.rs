@@ -0,0 +1,1121 @@
+use crate::models::*;
+use crate::providers::error::ProviderError;
+use crate::providers::Provider;
+use crate::utils::drep_id::{
+    convert_to_cip105, decode_drep_id_to_hex, is_special_system_drep, normalize_to_cip129,
+};
+use crate::utils::proposal_id::{format_proposal_id, resolve_proposal_id, to_cip129_proposal_id};
+use async_trait::async_trait;
+use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tokio_postgres::types::{FromSql, ToSql};
+use tokio_postgres::{NoTls, Row};
+
+/// Queries a cardano-db-sync PostgreSQL database directly (schema 13.5 or
+/// later, for the governance and off-chain vote tables).
+pub struct DbSyncProvider {
+    pool: Pool,
+}
+
+// Latest registration, anchor, off-chain profile and current voting power of
+// every registered DRep; callers add the WHERE, ORDER BY and LIMIT clauses
+const DREPS_SQL: &str = r#"
+WITH current AS (
+    SELECT max(epoch_no) AS epoch_no FROM drep_distr
+),
+dreps AS (
+    SELECT
+        dh.id,
+        dh.view,
+        encode(dh.raw, 'hex') AS raw,
+        dh.has_script,
+        encode(latest_tx.hash, 'hex') AS latest_tx_hash,
+        encode(reg_tx.hash, 'hex') AS registration_tx_hash,
+        reg_block.epoch_no::integer AS registration_epoch,
+        reg.deposit::text AS deposit,
+        reg.tx_id AS registration_tx_id,
+        va.url AS anchor_url,
+        encode(va.data_hash, 'hex') AS anchor_hash,
+        ocvd.json AS metadata,
+        dd.given_name,
+        dd.objectives,
+        dd.motivations,
+        dd.qualifications,
+        dd.image_url,
+        dd.image_hash::text AS image_hash,
+        dd.payment_address,
+        distr.amount AS amount,
+        distr.amount::text AS voting_power,
+        distr.active_until::integer AS active_until,
+        CASE
+            WHEN latest.deposit < 0 THEN 'retired'
+            WHEN distr.active_until < current.epoch_no THEN 'inactive'
+            ELSE 'active'
+        END AS status
+    FROM drep_hash dh
+    CROSS JOIN current
+    JOIN LATERAL (
+        SELECT r.deposit, r.voting_anchor_id, r.tx_id
+        FROM drep_registration r
+        WHERE r.drep_hash_id = dh.id
+        ORDER BY r.tx_id DESC, r.cert_index DESC
+        LIMIT 1
+    ) latest ON true
+    LEFT JOIN LATERAL (
+        SELECT r.deposit, r.tx_id
+        FROM drep_registration r
+        WHERE r.drep_hash_id = dh.id AND r.deposit > 0
+        ORDER BY r.tx_id DESC
+        LIMIT 1
+    ) reg ON true
+    JOIN tx latest_tx ON latest_tx.id = latest.tx_id
+    LEFT JOIN tx reg_tx ON reg_tx.id = reg.tx_id
+    LEFT JOIN block reg_block ON reg_block.id = reg_tx.block_id
+    LEFT JOIN voting_anchor va ON va.id = latest.voting_anchor_id
+    LEFT JOIN LATERAL (
+        SELECT o.id, o.json
+        FROM off_chain_vote_data o
+        WHERE o.voting_anchor_id = va.id
+        ORDER BY o.id DESC
+        LIMIT 1
+    ) ocvd ON true
+    LEFT JOIN off_chain_vote_drep_data dd ON dd.off_chain_vote_data_id = ocvd.id
+    LEFT JOIN drep_distr distr ON distr.hash_id = dh.id AND distr.epoch_no = current.epoch_no
+)
+SELECT *, count(*) OVER () AS total
+FROM dreps
+"#;
+
+// Proposal with its anchor, off-chain metadata and treasury withdrawals;
+// callers add the WHERE, ORDER BY and LIMIT clauses
+const ACTIONS_SQL: &str = r#"
+SELECT
+    gap.id,
+    encode(tx.hash, 'hex') AS tx_hash,
+    gap.index::integer AS proposal_index,
+    gap.deposit::text AS deposit,
+    sa.view AS return_address,
+    gap.type::text AS action_type,
+    gap.description,
+    b.epoch_no::integer AS proposed_epoch,
+    extract(epoch FROM b.time)::bigint AS block_time,
+    gap.expiration::integer AS expiration,
+    gap.ratified_epoch::integer AS ratified_epoch,
+    gap.enacted_epoch::integer AS enacted_epoch,
+    gap.dropped_epoch::integer AS dropped_epoch,
+    gap.expired_epoch::integer AS expired_epoch,
+    va.url AS meta_url,
+    encode(va.data_hash, 'hex') AS meta_hash,
+    ocvd.json AS meta_json,
+    ocvd.language AS meta_language,
+    ocvd.comment AS meta_comment,
+    ocvd.is_valid AS meta_is_valid,
+    tw.amount AS withdrawal_amount,
+    tw.address AS withdrawal_address,
+    count(*) OVER () AS total
+FROM gov_action_proposal gap
+JOIN tx ON tx.id = gap.tx_id
+JOIN block b ON b.id = tx.block_id
+LEFT JOIN stake_address sa ON sa.id = gap.return_address
+LEFT JOIN voting_anchor va ON va.id = gap.voting_anchor_id
+LEFT JOIN LATERAL (
+    SELECT o.json, o.language, o.comment, o.is_valid
+    FROM off_chain_vote_data o
+    WHERE o.voting_anchor_id = va.id
+    ORDER BY o.id DESC
+    LIMIT 1
+) ocvd ON true
+LEFT JOIN LATERAL (
+    SELECT sum(w.amount)::text AS amount, min(wsa.view) AS address
+    FROM treasury_withdrawal w
+    JOIN stake_address wsa ON wsa.id = w.stake_address_id
+    WHERE w.gov_action_proposal_id = gap.id
+    HAVING count(*) > 0
+) tw ON true
+"#;
+
+impl DbSyncProvider {
+    /// Opens a pool of up to `pool_size` connections to the database at `url`
+    /// (a `postgres://` URL or libpq key/value string). Connections are made
+    /// lazily, so an unreachable database surfaces as request errors.
+    pub fn connect(url: &str, pool_size: usize) -> Result<Self, anyhow::Error> {
+        let mut config: tokio_postgres::Config = url
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid DBSYNC_URL: {}", e))?;
+        // Keep a slow query from holding a connection longer than an HTTP
+        // provider would be waited on. Options given in the URL (a
+        // search_path, say) are kept.
+        let options = match config.get_options() {
+            Some(options) => format!("{} -c statement_timeout=30000", options),
+            None => "-c statement_timeout=30000".to_string(),
+        };
+        config.options(&options);
+        let manager = Manager::from_config(
+            config,
+            NoTls,
+            ManagerConfig {
+                recycling_method: RecyclingMethod::Fast,
+            },
+        );
+        let pool = Pool::builder(manager).max_size(pool_size).build()?;
+        Ok(Self { pool })
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, ProviderError> {
+        let client = self.pool.get().await?;
+        let statement = client.prepare_cached(sql).await?;
+        let result = client.query(&statement, params).await;
+
+        if let Err(error) = &result {
+            tracing::error!("db-sync query error: {}", error);
+        }
+        Ok(result?)
+    }
+
+    async fn query_opt(
+        &self,
+        sql: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, ProviderError> {
+        Ok(self.query(sql, params).await?.into_iter().next())
+    }
+
+    fn map_drep(row: &Row) -> Result<DRep, ProviderError> {
+        let view: String = get(row, "view")?;
+        let has_script: Option<bool> = get(row, "has_script")?;
+        let status: String = get(row, "status")?;
+        let anchor_url: Option<String> = get(row, "anchor_url")?;
+        let anchor_hash: Option<String> = get(row, "anchor_hash")?;
+        let metadata: Option<Value> = get(row, "metadata")?;
+        let voting_power: Option<String> = get(row, "voting_power")?;
+        let given_name: Option<String> = get(row, "given_name")?;
+
+        Ok(DRep {
+            drep_id: normalize_to_cip129(&view).unwrap_or_else(|_| view.clone()),
+            drep_hash: get(row, "raw")?,
+            hex: get(row, "raw")?,
+            view: Some(view),
+            url: anchor_url.clone(),
+            metadata: metadata.map(|extra| DRepMetadata { extra }),
+            anchor: anchor_url
+                .zip(anchor_hash)
+                .map(|(url, data_hash)| DRepAnchor { url, data_hash }),
+            voting_power: voting_power.clone(),
+            voting_power_active: None,
+            amount: voting_power,
+            active: Some(status == "active"),
+            retired: Some(status == "retired"),
+            expired: Some(status == "inactive"),
+            status: Some(status),
+            active_epoch: None,
+            last_active_epoch: epoch(row, "active_until")?,
+            has_script,
+            registration_tx_hash: get(row, "registration_tx_hash")?,
+            registration_epoch: epoch(row, "registration_epoch")?,
+            delegator_count: None,
+            vote_count: None,
+            last_vote_epoch: None,
+            has_profile: Some(given_name.is_some()),
+            given_name,
+            objectives: get(row, "objectives")?,
+            motivations: get(row, "motivations")?,
+            qualifications: get(row, "qualifications")?,
+            votes_last_year: None,
+            identity_references: None,
+            link_references: None,
+            image_url: get(row, "image_url")?,
+            image_hash: get(row, "image_hash")?,
+            latest_registration_date: None,
+            latest_tx_hash: get(row, "latest_tx_hash")?,
+            deposit: get(row, "deposit")?,
+            metadata_error: None,
+            payment_address: get(row, "payment_address")?,
+            is_script_based: has_script,
+        })
+    }
+
+    fn map_governance_action(row: &Row) -> Result<GovernanceAction, ProviderError> {
+        let tx_hash: String = get(row, "tx_hash")?;
+        let index = epoch(row, "proposal_index")?.unwrap_or(0);
+        let proposal_id = to_cip129_proposal_id(&tx_hash, index).ok();
+
+        let action_type = match get::<String>(row, "action_type")?.as_str() {
+            "ParameterChange" => "parameter_change",
+            "HardForkInitiation" => "hard_fork_initiation",
+            "TreasuryWithdrawals" => "treasury_withdrawals",
+            "NoConfidence" => "no_confidence",
+            "NewCommittee" => "new_committee",
+            "NewConstitution" => "new_constitution",
+            _ => "info",
+        }
+        .to_string();
+
+        let proposed_epoch = epoch(row, "proposed_epoch")?;
+        let ratified_epoch = epoch(row, "ratified_epoch")?;
+        let enacted_epoch = epoch(row, "enacted_epoch")?;
+        let dropped_epoch = epoch(row, "dropped_epoch")?;
+        let expired_epoch = epoch(row, "expired_epoch")?;
+        let expiration = epoch(row, "expiration")?;
+
+        let status = if enacted_epoch.is_some() {
+            "enacted"
+        } else if ratified_epoch.is_some() {
+            "ratified"
+        } else if dropped_epoch.is_some() || expired_epoch.is_some() {
+            "expired"
+        } else {
+            "voting"
+        };
+
+        let description: Option<Value> = get(row, "description")?;
+        let meta_json: Option<Value> = get(row, "meta_json")?;
+        let withdrawal_amount: Option<String> = get(row, "withdrawal_amount")?;
+
+        Ok(GovernanceAction {
+            action_id: proposal_id
+                .clone()
+                .unwrap_or_else(|| format_proposal_id(&tx_hash, index)),
+            proposal_id,
+            proposal_tx_hash: Some(tx_hash.clone()),
+            proposal_index: Some(index),
+            cert_index: Some(index),
+            tx_hash,
+            deposit: get(row, "deposit")?,
+            reward_account: None,
+            return_address: get(row, "return_address")?,
+            r#type: action_type,
+            description: description.as_ref().and_then(|value| {
+                value
+                    .as_str()
+                    .or_else(|| value.get("description").and_then(|v| v.as_str()))
+                    .map(|s| s.to_string())
+            }),
+            status: Some(status.to_string()),
+            proposed_epoch,
+            voting_epoch: proposed_epoch,
+            proposed_epoch_start_time: None,
+            voting_epoch_start_time: None,
+            ratification_epoch_start_time: None,
+            enactment_epoch_start_time: None,
+            expiry_epoch_start_time: None,
+            expiration_epoch_start_time: None,
+            dropped_epoch_start_time: None,
+            ratification_epoch: ratified_epoch,
+            ratified_epoch,
+            enactment_epoch: enacted_epoch,
+            expiry_epoch: expired_epoch.or(expiration),
+            expiration,
+            dropped_epoch,
+            meta_url: get(row, "meta_url")?,
+            meta_hash: get(row, "meta_hash")?,
+            meta_json: meta_json.clone(),
+            meta_language: get(row, "meta_language")?,
+            meta_comment: get(row, "meta_comment")?,
+            meta_is_valid: get(row, "meta_is_valid")?,
+            metadata_checks: None,
+            withdrawal: withdrawal_amount.map(|amount| Withdrawal {
+                amount,
+                address: get(row, "withdrawal_address").ok().flatten(),
+            }),
+            param_proposal: None,
+            block_time: get::<Option<i64>>(row, "block_time")?.map(|t| t as u64),
+            metadata: meta_json,
+        })
+    }
+
+    fn map_vote_record(row: &Row) -> Result<Option<ActionVoteRecord>, ProviderError> {
+        let (voter_type, voter_identifier) = match get::<String>(row, "voter_role")?.as_str() {
+            "DRep" => {
+                let view: Option<String> = get(row, "drep_view")?;
+                (
+                    "drep",
+                    view.map(|view| normalize_to_cip129(&view).unwrap_or(view)),
+                )
+            }
+            "SPO" => ("spo", get(row, "pool_view")?),
+            "ConstitutionalCommittee" => ("cc", get(row, "committee_hot_key")?),
+            _ => return Ok(None),
+        };
+        let Some(voter_identifier) = voter_identifier else {
+            return Ok(None);
+        };
+
+        Ok(Some(ActionVoteRecord {
+            voter_identifier,
+            voter_type: voter_type.to_string(),
+            vote: get::<String>(row, "vote").map(|vote| VoteChoice::parse(&vote))?,
+            voting_power: get(row, "voting_power")?,
+            tx_hash: get(row, "tx_hash")?,
+            cert_index: epoch(row, "cert_index")?,
+            block_time: get::<Option<i64>>(row, "block_time")?.map(|t| t as u64),
+        }))
+    }
+
+    /// Row id of a proposal and the epoch whose stake distribution weighs its
+    /// votes: the current one while voting is open, otherwise the epoch the
+    /// action left the ballot.
+    async fn find_proposal(&self, id: &str) -> Result<Option<(i64, i32)>, ProviderError> {
+        let Some((tx_hash, index)) = resolve_proposal_id(id) else {
+            return Ok(None);
+        };
+        let row = self
+            .query_opt(
+                "SELECT gap.id,
+                        least(
+                            coalesce(gap.ratified_epoch, gap.expired_epoch, gap.dropped_epoch),
+                            (SELECT max(epoch_no) FROM drep_distr)
+                        )::integer AS closed_epoch,
+                        (SELECT max(epoch_no) FROM drep_distr)::integer AS current_epoch
+                 FROM gov_action_proposal gap
+                 JOIN tx ON tx.id = gap.tx_id
+                 WHERE tx.hash = decode($1, 'hex') AND gap.index = $2::bigint",
+                &[&tx_hash, &i64::from(index)],
+            )
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let closed: Option<i32> = get(&row, "closed_epoch")?;
+        let current: Option<i32> = get(&row, "current_epoch")?;
+        Ok(Some((get(&row, "id")?, closed.or(current).unwrap_or(0))))
+    }
+
+    /// Latest vote of each voter on a proposal, weighed by the voter's stake
+    /// in `power_epoch`.
+    async fn proposal_votes(
+        &self,
+        proposal: i64,
+        power_epoch: i32,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        let rows = self
+            .query(
+                "SELECT DISTINCT ON (vp.voter_role, vp.drep_voter, vp.pool_voter, vp.committee_voter)
+                        vp.voter_role::text AS voter_role,
+                        vp.vote::text AS vote,
+                        dh.view AS drep_view,
+                        ph.view AS pool_view,
+                        encode(ch.raw, 'hex') AS committee_hot_key,
+                        encode(tx.hash, 'hex') AS tx_hash,
+                        vp.index::integer AS cert_index,
+                        extract(epoch FROM b.time)::bigint AS block_time,
+                        CASE vp.voter_role
+                            WHEN 'DRep' THEN (
+                                SELECT dd.amount FROM drep_distr dd
+                                WHERE dd.hash_id = vp.drep_voter AND dd.epoch_no = $2::integer
+                            )
+                            WHEN 'SPO' THEN (
+                                SELECT sum(es.amount) FROM epoch_stake es
+                                WHERE es.pool_id = vp.pool_voter AND es.epoch_no = $2::integer
+                            )
+                        END::text AS voting_power
+                 FROM voting_procedure vp
+                 JOIN tx ON tx.id = vp.tx_id
+                 JOIN block b ON b.id = tx.block_id
+                 LEFT JOIN drep_hash dh ON dh.id = vp.drep_voter
+                 LEFT JOIN pool_hash ph ON ph.id = vp.pool_voter
+                 LEFT JOIN committee_hash ch ON ch.id = vp.committee_voter
+                 WHERE vp.gov_action_proposal_id = $1::bigint
+                 ORDER BY vp.voter_role, vp.drep_voter, vp.pool_voter, vp.committee_voter,
+                          vp.id DESC",
+                &[&proposal, &power_epoch],
+            )
+            .await?;
+
+        let mut records = Vec::with_capacity(rows.len());
+        for row in &rows {
+            if let Some(record) = Self::map_vote_record(row)? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Stake of the `drep_always_abstain` and `drep_always_no_confidence`
+    /// options in `epoch`.
+    async fn predefined_drep_power(&self, epoch: i32) -> Result<(u128, u128), ProviderError> {
+        let rows = self
+            .query(
+                "SELECT dh.view, dd.amount::text AS amount
+                 FROM drep_distr dd
+                 JOIN drep_hash dh ON dh.id = dd.hash_id
+                 WHERE dd.epoch_no = $1::integer
+                   AND dh.view IN ('drep_always_abstain', 'drep_always_no_confidence')",
+                &[&epoch],
+            )
+            .await?;
+
+        let mut abstain = 0;
+        let mut no_confidence = 0;
+        for row in &rows {
+            let amount = parse_lovelace(get(row, "amount")?);
+            match get::<String>(row, "view")?.as_str() {
+                "drep_always_abstain" => abstain = amount,
+                _ => no_confidence = amount,
+            }
+        }
+        Ok((abstain, no_confidence))
+    }
+
+    /// Network name db-sync recorded when it started syncing.
+    pub async fn get_network_name(&self) -> Result<Option<String>, ProviderError> {
+        let row = self
+            .query_opt(
+                "SELECT network_name FROM meta ORDER BY id DESC LIMIT 1",
+                &[],
+            )
+            .await?;
+        Ok(match row {
+            Some(row) => get(&row, "network_name")?,
+            None => None,
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for DbSyncProvider {
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
+        let page = query.normalized_page();
+        let offset = i64::from(page - 1) * i64::from(query.count);
+        let statuses = query.normalized_statuses();
+        let search = query
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        // A DRep id in either CIP-105 or CIP-129 form matches on its hash
+        let search_raw = search.as_deref().and_then(|s| drep_raw_hex(s).ok());
+
+        let sql = format!(
+            "{}
+             WHERE (cardinality($1::text[]) = 0
+                    OR status = ANY($1::text[])
+                    OR ('script' = ANY($1::text[]) AND has_script))
+               AND ($2::text IS NULL
+                    OR raw = $3::text
+                    OR view ILIKE '%' || $2::text || '%'
+                    OR given_name ILIKE '%' || $2::text || '%')
+             ORDER BY {}
+             LIMIT $4::bigint OFFSET $5::bigint",
+            DREPS_SQL,
+            drep_order(query)
+        );
+        let rows = self
+            .query(
+                &sql,
+                &[
+                    &statuses,
+                    &search,
+                    &search_raw,
+                    &i64::from(query.count),
+                    &offset,
+                ],
+            )
+            .await?;
+
+        let total = total(&rows)?;
+        let dreps = rows
+            .iter()
+            .map(Self::map_drep)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DRepsPage {
+            has_more: offset as u64 + (dreps.len() as u64) < total.unwrap_or(0),
+            dreps,
+            total,
+        })
+    }
+
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        // The predefined voting options never register
+        if is_special_system_drep(id) {
+            return Ok(None);
+        }
+        let raw = drep_raw_hex(id)?;
+        let sql = format!("{} WHERE raw = $1::text LIMIT 1", DREPS_SQL);
+        match self.query_opt(&sql, &[&raw]).await? {
+            Some(row) => Ok(Some(Self::map_drep(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        let raw = drep_raw_hex(id)?;
+        let rows = self
+            .query(
+                "WITH target AS (
+                     SELECT id FROM drep_hash WHERE raw = decode($1, 'hex')
+                 )
+                 SELECT sa.view AS address,
+                        coalesce(es.amount, 0)::text AS amount
+                 FROM stake_address sa
+                 JOIN LATERAL (
+                     SELECT dv.drep_hash_id FROM delegation_vote dv
+                     WHERE dv.addr_id = sa.id
+                     ORDER BY dv.tx_id DESC, dv.cert_index DESC
+                     LIMIT 1
+                 ) latest ON latest.drep_hash_id IN (SELECT id FROM target)
+                 LEFT JOIN epoch_stake es
+                     ON es.addr_id = sa.id
+                    AND es.epoch_no = (SELECT max(epoch_no) FROM epoch_stake)
+                 WHERE sa.id IN (
+                     SELECT dv.addr_id FROM delegation_vote dv
+                     WHERE dv.drep_hash_id IN (SELECT id FROM target)
+                 )
+                 ORDER BY es.amount DESC NULLS LAST",
+                &[&raw],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DRepDelegator {
+                    address: get(row, "address")?,
+                    amount: get(row, "amount")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_drep_voting_history(
+        &self,
+        id: &str,
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        let raw = drep_raw_hex(id)?;
+        let rows = self
+            .query(
+                "SELECT encode(vote_tx.hash, 'hex') AS tx_hash,
+                        vp.index::integer AS cert_index,
+                        encode(prop_tx.hash, 'hex') AS proposal_tx_hash,
+                        gap.index::integer AS proposal_index,
+                        vp.vote::text AS vote,
+                        b.epoch_no::integer AS epoch
+                 FROM voting_procedure vp
+                 JOIN drep_hash dh ON dh.id = vp.drep_voter
+                 JOIN gov_action_proposal gap ON gap.id = vp.gov_action_proposal_id
+                 JOIN tx prop_tx ON prop_tx.id = gap.tx_id
+                 JOIN tx vote_tx ON vote_tx.id = vp.tx_id
+                 JOIN block b ON b.id = vote_tx.block_id
+                 WHERE dh.raw = decode($1, 'hex')
+                 ORDER BY vp.id DESC",
+                &[&raw],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let proposal_tx_hash: String = get(row, "proposal_tx_hash")?;
+                let proposal_index = epoch(row, "proposal_index")?.unwrap_or(0);
+                let proposal_id = to_cip129_proposal_id(&proposal_tx_hash, proposal_index).ok();
+                Ok(DRepVotingHistory {
+                    tx_hash: get(row, "tx_hash")?,
+                    cert_index: epoch(row, "cert_index")?,
+                    action_id: proposal_id.clone(),
+                    proposal_id,
+                    proposal_tx_hash: Some(proposal_tx_hash),
+                    proposal_cert_index: Some(proposal_index),
+                    vote: get::<String>(row, "vote")?.to_lowercase(),
+                    voting_power: None,
+                    epoch: epoch(row, "epoch")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_governance_actions_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<ActionsPage, ProviderError> {
+        let offset = i64::from(page.max(1) - 1) * i64::from(count);
+        let sql = format!(
+            "{} ORDER BY gap.id DESC LIMIT $1::bigint OFFSET $2::bigint",
+            ACTIONS_SQL
+        );
+        let rows = self.query(&sql, &[&i64::from(count), &offset]).await?;
+
+        let total = total(&rows)?;
+        let actions = rows
+            .iter()
+            .map(Self::map_governance_action)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ActionsPage {
+            has_more: offset as u64 + (actions.len() as u64) < total.unwrap_or(0),
+            actions,
+            total,
+        })
+    }
+
+    async fn get_governance_action(
+        &self,
+        id: &str,
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
+        let Some((tx_hash, index)) = resolve_proposal_id(id) else {
+            return Ok(None);
+        };
+        let sql = format!(
+            "{} WHERE tx.hash = decode($1, 'hex') AND gap.index = $2::bigint",
+            ACTIONS_SQL
+        );
+        match self.query_opt(&sql, &[&tx_hash, &i64::from(index)]).await? {
+            Some(row) => Ok(Some(Self::map_governance_action(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_action_voting_results(
+        &self,
+        id: &str,
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
+        let Some((proposal, power_epoch)) = self.find_proposal(id).await? else {
+            return Ok(ActionVotingBreakdown {
+                total_voting_power: "0".to_string(),
+                ..Default::default()
+            });
+        };
+        let records = self.proposal_votes(proposal, power_epoch).await?;
+        let (always_abstain, always_no_confidence) =
+            self.predefined_drep_power(power_epoch).await?;
+
+        let mut drep = Tally::default();
+        let mut pool = Tally::default();
+        let mut committee = Tally::default();
+        for record in &records {
+            let tally = match record.voter_type.as_str() {
+                "drep" => &mut drep,
+                "spo" => &mut pool,
+                _ => &mut committee,
+            };
+            tally.add(
+                record.vote.as_ref(),
+                parse_lovelace(record.voting_power.clone()),
+            );
+        }
+
+        let summary = ProposalVotingSummary {
+            epoch_no: Some(power_epoch as u32),
+            drep_yes_votes_cast: Some(drep.yes_votes),
+            drep_active_yes_vote_power: Some(drep.yes_power.to_string()),
+            drep_yes_vote_power: Some(drep.yes_power.to_string()),
+            drep_no_votes_cast: Some(drep.no_votes),
+            drep_active_no_vote_power: Some(drep.no_power.to_string()),
+            drep_no_vote_power: Some(drep.no_power.to_string()),
+            drep_abstain_votes_cast: Some(drep.abstain_votes),
+            drep_active_abstain_vote_power: Some(drep.abstain_power.to_string()),
+            drep_always_no_confidence_vote_power: Some(always_no_confidence.to_string()),
+            drep_always_abstain_vote_power: Some(always_abstain.to_string()),
+            pool_yes_votes_cast: Some(pool.yes_votes),
+            pool_active_yes_vote_power: Some(pool.yes_power.to_string()),
+            pool_yes_vote_power: Some(pool.yes_power.to_string()),
+            pool_no_votes_cast: Some(pool.no_votes),
+            pool_active_no_vote_power: Some(pool.no_power.to_string()),
+            pool_no_vote_power: Some(pool.no_power.to_string()),
+            pool_abstain_votes_cast: Some(pool.abstain_votes),
+            pool_active_abstain_vote_power: Some(pool.abstain_power.to_string()),
+            committee_yes_votes_cast: Some(committee.yes_votes),
+            committee_no_votes_cast: Some(committee.no_votes),
+            committee_abstain_votes_cast: Some(committee.abstain_votes),
+            ..Default::default()
+        };
+
+        // Always-abstain stake counts as abstaining, as in the Koios summary
+        drep.abstain_power += always_abstain;
+        let total_power = drep.yes_power
+            + drep.no_power
+            + drep.abstain_power
+            + pool.yes_power
+            + pool.no_power
+            + pool.abstain_power;
+
+        Ok(ActionVotingBreakdown {
+            drep_votes: drep.counts(true),
+            spo_votes: pool.counts(true),
+            cc_votes: committee.counts(false),
+            total_voting_power: total_power.to_string(),
+            summary: Some(summary),
+            vote_timeline: vote_timeline(&records),
+        })
+    }
+
+    async fn get_drep_metadata(&self, id: &str) -> Result<Option<Value>, ProviderError> {
+        Ok(self
+            .get_drep(id)
+            .await?
+            .and_then(|drep| drep.metadata)
+            .map(|metadata| metadata.extra))
+    }
+
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
+        let row = self
+            .query_opt(
+                "SELECT count(*) AS dreps
+                 FROM drep_distr dd
+                 JOIN drep_hash dh ON dh.id = dd.hash_id
+                 WHERE dd.epoch_no = (SELECT max(epoch_no) FROM drep_distr)
+                   AND dh.raw IS NOT NULL
+                   AND (dd.active_until IS NULL OR dd.active_until >= dd.epoch_no)",
+                &[],
+            )
+            .await?;
+        Ok(match row {
+            Some(row) => Some(get::<i64>(&row, "dreps")? as u32),
+            None => None,
+        })
+    }
+
+    async fn get_stake_delegation(
+        &self,
+        stake_address: &str,
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
+        let row = self
+            .query_opt(
+                "SELECT
+                     (SELECT ph.view FROM delegation d
+                      JOIN pool_hash ph ON ph.id = d.pool_hash_id
+                      WHERE d.addr_id = sa.id
+                      ORDER BY d.tx_id DESC, d.cert_index DESC
+                      LIMIT 1) AS delegated_pool,
+                     (SELECT dh.view FROM delegation_vote dv
+                      JOIN drep_hash dh ON dh.id = dv.drep_hash_id
+                      WHERE dv.addr_id = sa.id
+                      ORDER BY dv.tx_id DESC, dv.cert_index DESC
+                      LIMIT 1) AS delegated_drep,
+                     (SELECT coalesce(sum(o.value), 0) FROM tx_out o
+                      LEFT JOIN tx_in i ON i.tx_out_id = o.tx_id AND i.tx_out_index = o.index
+                      WHERE o.stake_address_id = sa.id AND i.id IS NULL)::text AS utxo,
+                     ((SELECT coalesce(sum(r.amount), 0) FROM reward r
+                       WHERE r.addr_id = sa.id
+                         AND r.spendable_epoch <= (SELECT max(no) FROM epoch))
+                      + (SELECT coalesce(sum(r.amount), 0) FROM reward_rest r
+                         WHERE r.addr_id = sa.id
+                           AND r.spendable_epoch <= (SELECT max(no) FROM epoch))
+                      - (SELECT coalesce(sum(w.amount), 0) FROM withdrawal w
+                         WHERE w.addr_id = sa.id))::text AS rewards
+                 FROM stake_address sa
+                 WHERE sa.view = $1",
+                &[&stake_address],
+            )
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let utxo: Option<String> = get(&row, "utxo")?;
+        let rewards: Option<String> = get(&row, "rewards")?;
+        let delegated_drep: Option<String> = get(&row, "delegated_drep")?;
+        let total = parse_lovelace(utxo.clone()) + parse_lovelace(rewards.clone());
+
+        Ok(Some(StakeDelegation {
+            stake_address: stake_address.to_string(),
+            delegated_pool: get(&row, "delegated_pool")?,
+            delegated_drep: delegated_drep.map(|view| normalize_to_cip129(&view).unwrap_or(view)),
+            total_balance: Some(total.to_string()),
+            utxo_balance: utxo,
+            rewards_available: rewards,
+        }))
+    }
+
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<StakePoolPage, ProviderError> {
+        let offset = i64::from(page.max(1) - 1) * i64::from(count);
+        let rows = self
+            .query(
+                "SELECT ph.view,
+                        encode(ph.hash_raw, 'hex') AS hex,
+                        ocpd.ticker_name,
+                        ocpd.json,
+                        pr.retiring_epoch::integer AS retiring_epoch,
+                        count(*) OVER () AS total
+                 FROM pool_hash ph
+                 JOIN LATERAL (
+                     SELECT pu.registered_tx_id FROM pool_update pu
+                     WHERE pu.hash_id = ph.id
+                     ORDER BY pu.registered_tx_id DESC
+                     LIMIT 1
+                 ) pu ON true
+                 LEFT JOIN LATERAL (
+                     SELECT r.retiring_epoch FROM pool_retire r
+                     WHERE r.hash_id = ph.id AND r.announced_tx_id > pu.registered_tx_id
+                     ORDER BY r.announced_tx_id DESC
+                     LIMIT 1
+                 ) pr ON true
+                 LEFT JOIN LATERAL (
+                     SELECT o.ticker_name, o.json FROM off_chain_pool_data o
+                     WHERE o.pool_id = ph.id
+                     ORDER BY o.id DESC
+                     LIMIT 1
+                 ) ocpd ON true
+                 WHERE pr.retiring_epoch IS NULL
+                    OR pr.retiring_epoch > (SELECT max(no) FROM epoch)
+                 ORDER BY ph.id
+                 LIMIT $1::bigint OFFSET $2::bigint",
+                &[&i64::from(count), &offset],
+            )
+            .await?;
+
+        let total = total(&rows)?;
+        let mut pools = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let json: Option<Value> = get(row, "json")?;
+            let field = |name: &str| {
+                json.as_ref()
+                    .and_then(|json| json[name].as_str())
+                    .map(|s| s.to_string())
+            };
+            pools.push(StakePool {
+                pool_id: get(row, "view")?,
+                hex: get(row, "hex")?,
+                ticker: get(row, "ticker_name")?,
+                name: field("name"),
+                description: field("description"),
+                homepage: field("homepage"),
+                retiring_epoch: epoch(row, "retiring_epoch")?,
+            });
+        }
+
+        Ok(StakePoolPage {
+            has_more: offset as u64 + (pools.len() as u64) < total.unwrap_or(0),
+            pools,
+            total,
+        })
+    }
+
+    /// Members of the committee in force: the one installed by the latest
+    /// enacted committee action, or the genesis committee before any.
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        let rows = self
+            .query(
+                "WITH current_committee AS (
+                     SELECT c.id FROM committee c
+                     LEFT JOIN gov_action_proposal gap ON gap.id = c.gov_action_proposal_id
+                     WHERE c.gov_action_proposal_id IS NULL OR gap.enacted_epoch IS NOT NULL
+                     ORDER BY gap.enacted_epoch DESC NULLS LAST, c.id DESC
+                     LIMIT 1
+                 )
+                 SELECT encode(cold.raw, 'hex') AS cold_key,
+                        encode(hot.raw, 'hex') AS hot_key,
+                        cm.expiration_epoch::integer AS expiry_epoch,
+                        CASE
+                            WHEN dereg.tx_id > coalesce(reg.tx_id, 0) THEN 'resigned'
+                            WHEN reg.tx_id IS NOT NULL THEN 'authorized'
+                            ELSE 'not_authorized'
+                        END AS role
+                 FROM committee_member cm
+                 JOIN current_committee cc ON cc.id = cm.committee_id
+                 JOIN committee_hash cold ON cold.id = cm.committee_hash_id
+                 LEFT JOIN LATERAL (
+                     SELECT r.hot_key_id, r.tx_id FROM committee_registration r
+                     WHERE r.cold_key_id = cold.id
+                     ORDER BY r.tx_id DESC, r.cert_index DESC
+                     LIMIT 1
+                 ) reg ON true
+                 LEFT JOIN LATERAL (
+                     SELECT d.tx_id FROM committee_de_registration d
+                     WHERE d.cold_key_id = cold.id
+                     ORDER BY d.tx_id DESC
+                     LIMIT 1
+                 ) dereg ON true
+                 LEFT JOIN committee_hash hot ON hot.id = reg.hot_key_id
+                 ORDER BY cm.id",
+                &[],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let cold_key: Option<String> = get(row, "cold_key")?;
+                Ok(CommitteeMemberInfo {
+                    identifier: cold_key.clone().unwrap_or_default(),
+                    role: get(row, "role")?,
+                    hot_key: get(row, "hot_key")?,
+                    cold_key,
+                    expiry_epoch: epoch(row, "expiry_epoch")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        let row = self
+            .query_opt(
+                "SELECT extract(epoch FROM start_time)::bigint AS start_time
+                 FROM epoch WHERE no = $1::integer",
+                &[&(epoch as i32)],
+            )
+            .await?;
+        Ok(match row {
+            Some(row) => get::<Option<i64>>(&row, "start_time")?.map(|t| t as u64),
+            None => None,
+        })
+    }
+
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        let Some(cert_index) = action.cert_index else {
+            return Ok(Vec::new());
+        };
+        match self
+            .find_proposal(&format_proposal_id(&action.tx_hash, cert_index))
+            .await?
+        {
+            Some((proposal, power_epoch)) => self.proposal_votes(proposal, power_epoch).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn health_check(&self) -> Result<bool, ProviderError> {
+        Ok(self.query_opt("SELECT 1", &[]).await?.is_some())
+    }
+}
+
+/// Vote counts and stake per choice for one voter role.
+#[derive(Default)]
+struct Tally {
+    yes_votes: u32,
+    no_votes: u32,
+    abstain_votes: u32,
+    yes_power: u128,
+    no_power: u128,
+    abstain_power: u128,
+}
+
+impl Tally {
+    fn add(&mut self, vote: Option<&VoteChoice>, power: u128) {
+        match vote {
+            Some(VoteChoice::Yes) => {
+                self.yes_votes += 1;
+                self.yes_power += power;
+            }
+            Some(VoteChoice::No) => {
+                self.no_votes += 1;
+                self.no_power += power;
+            }
+            _ => {
+                self.abstain_votes += 1;
+                self.abstain_power += power;
+            }
+        }
+    }
+
+    // Committee votes carry no stake, so their power is reported as zero
+    fn counts(&self, weighted: bool) -> VoteCounts {
+        let power = |value: u128| {
+            if weighted {
+                value.to_string()
+            } else {
+                "0".to_string()
+            }
+        };
+        VoteCounts {
+            yes: power(self.yes_power),
+            no: power(self.no_power),
+            abstain: power(self.abstain_power),
+            yes_votes_cast: Some(self.yes_votes),
+            no_votes_cast: Some(self.no_votes),
+            abstain_votes_cast: Some(self.abstain_votes),
+        }
+    }
+}
+
+// Cumulative counts and stake per choice at each block time a vote landed
+fn vote_timeline(records: &[ActionVoteRecord]) -> Option<Vec<VoteTimelinePoint>> {
+    let mut grouped: BTreeMap<u64, Tally> = BTreeMap::new();
+    for record in records {
+        let Some(timestamp) = record.block_time.filter(|t| *t > 0) else {
+            continue;
+        };
+        grouped.entry(timestamp).or_default().add(
+            record.vote.as_ref(),
+            parse_lovelace(record.voting_power.clone()),
+        );
+    }
+
+    let mut cumulative = Tally::default();
+    let points: Vec<VoteTimelinePoint> = grouped
+        .into_iter()
+        .map(|(timestamp, tally)| {
+            cumulative.yes_votes += tally.yes_votes;
+            cumulative.no_votes += tally.no_votes;
+            cumulative.abstain_votes += tally.abstain_votes;
+            cumulative.yes_power += tally.yes_power;
+            cumulative.no_power += tally.no_power;
+            cumulative.abstain_power += tally.abstain_power;
+            VoteTimelinePoint {
+                timestamp,
+                yes_votes: cumulative.yes_votes,
+                no_votes: cumulative.no_votes,
+                abstain_votes: cumulative.abstain_votes,
+                yes_power: cumulative.yes_power.to_string(),
+                no_power: cumulative.no_power.to_string(),
+                abstain_power: cumulative.abstain_power.to_string(),
+            }
+        })
+        .collect();
+
+    (!points.is_empty()).then_some(points)
+}
+
+// Sort keys accepted from the API, mapped onto columns of DREPS_SQL so the
+// ORDER BY clause is never built from request input
+fn drep_order(query: &DRepsQuery) -> String {
+    let column = match query
+        .sort
+        .as_deref()
+        .map(|s| s.trim().to_ascii_lowercase().replace('_', ""))
+        .as_deref()
+    {
+        Some("registrationdate") => "registration_tx_id",
+        Some("status") => "status",
+        Some("name") => "given_name",
+        _ => "amount",
+    };
+    let direction = match query.direction.as_deref().map(str::trim) {
+        Some(d) if d.eq_ignore_ascii_case("asc") || d.eq_ignore_ascii_case("ascending") => {
+            "ASC NULLS FIRST"
+        }
+        _ => "DESC NULLS LAST",
+    };
+    format!("{} {}, id", column, direction)
+}
+
+// drep_hash.raw holds the 28-byte credential without the CIP-129 header byte
+fn drep_raw_hex(id: &str) -> Result<String, ProviderError> {
+    convert_to_cip105(id)
+        .and_then(|cip105| decode_drep_id_to_hex(&cip105))
+        .map_err(|e| ProviderError::InvalidInput(e.to_string()))
+}
+
+fn get<'a, T: FromSql<'a>>(row: &'a Row, column: &str) -> Result<T, ProviderError> {
+    row.try_get(column)
+        .map_err(|e| ProviderError::Decode(format!("{}: {}", column, e)))
+}
+
+// Epochs and indexes are selected as `integer`
+fn epoch(row: &Row, column: &str) -> Result<Option<u32>, ProviderError> {
+    Ok(get::<Option<i32>>(row, column)?.map(|value| value as u32))
+}
+
+// Window total selected as `count(*) OVER ()`; an empty page has no rows to carry it
+fn total(rows: &[Row]) -> Result<Option<u64>, ProviderError> {
+    match rows.first() {
+        Some(row) => Ok(Some(get::<i64>(row, "total")? as u64)),
+        None => Ok(Some(0)),
+    }
+}
+
+// Lovelace amounts are selected as text, since they can exceed i64
+fn parse_lovelace(value: Option<String>) -> u128 {
+    value.and_then(|s| s.parse().ok()).unwrap_or(0)
+}
@@ -0,0 +1,238 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Failure of an upstream provider request, typed so callers can tell rate
+/// limits, timeouts and server errors apart.
+#[derive(Debug, Clone, Error)]
+pub enum ProviderError {
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("rate limited{}", retry_after_suffix(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("request timed out")]
+    Timeout,
+    #[error("upstream returned {status}: {message}")]
+    Upstream { status: u16, message: String },
+    #[error("transport error: {0}")]
+    Transport(String),
+    #[error("failed to decode response: {0}")]
+    Decode(String),
+    /// No provider could take the request (circuit open or none configured)
+    #[error("provider unavailable: {0}")]
+    Unavailable(String),
+    /// Replaying fixtures, and the call was never recorded
+    #[error("no recorded response for {0}")]
+    NotRecorded(String),
+}
+
+fn retry_after_suffix(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(delay) => format!(" (retry after {}s)", delay.as_secs()),
+        None => String::new(),
+    }
+}
+
+impl ProviderError {
+    /// Whether repeating the same request may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::RateLimited { .. }
+            | ProviderError::Timeout
+            | ProviderError::Transport(_) => true,
+            ProviderError::Upstream { status, .. } => *status >= 500,
+            ProviderError::NotFound(_)
+            | ProviderError::InvalidInput(_)
+            | ProviderError::Decode(_)
+            | ProviderError::Unavailable(_)
+            | ProviderError::NotRecorded(_) => false,
+        }
+    }
+
+    /// Builds the error for a non-success response, consuming its body.
+    pub async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return ProviderError::RateLimited {
+                retry_after: parse_retry_after(&response),
+            };
+        }
+
+        let message = response.text().await.unwrap_or_default();
+        ProviderError::Upstream {
+            status: status.as_u16(),
+            message,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ProviderError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            ProviderError::Timeout
+        } else if error.is_decode() {
+            ProviderError::Decode(error.to_string())
+        } else {
+            ProviderError::Transport(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ProviderError {
+    fn from(error: serde_json::Error) -> Self {
+        ProviderError::Decode(error.to_string())
+    }
+}
+
+#[cfg(feature = "dbsync")]
+impl From<tokio_postgres::Error> for ProviderError {
+    fn from(error: tokio_postgres::Error) -> Self {
+        use tokio_postgres::error::SqlState;
+
+        match error.code() {
+            Some(code) if *code == SqlState::QUERY_CANCELED => ProviderError::Timeout,
+            // The server rejected the query; report it like an upstream 5xx
+            Some(_) => ProviderError::Upstream {
+                status: 500,
+                message: error.to_string(),
+            },
+            None => ProviderError::Transport(error.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "dbsync")]
+impl From<deadpool_postgres::PoolError> for ProviderError {
+    fn from(error: deadpool_postgres::PoolError) -> Self {
+        match error {
+            deadpool_postgres::PoolError::Timeout(_) => ProviderError::Timeout,
+            deadpool_postgres::PoolError::Backend(error) => error.into(),
+            other => ProviderError::Unavailable(format!("db-sync pool: {}", other)),
+        }
+    }
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, retry_after: Option<&str>) -> Response {
+        let mut builder = http::Response::builder().status(status);
+        if let Some(value) = retry_after {
+            builder = builder.header(RETRY_AFTER, value);
+        }
+        Response::from(builder.body("slow down").unwrap())
+    }
+
+    #[test]
+    fn retry_after_in_seconds() {
+        assert_eq!(
+            parse_retry_after(&response(429, Some("120"))),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after(&response(429, Some(" 0 "))),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn retry_after_as_http_date() {
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(90));
+        let delay = parse_retry_after(&response(429, Some(&date))).unwrap();
+        // The date has whole-second precision
+        assert!(
+            delay <= Duration::from_secs(90) && delay >= Duration::from_secs(88),
+            "{:?}",
+            delay
+        );
+
+        // A date in the past means retry now
+        assert_eq!(
+            parse_retry_after(&response(429, Some("Wed, 21 Oct 2015 07:28:00 GMT"))),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn missing_or_malformed_retry_after() {
+        assert_eq!(parse_retry_after(&response(429, None)), None);
+        assert_eq!(parse_retry_after(&response(429, Some("soon"))), None);
+        assert_eq!(parse_retry_after(&response(429, Some("-5"))), None);
+    }
+
+    #[tokio::test]
+    async fn from_response_types_rate_limits_and_upstream_errors() {
+        match ProviderError::from_response(response(429, Some("7"))).await {
+            ProviderError::RateLimited { retry_after } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(7)))
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+        match ProviderError::from_response(response(503, None)).await {
+            ProviderError::Upstream { status, message } => {
+                assert_eq!(status, 503);
+                assert_eq!(message, "slow down");
+            }
+            other => panic!("expected Upstream, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn retryable_classification() {
+        let retryable = [
+            ProviderError::RateLimited { retry_after: None },
+            ProviderError::RateLimited {
+                retry_after: Some(Duration::from_secs(1)),
+            },
+            ProviderError::Timeout,
+            ProviderError::Transport("connection reset".into()),
+            ProviderError::Upstream {
+                status: 500,
+                message: String::new(),
+            },
+            ProviderError::Upstream {
+                status: 503,
+                message: String::new(),
+            },
+        ];
+        let permanent = [
+            ProviderError::NotFound("drep".into()),
+            ProviderError::InvalidInput("bad id".into()),
+            ProviderError::Decode("eof".into()),
+            ProviderError::Unavailable("circuit open".into()),
+            ProviderError::NotRecorded("koios GET /tip".into()),
+            ProviderError::Upstream {
+                status: 400,
+                message: String::new(),
+            },
+            ProviderError::Upstream {
+                status: 404,
+                message: String::new(),
+            },
+        ];
+
+        for error in retryable {
+            assert!(error.is_retryable(), "{:?}", error);
+        }
+        for error in permanent {
+            assert!(!error.is_retryable(), "{:?}", error);
+        }
+    }
+}
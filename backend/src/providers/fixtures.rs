@@ -0,0 +1,208 @@
+use crate::providers::ProviderError;
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// How the providers are stood in for, from `PROVIDER_FIXTURES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Call the live providers through `RecordingProvider`, which writes every
+    /// call's outcome to the fixtures
+    Record,
+    /// Answer from the fixtures with `ReplayProvider`; nothing goes over the
+    /// network
+    Replay,
+}
+
+impl FixtureMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "record" => Some(Self::Record),
+            "replay" => Some(Self::Replay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixtureConfig {
+    pub mode: FixtureMode,
+    /// Directory holding one `<provider>.jsonl` per provider
+    pub dir: PathBuf,
+}
+
+/// One recorded provider call and its outcome.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Recording {
+    /// The `Provider` (or `GovToolsApi`) method, e.g. `get_drep`
+    call: String,
+    /// Its arguments, as a JSON array
+    args: Value,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+/// What a call returned. Only outcomes that asking again would repeat are
+/// recorded; timeouts, rate limits and server errors are not.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    Ok(Value),
+    NotFound(String),
+    InvalidInput(String),
+}
+
+impl Recording {
+    fn key(&self) -> String {
+        call_key(&self.call, &self.args)
+    }
+}
+
+fn call_key(call: &str, args: &Value) -> String {
+    format!("{} {}", call, args)
+}
+
+/// Recorded calls to one provider, kept in `<dir>/<provider>.jsonl` with one
+/// call per line. Calls are keyed by method name and arguments, so fixtures
+/// hold no URLs or request headers and API keys stay out of them.
+pub struct ProviderFixtures {
+    provider: &'static str,
+    path: PathBuf,
+    recordings: RwLock<BTreeMap<String, Recording>>,
+    // Serializes rewrites of the fixture file
+    write: tokio::sync::Mutex<()>,
+}
+
+impl ProviderFixtures {
+    /// Loads `<dir>/<provider>.jsonl`. Replaying requires the file; recording
+    /// starts one if needed and keeps what it already holds.
+    pub async fn open(
+        config: &FixtureConfig,
+        provider: &'static str,
+    ) -> Result<Arc<Self>, anyhow::Error> {
+        let path = config.dir.join(format!("{}.jsonl", provider));
+        let recordings = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => parse_recordings(&path, &text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if config.mode == FixtureMode::Replay {
+                    tracing::warn!(
+                        "No {} fixtures at {}; every {} call will fail",
+                        provider,
+                        path.display(),
+                        provider
+                    );
+                }
+                BTreeMap::new()
+            }
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+
+        Ok(Arc::new(Self {
+            provider,
+            path,
+            recordings: RwLock::new(recordings),
+            write: tokio::sync::Mutex::new(()),
+        }))
+    }
+
+    /// The recorded outcome of `call` with `args`, or `NotRecorded`.
+    pub fn replay<T: DeserializeOwned>(&self, call: &str, args: Value) -> Result<T, ProviderError> {
+        let key = call_key(call, &args);
+        let outcome = self
+            .recordings
+            .read()
+            .unwrap()
+            .get(&key)
+            .map(|recording| recording.outcome.clone());
+        match outcome {
+            Some(Outcome::Ok(value)) => Ok(serde_json::from_value(value)?),
+            Some(Outcome::NotFound(what)) => Err(ProviderError::NotFound(what)),
+            Some(Outcome::InvalidInput(message)) => Err(ProviderError::InvalidInput(message)),
+            None => {
+                tracing::warn!("No {} fixture for {}", self.provider, key);
+                Err(ProviderError::NotRecorded(format!(
+                    "{} {}",
+                    self.provider, key
+                )))
+            }
+        }
+    }
+
+    /// Saves the outcome of `call` with `args`, when it is one worth keeping.
+    pub async fn record<T: Serialize>(
+        &self,
+        call: &str,
+        args: Value,
+        result: &Result<T, ProviderError>,
+    ) {
+        let outcome = match result {
+            Ok(value) => match serde_json::to_value(value) {
+                Ok(value) => Outcome::Ok(value),
+                Err(e) => {
+                    tracing::warn!("Failed to record {} {}: {}", self.provider, call, e);
+                    return;
+                }
+            },
+            Err(ProviderError::NotFound(what)) => Outcome::NotFound(what.clone()),
+            Err(ProviderError::InvalidInput(message)) => Outcome::InvalidInput(message.clone()),
+            Err(_) => return,
+        };
+        let recording = Recording {
+            call: call.to_string(),
+            args,
+            outcome,
+        };
+
+        let _write = self.write.lock().await;
+        let lines = {
+            let mut recordings = self.recordings.write().unwrap();
+            let key = recording.key();
+            if recordings.get(&key) == Some(&recording) {
+                return;
+            }
+            recordings.insert(key, recording);
+            recordings
+                .values()
+                .filter_map(|recording| serde_json::to_string(recording).ok())
+                .collect::<Vec<_>>()
+        };
+        if let Err(e) = write_lines(&self.path, &lines).await {
+            tracing::warn!(
+                "Failed to write {} fixtures to {}: {:#}",
+                self.provider,
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn parse_recordings(path: &Path, text: &str) -> Result<BTreeMap<String, Recording>, anyhow::Error> {
+    let mut recordings = BTreeMap::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let recording: Recording = serde_json::from_str(line)
+            .with_context(|| format!("{}:{}", path.display(), number + 1))?;
+        recordings.insert(recording.key(), recording);
+    }
+    Ok(recordings)
+}
+
+// Sorted by call and rewritten whole, so re-recording gives a small diff
+async fn write_lines(path: &Path, lines: &[String]) -> Result<(), anyhow::Error> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    tokio::fs::write(&tmp, contents).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
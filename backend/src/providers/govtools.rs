@@ -1,9 +1,22 @@
 use crate::models::{DRep, DRepAnchor, DRepExternalReference, DRepMetadata, DRepsPage, DRepsQuery};
+use async_trait::async_trait;
 use reqwest::{Client, Url};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue};
 use std::time::Duration;
 
+/// The GovTools lookups the cached router makes. `GovToolsProvider` calls the
+/// API; `RecordingProvider` and `ReplayProvider` stand in for it with fixtures.
+#[async_trait]
+pub trait GovToolsApi: Send + Sync {
+    async fn list_dreps(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error>;
+
+    async fn get_drep_profile(
+        &self,
+        hex_id: &str,
+    ) -> Result<Option<GovToolsEnrichment>, anyhow::Error>;
+}
+
 #[derive(Clone)]
 pub struct GovToolsProvider {
     client: Client,
@@ -82,7 +95,7 @@ struct GovToolsReference {
     uri: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GovToolsEnrichment {
     pub given_name: Option<String>,
     pub objectives: Option<String>,
@@ -403,8 +416,11 @@ impl GovToolsProvider {
 
         Some(result)
     }
+}
 
-    pub async fn list_dreps(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
+#[async_trait]
+impl GovToolsApi for GovToolsProvider {
+    async fn list_dreps(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
         let url = self.build_list_url(query)?;
         let response = self.client.get(url).send().await?;
 
@@ -455,7 +471,7 @@ impl GovToolsProvider {
         })
     }
 
-    pub async fn get_drep_profile(
+    async fn get_drep_profile(
         &self,
         hex_id: &str,
     ) -> Result<Option<GovToolsEnrichment>, anyhow::Error> {
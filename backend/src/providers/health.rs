@@ -0,0 +1,519 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// Number of recent requests used for error rate and latency figures
+const WINDOW_SIZE: usize = 100;
+// Minimum samples before the error rate alone can trip the breaker
+const MIN_SAMPLES_FOR_RATE: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// Error rate (0.0 - 1.0) over the rolling window that opens the circuit
+    pub error_rate_threshold: f64,
+    /// How long an open circuit waits before letting a probe request through
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            error_rate_threshold: 0.5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    ok: bool,
+    latency: Duration,
+}
+
+#[derive(Debug)]
+struct HealthState {
+    samples: VecDeque<Sample>,
+    consecutive_failures: u32,
+    circuit: CircuitState,
+    opened_at: Option<Instant>,
+    // Provider-requested open duration (Retry-After) overriding the cooldown
+    open_for: Option<Duration>,
+    probe_started: Option<Instant>,
+    // The half-open probe in flight; only its outcome closes or re-opens the circuit
+    probe: Option<u64>,
+    next_probe: u64,
+    open_reason: Option<String>,
+    last_error: Option<String>,
+    total_requests: u64,
+    total_failures: u64,
+    rejected_requests: u64,
+}
+
+impl HealthState {
+    fn error_rate(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let failures = self.samples.iter().filter(|s| !s.ok).count();
+        failures as f64 / self.samples.len() as f64
+    }
+
+    fn push_sample(&mut self, sample: Sample) {
+        if self.samples.len() == WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        let mut latencies: Vec<Duration> = self
+            .samples
+            .iter()
+            .filter(|s| s.ok)
+            .map(|s| s.latency)
+            .collect();
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort();
+        let rank = (percentile.clamp(0.0, 1.0) * (latencies.len() - 1) as f64).round() as usize;
+        latencies.get(rank).copied()
+    }
+
+    fn open_duration(&self, config: &BreakerConfig) -> Duration {
+        self.open_for.unwrap_or(config.cooldown)
+    }
+
+    fn open(&mut self, reason: String) {
+        self.circuit = CircuitState::Open;
+        self.opened_at = Some(Instant::now());
+        self.open_for = None;
+        self.probe_started = None;
+        self.probe = None;
+        self.open_reason = Some(reason);
+    }
+
+    fn start_probe(&mut self, now: Instant) -> Permit {
+        self.next_probe = self.next_probe.wrapping_add(1);
+        self.probe = Some(self.next_probe);
+        self.probe_started = Some(now);
+        Permit {
+            probe: Some(self.next_probe),
+        }
+    }
+
+    fn is_probe(&self, permit: &Permit) -> bool {
+        self.circuit == CircuitState::HalfOpen
+            && permit.probe.is_some()
+            && permit.probe == self.probe
+    }
+}
+
+/// Admission to call a provider, from `ProviderHealth::try_acquire`. The
+/// outcome is reported back with the same permit.
+#[derive(Debug)]
+pub struct Permit {
+    probe: Option<u64>,
+}
+
+/// Rolling health state and circuit breaker for a single upstream provider.
+pub struct ProviderHealth {
+    name: &'static str,
+    config: BreakerConfig,
+    state: Mutex<HealthState>,
+}
+
+impl ProviderHealth {
+    pub fn new(name: &'static str, config: BreakerConfig) -> Self {
+        Self {
+            name,
+            config,
+            state: Mutex::new(HealthState {
+                samples: VecDeque::with_capacity(WINDOW_SIZE),
+                consecutive_failures: 0,
+                circuit: CircuitState::Closed,
+                opened_at: None,
+                open_for: None,
+                probe_started: None,
+                probe: None,
+                next_probe: 0,
+                open_reason: None,
+                last_error: None,
+                total_requests: 0,
+                total_failures: 0,
+                rejected_requests: 0,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns a permit when a request may be sent to this provider right now.
+    ///
+    /// An open circuit rejects requests until the cooldown has elapsed, then lets a
+    /// single probe through (half-open). Only the probe's outcome closes or re-opens
+    /// it; requests admitted before the circuit opened don't.
+    pub fn try_acquire(&self) -> Option<Permit> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        match state.circuit {
+            CircuitState::Closed => Some(Permit { probe: None }),
+            CircuitState::Open => {
+                let open_duration = state.open_duration(&self.config);
+                let cooled_down = state
+                    .opened_at
+                    .map(|opened| now.duration_since(opened) >= open_duration)
+                    .unwrap_or(true);
+                if cooled_down {
+                    tracing::info!("Circuit for {} half-open, probing", self.name);
+                    state.circuit = CircuitState::HalfOpen;
+                    Some(state.start_probe(now))
+                } else {
+                    state.rejected_requests = state.rejected_requests.saturating_add(1);
+                    None
+                }
+            }
+            CircuitState::HalfOpen => {
+                // A probe that never reported back (e.g. cancelled) must not wedge the circuit
+                let probe_lost = state
+                    .probe_started
+                    .map(|started| now.duration_since(started) >= self.config.cooldown)
+                    .unwrap_or(true);
+                if probe_lost {
+                    Some(state.start_probe(now))
+                } else {
+                    state.rejected_requests = state.rejected_requests.saturating_add(1);
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self, permit: &Permit, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.total_requests = state.total_requests.saturating_add(1);
+
+        if state.is_probe(permit) {
+            tracing::info!("Circuit for {} closed after successful probe", self.name);
+            state.circuit = CircuitState::Closed;
+            state.consecutive_failures = 0;
+            state.opened_at = None;
+            state.open_for = None;
+            state.probe_started = None;
+            state.probe = None;
+            state.open_reason = None;
+            // Start from a clean window so old failures don't immediately re-trip it
+            state.samples.clear();
+        } else if state.circuit == CircuitState::Closed {
+            state.consecutive_failures = 0;
+        }
+
+        state.push_sample(Sample { ok: true, latency });
+    }
+
+    pub fn record_failure(&self, permit: &Permit, latency: Duration, error: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.total_requests = state.total_requests.saturating_add(1);
+        state.total_failures = state.total_failures.saturating_add(1);
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.last_error = Some(error.to_string());
+        state.push_sample(Sample { ok: false, latency });
+
+        match state.circuit {
+            CircuitState::HalfOpen if state.is_probe(permit) => {
+                tracing::warn!(
+                    "Probe to {} failed, re-opening circuit: {}",
+                    self.name,
+                    error
+                );
+                state.open(format!("probe failed: {}", error));
+            }
+            CircuitState::Closed => {
+                let error_rate = state.error_rate();
+                if state.consecutive_failures >= self.config.failure_threshold {
+                    let reason = format!("{} consecutive failures", state.consecutive_failures);
+                    tracing::warn!("Opening circuit for {}: {}", self.name, reason);
+                    state.open(reason);
+                } else if state.samples.len() >= MIN_SAMPLES_FOR_RATE
+                    && error_rate >= self.config.error_rate_threshold
+                {
+                    let reason = format!("error rate {:.0}%", error_rate * 100.0);
+                    tracing::warn!("Opening circuit for {}: {}", self.name, reason);
+                    state.open(reason);
+                }
+            }
+            // A straggler from before the circuit opened; only the probe decides
+            CircuitState::HalfOpen | CircuitState::Open => {}
+        }
+    }
+
+    /// Records a rate-limit response carrying Retry-After: the circuit opens
+    /// immediately and stays open for as long as the provider asked.
+    pub fn record_rate_limited(&self, latency: Duration, retry_after: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.total_requests = state.total_requests.saturating_add(1);
+        state.total_failures = state.total_failures.saturating_add(1);
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.last_error = Some("rate limited".to_string());
+        state.push_sample(Sample { ok: false, latency });
+
+        tracing::warn!(
+            "{} rate limited, opening circuit for {}s",
+            self.name,
+            retry_after.as_secs()
+        );
+        state.open(format!("rate limited for {}s", retry_after.as_secs()));
+        state.open_for = Some(retry_after);
+    }
+
+    /// Latency of successful requests at `percentile` (0.0 - 1.0) over the rolling window.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.state.lock().unwrap().latency_percentile(percentile)
+    }
+
+    pub fn snapshot(&self) -> ProviderHealthSnapshot {
+        let state = self.state.lock().unwrap();
+        let successful: Vec<Duration> = state
+            .samples
+            .iter()
+            .filter(|s| s.ok)
+            .map(|s| s.latency)
+            .collect();
+        let avg_latency_ms = if successful.is_empty() {
+            None
+        } else {
+            let total: Duration = successful.iter().sum();
+            Some(total.as_millis() as u64 / successful.len() as u64)
+        };
+        let retry_in_secs = match (state.circuit, state.opened_at) {
+            (CircuitState::Open, Some(opened)) => Some(
+                state
+                    .open_duration(&self.config)
+                    .saturating_sub(opened.elapsed())
+                    .as_secs(),
+            ),
+            _ => None,
+        };
+
+        ProviderHealthSnapshot {
+            name: self.name,
+            circuit: state.circuit,
+            error_rate: state.error_rate(),
+            avg_latency_ms,
+            p95_latency_ms: state.latency_percentile(0.95).map(|d| d.as_millis() as u64),
+            consecutive_failures: state.consecutive_failures,
+            window_size: state.samples.len(),
+            total_requests: state.total_requests,
+            total_failures: state.total_failures,
+            rejected_requests: state.rejected_requests,
+            open_reason: state.open_reason.clone(),
+            retry_in_secs,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealthSnapshot {
+    pub name: &'static str,
+    pub circuit: CircuitState,
+    pub error_rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p95_latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub window_size: usize,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    pub rejected_requests: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::advance;
+
+    const COOLDOWN: Duration = Duration::from_secs(30);
+    const LATENCY: Duration = Duration::from_millis(5);
+
+    fn health() -> ProviderHealth {
+        ProviderHealth::new(
+            "test",
+            BreakerConfig {
+                failure_threshold: 3,
+                error_rate_threshold: 0.5,
+                cooldown: COOLDOWN,
+            },
+        )
+    }
+
+    fn circuit(health: &ProviderHealth) -> CircuitState {
+        health.snapshot().circuit
+    }
+
+    fn fail(health: &ProviderHealth, times: u32) {
+        for _ in 0..times {
+            let permit = health.try_acquire().expect("circuit closed");
+            health.record_failure(&permit, LATENCY, "boom");
+        }
+    }
+
+    async fn open_and_cool_down(health: &ProviderHealth) {
+        fail(health, 3);
+        assert_eq!(circuit(health), CircuitState::Open);
+        advance(COOLDOWN + Duration::from_millis(10)).await;
+    }
+
+    #[test]
+    fn consecutive_failures_open_the_circuit() {
+        let health = health();
+        fail(&health, 2);
+        assert_eq!(circuit(&health), CircuitState::Closed);
+
+        fail(&health, 1);
+        assert_eq!(circuit(&health), CircuitState::Open);
+        assert!(health.try_acquire().is_none());
+        assert_eq!(health.snapshot().rejected_requests, 1);
+    }
+
+    #[test]
+    fn a_success_resets_the_failure_streak() {
+        let health = health();
+        fail(&health, 2);
+        let permit = health.try_acquire().unwrap();
+        health.record_success(&permit, LATENCY);
+        fail(&health, 2);
+        assert_eq!(circuit(&health), CircuitState::Closed);
+    }
+
+    #[test]
+    fn error_rate_opens_the_circuit_once_enough_samples() {
+        let health = health();
+        for _ in 0..MIN_SAMPLES_FOR_RATE / 2 {
+            let permit = health.try_acquire().unwrap();
+            health.record_success(&permit, LATENCY);
+            fail(&health, 1);
+        }
+        assert_eq!(circuit(&health), CircuitState::Open);
+        assert_eq!(
+            health.snapshot().open_reason.as_deref(),
+            Some("error rate 50%")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn open_circuit_rejects_until_cooldown_then_lets_one_probe_through() {
+        let health = health();
+        fail(&health, 3);
+        assert!(health.try_acquire().is_none());
+
+        advance(COOLDOWN + Duration::from_millis(10)).await;
+        let probe = health.try_acquire().expect("probe after cooldown");
+        assert_eq!(circuit(&health), CircuitState::HalfOpen);
+        assert!(health.try_acquire().is_none(), "only one probe at a time");
+
+        health.record_success(&probe, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::Closed);
+        assert_eq!(health.snapshot().consecutive_failures, 0);
+        assert!(health.try_acquire().is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_probe_reopens_the_circuit() {
+        let health = health();
+        open_and_cool_down(&health).await;
+
+        let probe = health.try_acquire().unwrap();
+        health.record_failure(&probe, LATENCY, "still down");
+        assert_eq!(circuit(&health), CircuitState::Open);
+        assert!(health.try_acquire().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn only_the_probe_closes_a_half_open_circuit() {
+        let health = health();
+        // Admitted while closed, finishes after the circuit has opened
+        let straggler = health.try_acquire().unwrap();
+        open_and_cool_down(&health).await;
+        let probe = health.try_acquire().unwrap();
+
+        health.record_success(&straggler, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::HalfOpen);
+        health.record_failure(&straggler, LATENCY, "late");
+        assert_eq!(circuit(&health), CircuitState::HalfOpen);
+
+        health.record_success(&probe, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lost_probe_is_replaced_after_cooldown() {
+        let health = health();
+        open_and_cool_down(&health).await;
+        let lost = health.try_acquire().unwrap();
+
+        advance(COOLDOWN + Duration::from_millis(10)).await;
+        let probe = health.try_acquire().expect("replacement probe");
+        health.record_success(&lost, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::HalfOpen);
+
+        health.record_success(&probe, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_opens_for_retry_after_instead_of_cooldown() {
+        let health = health();
+        let permit = health.try_acquire().unwrap();
+        health.record_rate_limited(LATENCY, COOLDOWN * 3);
+        assert_eq!(circuit(&health), CircuitState::Open);
+
+        advance(COOLDOWN + Duration::from_millis(10)).await;
+        assert!(health.try_acquire().is_none(), "still inside Retry-After");
+
+        advance(COOLDOWN * 2).await;
+        let probe = health.try_acquire().expect("probe after Retry-After");
+        health.record_success(&probe, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::Closed);
+
+        // The rate-limited request's own permit has no say afterwards
+        health.record_failure(&permit, LATENCY, "late");
+        assert_eq!(circuit(&health), CircuitState::Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_reopens_a_half_open_circuit() {
+        let health = health();
+        open_and_cool_down(&health).await;
+        let probe = health.try_acquire().unwrap();
+        health.record_rate_limited(LATENCY, COOLDOWN);
+        assert_eq!(circuit(&health), CircuitState::Open);
+
+        // The probe's late success doesn't close the re-opened circuit
+        health.record_success(&probe, LATENCY);
+        assert_eq!(circuit(&health), CircuitState::Open);
+    }
+}
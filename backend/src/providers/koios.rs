@@ -1,6 +1,10 @@
 use crate::models::*;
+use crate::providers::error::ProviderError;
+use crate::providers::quota::{QuotaSnapshot, QuotaTracker};
+use crate::providers::retry::RetryPolicy;
 use crate::providers::Provider;
 use crate::utils::drep_id::normalize_to_cip129;
+use crate::utils::proposal_id::{resolve_proposal_id, to_cip129_proposal_id};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::Value;
@@ -10,15 +14,45 @@ use std::time::Duration;
 pub struct KoiosProvider {
     client: Client,
     base_url: String,
+    /// Bearer token for the authenticated Koios tiers
+    api_key: Option<String>,
+    retry: RetryPolicy,
+    quota: QuotaTracker,
+}
+
+/// One page of a Koios (PostgREST) list endpoint.
+struct KoiosPage {
+    rows: Vec<Value>,
+    /// Total matching rows, from the `Content-Range` header
+    total: Option<u64>,
 }
 
 impl KoiosProvider {
-    pub fn new(base_url: String) -> Self {
+    pub fn new(base_url: String, api_key: Option<String>, retry: RetryPolicy) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
             .unwrap();
-        Self { client, base_url }
+        let quota = QuotaTracker::new("koios", base_url.clone(), api_key.is_some());
+        Self {
+            client,
+            base_url,
+            api_key,
+            retry,
+            quota,
+        }
+    }
+
+    fn request(&self, method: &str, url: &str) -> reqwest::RequestBuilder {
+        let request = match method {
+            "POST" => self.client.post(url),
+            _ => self.client.get(url),
+        };
+        let request = request.header("Content-Type", "application/json");
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
     }
 
     async fn fetch(
@@ -26,50 +60,133 @@ impl KoiosProvider {
         endpoint: &str,
         method: &str,
         body: Option<Value>,
-    ) -> Result<Option<Value>, anyhow::Error> {
-        let url = format!("{}{}", self.base_url, endpoint);
+    ) -> Result<Option<Value>, ProviderError> {
+        let label = format!("Koios {}", endpoint);
+        let result = self
+            .retry
+            .run(&label, || self.fetch_once(endpoint, method, body.as_ref()))
+            .await;
+
+        if let Err(error) = &result {
+            tracing::error!("Koios API error for {}: {}", endpoint, error);
+        }
+        result
+    }
 
-        let mut request = match method {
-            "POST" => self.client.post(&url),
-            _ => self.client.get(&url),
-        };
+    async fn fetch_once(
+        &self,
+        endpoint: &str,
+        method: &str,
+        body: Option<&Value>,
+    ) -> Result<Option<Value>, ProviderError> {
+        let url = format!("{}{}", self.base_url, endpoint);
 
-        request = request.header("Content-Type", "application/json");
+        let mut request = self.request(method, &url);
 
         if let Some(body) = body {
-            request = request.json(&body);
+            request = request.json(body);
         }
 
         let response = request.send().await?;
+        self.quota.observe(response.headers());
+
+        if response.status() == 404 {
+            return Ok(None);
+        }
 
         if !response.status().is_success() {
-            if response.status() == 429 {
-                tracing::warn!("Koios API rate limited (429): {}", endpoint);
-                return Ok(None);
-            } else if response.status() == 404 {
-                return Ok(None);
-            } else {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                tracing::error!(
-                    "Koios API error: {} {} for {}",
-                    status,
-                    error_text,
-                    endpoint
-                );
-                return Ok(None);
-            }
+            return Err(ProviderError::from_response(response).await);
         }
 
         let json: Value = response.json().await?;
         Ok(Some(json))
     }
 
-    fn map_drep(&self, drep: &Value) -> Result<DRep, anyhow::Error> {
+    /// Fetches `limit` rows starting at `offset` from a list endpoint, asking
+    /// PostgREST for an exact count of the matching rows. The rows are
+    /// selected with a `Range` header, so `endpoint` may carry filters in its
+    /// query string.
+    async fn fetch_page(
+        &self,
+        endpoint: &str,
+        offset: u64,
+        limit: u32,
+    ) -> Result<KoiosPage, ProviderError> {
+        let range = format!("{}-{}", offset, offset + (limit as u64).max(1) - 1);
+        let label = format!("Koios {} (items {})", endpoint, range);
+        let result = self
+            .retry
+            .run(&label, || self.fetch_page_once(endpoint, &range))
+            .await;
+
+        if let Err(error) = &result {
+            tracing::error!(
+                "Koios API error for {} (items {}): {}",
+                endpoint,
+                range,
+                error
+            );
+        }
+        result
+    }
+
+    async fn fetch_page_once(
+        &self,
+        endpoint: &str,
+        range: &str,
+    ) -> Result<KoiosPage, ProviderError> {
+        let url = format!("{}{}", self.base_url, endpoint);
+        let request = self
+            .request("GET", &url)
+            .header("Range-Unit", "items")
+            .header("Range", range)
+            .header("Prefer", "count=exact");
+        let response = request.send().await?;
+        self.quota.observe(response.headers());
+
+        if response.status() == 404 {
+            return Ok(KoiosPage {
+                rows: Vec::new(),
+                total: Some(0),
+            });
+        }
+
+        // A range starting past the last row is 416, with `*/<total>` as its
+        // Content-Range; that is an empty page, not a failure
+        if response.status() == 416 {
+            let total = response
+                .headers()
+                .get("content-range")
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_content_range_total);
+            return Ok(KoiosPage {
+                rows: Vec::new(),
+                total,
+            });
+        }
+
+        // PostgREST answers a ranged request with 206 Partial Content
+        if !response.status().is_success() {
+            return Err(ProviderError::from_response(response).await);
+        }
+
+        let total = response
+            .headers()
+            .get("content-range")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_content_range_total);
+        let rows = match response.json::<Value>().await? {
+            Value::Array(rows) => rows,
+            _ => Vec::new(),
+        };
+        Ok(KoiosPage { rows, total })
+    }
+
+    fn map_drep(&self, drep: &Value) -> Result<DRep, ProviderError> {
         Ok(DRep {
             drep_id: drep["drep_id"]
                 .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Missing drep_id"))?
+                .ok_or_else(|| ProviderError::Decode("Missing drep_id".to_string()))?
                 .to_string(),
             drep_hash: None,
             hex: drep["hex"].as_str().map(|s| s.to_string()),
@@ -115,7 +232,7 @@ impl KoiosProvider {
         })
     }
 
-    fn map_governance_action(&self, proposal: &Value) -> Result<GovernanceAction, anyhow::Error> {
+    fn map_governance_action(&self, proposal: &Value) -> Result<GovernanceAction, ProviderError> {
         let proposal_type = proposal["proposal_type"]
             .as_str()
             .unwrap_or("InfoAction")
@@ -204,13 +321,20 @@ impl KoiosProvider {
             meta_comment: proposal["meta_comment"].as_str().map(|s| s.to_string()),
             meta_is_valid: proposal["meta_is_valid"].as_bool(),
             metadata_checks: None,
-            withdrawal: proposal["withdrawal"].as_object().map(|w| Withdrawal {
-                amount: w["amount"]
-                    .as_str()
-                    .map(|s| s.to_string())
-                    .or_else(|| w["amount"].as_u64().map(|v| v.to_string()))
-                    .unwrap_or_default(),
-                address: w["address"].as_str().map(|s| s.to_string()),
+            withdrawal: proposal["withdrawal"].as_object().map(|w| {
+                // Indexing a map panics on a missing key, so look fields up
+                let field = |name: &str| w.get(name).unwrap_or(&Value::Null);
+                Withdrawal {
+                    amount: field("amount")
+                        .as_str()
+                        .map(|s| s.to_string())
+                        .or_else(|| field("amount").as_u64().map(|v| v.to_string()))
+                        .unwrap_or_default(),
+                    address: field("stake_address")
+                        .as_str()
+                        .or_else(|| field("address").as_str())
+                        .map(|s| s.to_string()),
+                }
             }),
             param_proposal: (!proposal["param_proposal"].is_null())
                 .then(|| proposal["param_proposal"].clone()),
@@ -296,12 +420,15 @@ impl KoiosProvider {
         let vote_choice = vote
             .get("vote")
             .and_then(|value| value.as_str())
-            .and_then(VoteChoice::from_str);
+            .and_then(VoteChoice::parse);
 
         let voting_power = vote
             .get("voting_power")
             .and_then(|v| v.as_str().map(|s| s.to_string()))
-            .or_else(|| vote.get("power").and_then(|v| v.as_str().map(|s| s.to_string())))
+            .or_else(|| {
+                vote.get("power")
+                    .and_then(|v| v.as_str().map(|s| s.to_string()))
+            })
             .or_else(|| {
                 vote.get("voting_power")
                     .and_then(|v| v.as_u64().map(|u| u.to_string()))
@@ -323,14 +450,11 @@ impl KoiosProvider {
             })
             .map(|v| v as u32);
 
-        let block_time = vote
-            .get("block_time")
-            .and_then(|v| v.as_u64())
-            .or_else(|| {
-                vote.get("block_time")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| s.parse::<u64>().ok())
-            });
+        let block_time = vote.get("block_time").and_then(|v| v.as_u64()).or_else(|| {
+            vote.get("block_time")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse::<u64>().ok())
+        });
 
         Some(ActionVoteRecord {
             voter_identifier,
@@ -342,117 +466,46 @@ impl KoiosProvider {
             block_time,
         })
     }
-
-    pub async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, anyhow::Error> {
-        let json = self.fetch("/committee_info", "GET", None).await?;
-        let mut members: Vec<CommitteeMemberInfo> = Vec::new();
-
-        if let Some(Value::Array(entries)) = json {
-            for entry in entries {
-                if let Some(current) = entry.get("committee").and_then(|v| v.as_array()) {
-                    for member in current {
-                        if let Some(mapped) = Self::map_committee_member(member) {
-                            members.push(mapped);
-                        }
-                    }
-                }
-
-                if let Some(future) = entry
-                    .get("future_committee")
-                    .or_else(|| entry.get("next_committee"))
-                    .and_then(|v| v.as_array())
-                {
-                    for member in future {
-                        if let Some(mapped) = Self::map_committee_member(member) {
-                            members.push(mapped);
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(members)
-    }
-
-    pub async fn get_action_vote_records(
-        &self,
-        proposal_id: &str,
-    ) -> Result<Vec<ActionVoteRecord>, anyhow::Error> {
-        let endpoint = format!("/proposal_votes?_proposal_id={}", proposal_id);
-        let json = self.fetch(&endpoint, "GET", None).await?;
-
-        let mut records = Vec::new();
-
-        if let Some(Value::Array(arr)) = json {
-            for vote in arr {
-                if let Some(record) = Self::map_vote_record(&vote) {
-                    records.push(record);
-                }
-            }
-        }
-
-        Ok(records)
-    }
 }
 
 #[async_trait]
 impl Provider for KoiosProvider {
-    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
-        // Koios doesn't support pagination directly, so we fetch all and paginate in memory
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
         let page = query.normalized_page();
         let count = query.count;
-        let limit = page.saturating_mul(count).max(count);
-        let endpoint = format!("/drep_list?limit={}", limit);
-        let json = self.fetch(&endpoint, "GET", None).await?;
-        let _total_returned = json
-            .as_ref()
-            .and_then(|value| value.as_array())
-            .map(|arr| arr.len())
-            .unwrap_or(0);
+        let offset = page_offset(page, count);
+        let result = self.fetch_page("/drep_list", offset, count).await?;
 
-        let mut all_dreps = if let Some(Value::Array(arr)) = json {
-            arr.iter()
-                .filter_map(|drep| self.map_drep(drep).ok())
-                .collect::<Vec<_>>()
-        } else {
-            vec![]
-        };
-
-        let start = ((page - 1) * count) as usize;
-        let end = (start + count as usize).min(all_dreps.len());
-        let dreps = if start < all_dreps.len() {
-            all_dreps.drain(start..end).collect()
-        } else {
-            vec![]
-        };
-        let has_more = end < all_dreps.len() + dreps.len();
+        let has_more = has_more(offset, result.rows.len(), count, result.total);
+        let dreps = result
+            .rows
+            .iter()
+            .filter_map(|drep| self.map_drep(drep).ok())
+            .collect();
 
         Ok(DRepsPage {
             dreps,
             has_more,
-            total: None,
+            total: result.total,
         })
     }
 
-    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, anyhow::Error> {
-        // Koios doesn't have a single DRep endpoint, so we fetch the list and filter
-        let cip129_id = normalize_to_cip129(id)?;
-        let endpoint = "/drep_list";
-        let json = self.fetch(&endpoint, "GET", None).await?;
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        // Koios doesn't have a single DRep endpoint, so we filter the list server-side
+        let cip129_id =
+            normalize_to_cip129(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
+        let endpoint = format!("/drep_list?drep_id=eq.{}", cip129_id);
+        let result = self.fetch_page(&endpoint, 0, 1).await?;
 
-        if let Some(Value::Array(arr)) = json {
-            for drep in arr {
-                if drep["drep_id"].as_str() == Some(&cip129_id) {
-                    return Ok(Some(self.map_drep(&drep)?));
-                }
-            }
+        match result.rows.first() {
+            Some(drep) => Ok(Some(self.map_drep(drep)?)),
+            None => Ok(None),
         }
-
-        Ok(None)
     }
 
-    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, anyhow::Error> {
-        let cip129_id = normalize_to_cip129(id)?;
+    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        let cip129_id =
+            normalize_to_cip129(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
 
         let body = serde_json::json!([{
             "_drep_id": cip129_id
@@ -479,8 +532,9 @@ impl Provider for KoiosProvider {
     async fn get_drep_voting_history(
         &self,
         id: &str,
-    ) -> Result<Vec<DRepVotingHistory>, anyhow::Error> {
-        let cip129_id = normalize_to_cip129(id)?;
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        let cip129_id =
+            normalize_to_cip129(id).map_err(|e| ProviderError::InvalidInput(e.to_string()))?;
 
         let body = serde_json::json!([{
             "_drep_id": cip129_id
@@ -490,18 +544,16 @@ impl Provider for KoiosProvider {
 
         let votes = if let Some(Value::Array(arr)) = json {
             arr.iter()
-                .filter_map(|item| {
-                    Some(DRepVotingHistory {
-                        tx_hash: item["vote_tx_hash"].as_str().map(|s| s.to_string()),
-                        cert_index: None,
-                        proposal_id: item["proposal_id"].as_str().map(|s| s.to_string()),
-                        action_id: item["proposal_id"].as_str().map(|s| s.to_string()),
-                        proposal_tx_hash: item["proposal_tx_hash"].as_str().map(|s| s.to_string()),
-                        proposal_cert_index: item["proposal_index"].as_u64().map(|v| v as u32),
-                        vote: item["vote"].as_str().unwrap_or_default().to_lowercase(),
-                        voting_power: None,
-                        epoch: None,
-                    })
+                .map(|item| DRepVotingHistory {
+                    tx_hash: item["vote_tx_hash"].as_str().map(|s| s.to_string()),
+                    cert_index: None,
+                    proposal_id: item["proposal_id"].as_str().map(|s| s.to_string()),
+                    action_id: item["proposal_id"].as_str().map(|s| s.to_string()),
+                    proposal_tx_hash: item["proposal_tx_hash"].as_str().map(|s| s.to_string()),
+                    proposal_cert_index: item["proposal_index"].as_u64().map(|v| v as u32),
+                    vote: item["vote"].as_str().unwrap_or_default().to_lowercase(),
+                    voting_power: None,
+                    epoch: None,
                 })
                 .collect()
         } else {
@@ -515,58 +567,48 @@ impl Provider for KoiosProvider {
         &self,
         page: u32,
         count: u32,
-    ) -> Result<ActionsPage, anyhow::Error> {
-        let endpoint = format!("/proposal_list?limit={}", (page * count + 1));
-        let json = self.fetch(&endpoint, "GET", None).await?;
-
-        let mut all_actions = if let Some(Value::Array(arr)) = json {
-            arr.iter()
-                .filter_map(|proposal| self.map_governance_action(proposal).ok())
-                .collect::<Vec<_>>()
-        } else {
-            vec![]
-        };
+    ) -> Result<ActionsPage, ProviderError> {
+        let offset = page_offset(page, count);
+        let result = self.fetch_page("/proposal_list", offset, count).await?;
 
-        let has_more = all_actions.len() > (page * count) as usize;
-
-        let start = ((page - 1) * count) as usize;
-        let end = (start + count as usize).min(all_actions.len());
-        let actions = if start < all_actions.len() {
-            all_actions.drain(start..end).collect()
-        } else {
-            vec![]
-        };
+        let has_more = has_more(offset, result.rows.len(), count, result.total);
+        let actions = result
+            .rows
+            .iter()
+            .filter_map(|proposal| self.map_governance_action(proposal).ok())
+            .collect();
 
         Ok(ActionsPage {
             actions,
             has_more,
-            total: None,
+            total: result.total,
         })
     }
 
     async fn get_governance_action(
         &self,
         id: &str,
-    ) -> Result<Option<GovernanceAction>, anyhow::Error> {
-        // Try to find in proposal list
-        let endpoint = "/proposal_list";
-        let json = self.fetch(&endpoint, "GET", None).await?;
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
+        // Koios filters on the CIP-129 id; re-encoding it also keeps anything
+        // that isn't a proposal id out of the filter
+        let Some(proposal_id) = resolve_proposal_id(id)
+            .and_then(|(tx_hash, index)| to_cip129_proposal_id(&tx_hash, index).ok())
+        else {
+            return Ok(None);
+        };
+        let endpoint = format!("/proposal_list?proposal_id=eq.{}", proposal_id);
+        let result = self.fetch_page(&endpoint, 0, 1).await?;
 
-        if let Some(Value::Array(arr)) = json {
-            for proposal in arr {
-                if proposal["proposal_id"].as_str() == Some(id) {
-                    return Ok(Some(self.map_governance_action(&proposal)?));
-                }
-            }
+        match result.rows.first() {
+            Some(proposal) => Ok(Some(self.map_governance_action(proposal)?)),
+            None => Ok(None),
         }
-
-        Ok(None)
     }
 
     async fn get_action_voting_results(
         &self,
         id: &str,
-    ) -> Result<ActionVotingBreakdown, anyhow::Error> {
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
         let endpoint = format!("/proposal_voting_summary?_proposal_id={}", id);
         let json = self.fetch(&endpoint, "GET", None).await?;
 
@@ -845,14 +887,14 @@ impl Provider for KoiosProvider {
         })
     }
 
-    async fn get_drep_metadata(&self, _id: &str) -> Result<Option<Value>, anyhow::Error> {
+    async fn get_drep_metadata(&self, _id: &str) -> Result<Option<Value>, ProviderError> {
         // Koios doesn't have a metadata endpoint for DReps
         Ok(None)
     }
 
-    async fn get_total_active_dreps(&self) -> Result<Option<u32>, anyhow::Error> {
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
         let endpoint = "/drep_epoch_summary";
-        let json = self.fetch(&endpoint, "GET", None).await?;
+        let json = self.fetch(endpoint, "GET", None).await?;
 
         if let Some(Value::Array(arr)) = json {
             if let Some(summary) = arr.first() {
@@ -868,7 +910,7 @@ impl Provider for KoiosProvider {
     async fn get_stake_delegation(
         &self,
         stake_address: &str,
-    ) -> Result<Option<StakeDelegation>, anyhow::Error> {
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
         let body = serde_json::json!({
             "_stake_addresses": [stake_address]
         });
@@ -900,9 +942,407 @@ impl Provider for KoiosProvider {
         Ok(None)
     }
 
-    async fn health_check(&self) -> Result<bool, anyhow::Error> {
-        let endpoint = "/tip";
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<StakePoolPage, ProviderError> {
+        let offset = page_offset(page, count);
+        let result = self
+            .fetch_page("/pool_list?pool_status=neq.retired", offset, count)
+            .await?;
+
+        // Names, descriptions and homepages are only in the per-pool metadata
+        let has_more = has_more(offset, result.rows.len(), count, result.total);
+        let pools = result
+            .rows
+            .iter()
+            .filter_map(|pool| {
+                Some(StakePool {
+                    pool_id: pool["pool_id_bech32"].as_str()?.to_string(),
+                    hex: pool["pool_id_hex"].as_str().map(|s| s.to_string()),
+                    ticker: pool["ticker"].as_str().map(|s| s.to_string()),
+                    name: None,
+                    description: None,
+                    homepage: None,
+                    retiring_epoch: pool["retiring_epoch"].as_u64().map(|v| v as u32),
+                })
+            })
+            .collect();
+
+        Ok(StakePoolPage {
+            pools,
+            has_more,
+            total: result.total,
+        })
+    }
+
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        let json = self.fetch("/committee_info", "GET", None).await?;
+        let mut members: Vec<CommitteeMemberInfo> = Vec::new();
+
+        if let Some(Value::Array(entries)) = json {
+            for entry in entries {
+                if let Some(current) = entry.get("committee").and_then(|v| v.as_array()) {
+                    for member in current {
+                        if let Some(mapped) = Self::map_committee_member(member) {
+                            members.push(mapped);
+                        }
+                    }
+                }
+
+                if let Some(future) = entry
+                    .get("future_committee")
+                    .or_else(|| entry.get("next_committee"))
+                    .and_then(|v| v.as_array())
+                {
+                    for member in future {
+                        if let Some(mapped) = Self::map_committee_member(member) {
+                            members.push(mapped);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(members)
+    }
+
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        let endpoint = format!("/epoch_info?_epoch_no={}&_include_next_epoch=false", epoch);
         let json = self.fetch(&endpoint, "GET", None).await?;
+
+        Ok(json
+            .as_ref()
+            .and_then(|v| v.as_array())
+            .and_then(|a| a.first())
+            .and_then(|info| info["start_time"].as_u64()))
+    }
+
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        let proposal_id = action
+            .proposal_id
+            .as_deref()
+            .unwrap_or(action.action_id.as_str());
+        let endpoint = format!("/proposal_votes?_proposal_id={}", proposal_id);
+        let json = self.fetch(&endpoint, "GET", None).await?;
+
+        let mut records = Vec::new();
+
+        if let Some(Value::Array(arr)) = json {
+            for vote in arr {
+                if let Some(record) = Self::map_vote_record(&vote) {
+                    records.push(record);
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    async fn health_check(&self) -> Result<bool, ProviderError> {
+        let endpoint = "/tip";
+        let json = self.fetch(endpoint, "GET", None).await?;
         Ok(json.is_some())
     }
+
+    fn quota(&self) -> Option<QuotaSnapshot> {
+        Some(self.quota.snapshot())
+    }
+
+    async fn get_tip(&self) -> Result<Option<ChainTip>, ProviderError> {
+        let json = self.fetch("/tip", "GET", None).await?;
+        let Some(tip) = json
+            .as_ref()
+            .and_then(|v| v.as_array())
+            .and_then(|a| a.first())
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(ChainTip {
+            hash: tip["hash"]
+                .as_str()
+                .ok_or_else(|| ProviderError::Decode("Missing tip hash".to_string()))?
+                .to_string(),
+            epoch_no: tip["epoch_no"]
+                .as_u64()
+                .ok_or_else(|| ProviderError::Decode("Missing tip epoch_no".to_string()))?
+                as u32,
+            abs_slot: tip["abs_slot"].as_u64().unwrap_or(0),
+            block_height: tip["block_height"]
+                .as_u64()
+                .or_else(|| tip["block_no"].as_u64())
+                .ok_or_else(|| ProviderError::Decode("Missing tip block_height".to_string()))?,
+            block_time: tip["block_time"].as_u64(),
+        }))
+    }
+
+    /// Protocol magic of the network this endpoint serves.
+    async fn get_network_magic(&self) -> Result<Option<u64>, ProviderError> {
+        let json = self.fetch("/genesis", "GET", None).await?;
+        let Some(genesis) = json
+            .as_ref()
+            .and_then(|v| v.as_array())
+            .and_then(|a| a.first())
+        else {
+            return Ok(None);
+        };
+
+        // Koios reports genesis values as strings
+        Ok(genesis["networkmagic"]
+            .as_str()
+            .and_then(|s| s.parse().ok())
+            .or_else(|| genesis["networkmagic"].as_u64()))
+    }
+
+    /// Votes included in blocks after `block_height`, oldest first.
+    async fn get_votes_since(
+        &self,
+        block_height: u64,
+        limit: u32,
+    ) -> Result<Vec<RecentVote>, ProviderError> {
+        let endpoint = format!(
+            "/vote_list?block_height=gt.{}&order=block_height.asc&limit={}\
+             &select=proposal_id,proposal_tx_hash,voter_role,voter_id,block_height",
+            block_height, limit
+        );
+        let json = self.fetch(&endpoint, "GET", None).await?;
+
+        let mut votes = Vec::new();
+        if let Some(Value::Array(arr)) = json {
+            for vote in arr {
+                let (Some(voter_role), Some(voter_id), Some(block_height)) = (
+                    vote["voter_role"].as_str(),
+                    vote["voter_id"].as_str(),
+                    vote["block_height"].as_u64(),
+                ) else {
+                    continue;
+                };
+                votes.push(RecentVote {
+                    proposal_id: vote["proposal_id"].as_str().map(|s| s.to_string()),
+                    proposal_tx_hash: vote["proposal_tx_hash"].as_str().map(|s| s.to_string()),
+                    voter_role: voter_role.to_string(),
+                    voter_id: voter_id.to_string(),
+                    block_height,
+                });
+            }
+        }
+
+        Ok(votes)
+    }
+}
+
+// Content-Range is `<first>-<last>/<total>`, with `*` for an unknown total
+fn parse_content_range_total(value: &str) -> Option<u64> {
+    value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+// Rows before the 1-based `page`
+fn page_offset(page: u32, count: u32) -> u64 {
+    u64::from(page.max(1) - 1) * u64::from(count)
+}
+
+fn has_more(offset: u64, returned: usize, limit: u32, total: Option<u64>) -> bool {
+    match total {
+        Some(total) => offset + (returned as u64) < total,
+        // Without a count a full page is the only hint that more rows follow
+        None => returned as u32 >= limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::retry::RetryConfig;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::{routing::get, Json, Router};
+    use serde_json::json;
+
+    const TOTAL: u64 = 25;
+
+    // A PostgREST stand-in with TOTAL proposals. It honours the `Range` header,
+    // answering 416 past the last row, and only reports the total when asked
+    // with `Prefer: count=exact`
+    async fn standin() -> KoiosProvider {
+        standin_with_key(None).await
+    }
+
+    async fn standin_with_key(api_key: Option<String>) -> KoiosProvider {
+        let app = Router::new()
+            .route("/proposal_list", get(proposals))
+            .route("/drep_list", get(dreps_without_count))
+            .route("/tip", get(tip));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let retry = RetryPolicy::new(RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        });
+        KoiosProvider::new(url, api_key, retry)
+    }
+
+    // Echoes the Authorization header back as the tip hash, with the quota
+    // headers of an authenticated tier
+    async fn tip(headers: HeaderMap) -> impl IntoResponse {
+        let authorization = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("none");
+        let tip = json!([{
+            "hash": authorization,
+            "epoch_no": 500,
+            "block_height": 1000,
+        }]);
+        (
+            [
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "4990"),
+            ],
+            Json(tip),
+        )
+    }
+
+    // The `<first>-<last>` of an items Range header
+    fn requested_range(headers: &HeaderMap) -> (u64, u64) {
+        assert_eq!(headers["range-unit"], "items");
+        let range = headers["range"].to_str().unwrap();
+        let (first, last) = range.split_once('-').unwrap();
+        (first.parse().unwrap(), last.parse().unwrap())
+    }
+
+    async fn proposals(headers: HeaderMap) -> impl IntoResponse {
+        let (offset, last) = requested_range(&headers);
+        let rows: Vec<Value> = (offset..(last + 1).min(TOTAL))
+            .map(|i| {
+                json!({
+                    "proposal_id": format!("gov_action_{}", i),
+                    "proposal_tx_hash": format!("{:064x}", i),
+                    "proposal_index": 0,
+                })
+            })
+            .collect();
+        if rows.is_empty() {
+            let range = format!("*/{}", TOTAL);
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [("content-range", range)],
+                Json(json!({ "message": "Requested range not satisfiable" })),
+            )
+                .into_response();
+        }
+        let mut response = (StatusCode::PARTIAL_CONTENT, Json(rows.clone())).into_response();
+        if headers.get("prefer").and_then(|v| v.to_str().ok()) == Some("count=exact") {
+            let range = format!("{}-{}/{}", offset, offset + rows.len() as u64 - 1, TOTAL);
+            response
+                .headers_mut()
+                .insert("content-range", range.parse().unwrap());
+        }
+        response
+    }
+
+    async fn dreps_without_count(headers: HeaderMap) -> Json<Value> {
+        let (offset, last) = requested_range(&headers);
+        let rows: Vec<Value> = (offset..=last)
+            .map(|i| json!({ "drep_id": format!("drep{}", i), "registered": true }))
+            .collect();
+        Json(Value::Array(rows))
+    }
+
+    #[test]
+    fn reads_the_total_from_content_range() {
+        assert_eq!(parse_content_range_total("0-9/123"), Some(123));
+        assert_eq!(parse_content_range_total("*/0"), Some(0));
+        assert_eq!(parse_content_range_total("0-9/*"), None);
+        assert_eq!(parse_content_range_total("0-9"), None);
+    }
+
+    #[test]
+    fn offsets_count_whole_pages() {
+        assert_eq!(page_offset(1, 20), 0);
+        assert_eq!(page_offset(4, 20), 60);
+        // Page 0 is read as the first page
+        assert_eq!(page_offset(0, 20), 0);
+    }
+
+    #[test]
+    fn known_totals_decide_has_more() {
+        assert!(has_more(0, 10, 10, Some(25)));
+        assert!(!has_more(10, 10, 10, Some(20)));
+        // The last short page
+        assert!(!has_more(20, 5, 10, Some(25)));
+        // A full page is not enough when the total says it was the last
+        assert!(!has_more(0, 10, 10, Some(10)));
+    }
+
+    #[test]
+    fn unknown_totals_fall_back_to_full_pages() {
+        assert!(has_more(0, 10, 10, None));
+        assert!(!has_more(20, 5, 10, None));
+        assert!(!has_more(0, 0, 10, None));
+    }
+
+    #[tokio::test]
+    async fn pages_through_the_server_side_count() {
+        let koios = standin().await;
+
+        let first = koios.get_governance_actions_page(1, 10).await.unwrap();
+        assert_eq!(first.total, Some(TOTAL));
+        assert!(first.has_more);
+        assert_eq!(first.actions[0].action_id, "gov_action_0");
+
+        let last = koios.get_governance_actions_page(3, 10).await.unwrap();
+        assert_eq!(last.actions.len(), 5);
+        assert_eq!(last.actions[0].action_id, "gov_action_20");
+        assert_eq!(last.total, Some(TOTAL));
+        assert!(!last.has_more);
+    }
+
+    #[tokio::test]
+    async fn pages_past_the_end_are_empty() {
+        let koios = standin().await;
+        // A 416 from PostgREST, which must not reach the router as an error
+        let page = koios.get_governance_actions_page(999, 10).await.unwrap();
+        assert!(page.actions.is_empty());
+        assert_eq!(page.total, Some(TOTAL));
+        assert!(!page.has_more);
+    }
+
+    #[tokio::test]
+    async fn pages_without_content_range_have_no_total() {
+        let koios = standin().await;
+        let page = koios
+            .get_dreps_page(&DRepsQuery {
+                count: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, None);
+        assert_eq!(page.dreps.len(), 10);
+        assert!(page.has_more);
+    }
+
+    #[tokio::test]
+    async fn sends_a_bearer_token_only_with_an_api_key() {
+        let public = standin().await;
+        assert_eq!(public.get_tip().await.unwrap().unwrap().hash, "none");
+        assert!(!public.quota().unwrap().authenticated);
+
+        let authenticated = standin_with_key(Some("koios-key".to_string())).await;
+        let tip = authenticated.get_tip().await.unwrap().unwrap();
+        assert_eq!(tip.hash, "Bearer koios-key");
+
+        let quota = authenticated.quota().unwrap();
+        assert!(quota.authenticated);
+        assert_eq!(quota.requests_today, 1);
+        assert_eq!(quota.limit, Some(5000));
+        assert_eq!(quota.remaining, Some(4990));
+    }
 }
@@ -1,56 +1,124 @@
 pub mod blockfrost;
 pub mod cached_router;
+pub mod consistency;
+#[cfg(feature = "dbsync")]
+pub mod dbsync;
+pub mod error;
+pub mod fixtures;
 pub mod govtools;
+pub mod health;
 pub mod koios;
+pub mod ogmios;
+pub mod quota;
+pub mod recording;
+pub mod replay;
+pub mod retry;
 pub mod router;
+pub mod routing;
+#[cfg(test)]
+pub(crate) mod testing;
 
 pub use blockfrost::BlockfrostProvider;
 pub use cached_router::CachedProviderRouter;
-pub use govtools::{GovToolsEnrichment, GovToolsProvider};
+pub use consistency::{ConsistencyKind, ConsistencyReport};
+#[cfg(feature = "dbsync")]
+pub use dbsync::DbSyncProvider;
+pub use error::ProviderError;
+pub use fixtures::{FixtureConfig, FixtureMode, ProviderFixtures};
+pub use govtools::{GovToolsApi, GovToolsEnrichment, GovToolsProvider};
+pub use health::{BreakerConfig, ProviderHealthSnapshot};
 pub use koios::KoiosProvider;
+pub use ogmios::OgmiosProvider;
+pub use quota::QuotaSnapshot;
+pub use recording::RecordingProvider;
+pub use replay::ReplayProvider;
+pub use retry::{RetryConfig, RetryPolicy};
 pub use router::ProviderRouter;
+pub use routing::RoutingPolicy;
 
 use crate::models::*;
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait Provider: Send + Sync {
-    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error>;
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError>;
 
-    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, anyhow::Error>;
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError>;
 
-    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, anyhow::Error>;
+    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError>;
 
     async fn get_drep_voting_history(
         &self,
         id: &str,
-    ) -> Result<Vec<DRepVotingHistory>, anyhow::Error>;
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError>;
 
     async fn get_governance_actions_page(
         &self,
         page: u32,
         count: u32,
-    ) -> Result<ActionsPage, anyhow::Error>;
+    ) -> Result<ActionsPage, ProviderError>;
 
     async fn get_governance_action(
         &self,
         id: &str,
-    ) -> Result<Option<GovernanceAction>, anyhow::Error>;
+    ) -> Result<Option<GovernanceAction>, ProviderError>;
 
     async fn get_action_voting_results(
         &self,
         id: &str,
-    ) -> Result<ActionVotingBreakdown, anyhow::Error>;
+    ) -> Result<ActionVotingBreakdown, ProviderError>;
 
     async fn get_drep_metadata(&self, id: &str)
-        -> Result<Option<serde_json::Value>, anyhow::Error>;
+        -> Result<Option<serde_json::Value>, ProviderError>;
 
-    async fn get_total_active_dreps(&self) -> Result<Option<u32>, anyhow::Error>;
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError>;
 
     async fn get_stake_delegation(
         &self,
         stake_address: &str,
-    ) -> Result<Option<StakeDelegation>, anyhow::Error>;
+    ) -> Result<Option<StakeDelegation>, ProviderError>;
 
-    async fn health_check(&self) -> Result<bool, anyhow::Error>;
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<StakePoolPage, ProviderError>;
+
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError>;
+
+    /// Unix time the epoch started at.
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError>;
+
+    /// Individual votes cast on `action`; providers pick the identifier they
+    /// need (transaction and certificate index, or CIP-129 proposal id).
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError>;
+
+    async fn health_check(&self) -> Result<bool, ProviderError>;
+
+    /// The latest block, for providers that follow the chain.
+    async fn get_tip(&self) -> Result<Option<ChainTip>, ProviderError> {
+        Ok(None)
+    }
+
+    /// Votes included in blocks after `block_height`, oldest first.
+    async fn get_votes_since(
+        &self,
+        _block_height: u64,
+        _limit: u32,
+    ) -> Result<Vec<RecentVote>, ProviderError> {
+        Ok(Vec::new())
+    }
+
+    /// Protocol magic of the network the provider serves, when it reports one.
+    async fn get_network_magic(&self) -> Result<Option<u64>, ProviderError> {
+        Ok(None)
+    }
+
+    /// Request quota used so far, for providers that meter requests.
+    fn quota(&self) -> Option<QuotaSnapshot> {
+        None
+    }
 }
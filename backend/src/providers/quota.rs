@@ -0,0 +1,199 @@
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Default)]
+struct QuotaState {
+    day: u64,
+    requests_today: u64,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    reset_secs: Option<u64>,
+    observed_at: Option<u64>,
+}
+
+/// Request usage for one provider, taken from the rate-limit headers on its
+/// responses where it sends them.
+pub struct QuotaTracker {
+    name: &'static str,
+    base_url: String,
+    authenticated: bool,
+    state: Mutex<QuotaState>,
+}
+
+/// Quota usage reported in `/health`, including the endpoint in use so a
+/// provider pointed at the wrong network stands out.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaSnapshot {
+    pub name: &'static str,
+    pub base_url: String,
+    pub authenticated: bool,
+    /// Responses received since midnight UTC
+    pub requests_today: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_secs: Option<u64>,
+    /// When rate-limit headers were last seen (unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed_at: Option<u64>,
+}
+
+impl QuotaTracker {
+    pub fn new(name: &'static str, base_url: String, authenticated: bool) -> Self {
+        Self {
+            name,
+            base_url,
+            authenticated,
+            state: Mutex::new(QuotaState::default()),
+        }
+    }
+
+    /// Counts a response and picks up `X-RateLimit-*` or `RateLimit-*` headers.
+    pub fn observe(&self, headers: &HeaderMap) {
+        let now = unix_now();
+        let limit = rate_limit_header(headers, "limit");
+        let remaining = rate_limit_header(headers, "remaining");
+        let reset_secs = rate_limit_header(headers, "reset");
+
+        let mut state = self.state.lock().unwrap();
+        let day = now / SECONDS_PER_DAY;
+        if state.day != day {
+            state.day = day;
+            state.requests_today = 0;
+        }
+        state.requests_today += 1;
+
+        if limit.is_some() || remaining.is_some() {
+            state.limit = limit;
+            state.remaining = remaining;
+            state.reset_secs = reset_secs;
+            state.observed_at = Some(now);
+        }
+    }
+
+    pub fn snapshot(&self) -> QuotaSnapshot {
+        let state = self.state.lock().unwrap();
+        let requests_today = if state.day == unix_now() / SECONDS_PER_DAY {
+            state.requests_today
+        } else {
+            0
+        };
+        QuotaSnapshot {
+            name: self.name,
+            base_url: self.base_url.clone(),
+            authenticated: self.authenticated,
+            requests_today,
+            limit: state.limit,
+            remaining: state.remaining,
+            reset_secs: state.reset_secs,
+            observed_at: state.observed_at,
+        }
+    }
+}
+
+fn rate_limit_header(headers: &HeaderMap, field: &str) -> Option<u64> {
+    [
+        format!("x-ratelimit-{}", field),
+        format!("ratelimit-{}", field),
+    ]
+    .iter()
+    .find_map(|name| headers.get(name.as_str()))
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.trim().parse().ok())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn tracker() -> QuotaTracker {
+        QuotaTracker::new("koios", "https://api.koios.rest/api/v1".to_string(), true)
+    }
+
+    #[test]
+    fn reads_x_ratelimit_and_ratelimit_headers() {
+        let blockfrost = headers(&[
+            ("x-ratelimit-limit", "50000"),
+            ("x-ratelimit-remaining", "49000"),
+        ]);
+        assert_eq!(rate_limit_header(&blockfrost, "limit"), Some(50000));
+        assert_eq!(rate_limit_header(&blockfrost, "remaining"), Some(49000));
+        assert_eq!(rate_limit_header(&blockfrost, "reset"), None);
+
+        // The IETF draft names
+        let draft = headers(&[
+            ("ratelimit-limit", " 5000 "),
+            ("ratelimit-remaining", "12"),
+            ("ratelimit-reset", "30"),
+        ]);
+        assert_eq!(rate_limit_header(&draft, "limit"), Some(5000));
+        assert_eq!(rate_limit_header(&draft, "reset"), Some(30));
+
+        // The X- form wins when both are sent; junk is ignored
+        let both = headers(&[
+            ("x-ratelimit-limit", "100"),
+            ("ratelimit-limit", "200"),
+            ("ratelimit-remaining", "many"),
+        ]);
+        assert_eq!(rate_limit_header(&both, "limit"), Some(100));
+        assert_eq!(rate_limit_header(&both, "remaining"), None);
+    }
+
+    #[test]
+    fn counts_requests_and_keeps_the_last_quota_seen() {
+        let quota = tracker();
+        quota.observe(&headers(&[
+            ("ratelimit-limit", "5000"),
+            ("ratelimit-remaining", "4999"),
+            ("ratelimit-reset", "60"),
+        ]));
+        // Responses without the headers still count, but keep the last quota
+        quota.observe(&HeaderMap::new());
+
+        let snapshot = quota.snapshot();
+        assert_eq!(snapshot.name, "koios");
+        assert!(snapshot.authenticated);
+        assert_eq!(snapshot.requests_today, 2);
+        assert_eq!(snapshot.limit, Some(5000));
+        assert_eq!(snapshot.remaining, Some(4999));
+        assert_eq!(snapshot.reset_secs, Some(60));
+        assert!(snapshot.observed_at.is_some());
+    }
+
+    #[test]
+    fn request_count_resets_each_day() {
+        let quota = tracker();
+        quota.observe(&HeaderMap::new());
+        quota.observe(&HeaderMap::new());
+        assert_eq!(quota.snapshot().requests_today, 2);
+
+        // The last response came in yesterday
+        quota.state.lock().unwrap().day -= 1;
+        assert_eq!(quota.snapshot().requests_today, 0);
+
+        quota.observe(&HeaderMap::new());
+        assert_eq!(quota.snapshot().requests_today, 1);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::models::*;
+use crate::providers::{
+    GovToolsApi, GovToolsEnrichment, Provider, ProviderError, ProviderFixtures, QuotaSnapshot,
+};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Passes every call through to a live provider and saves its outcome to
+/// fixtures, which `ReplayProvider` can answer from later. Wraps Koios and
+/// Blockfrost as a `Provider`, and GovTools as a `GovToolsApi`.
+pub struct RecordingProvider<P> {
+    inner: P,
+    fixtures: Arc<ProviderFixtures>,
+}
+
+impl<P> RecordingProvider<P> {
+    pub fn new(inner: P, fixtures: Arc<ProviderFixtures>) -> Self {
+        Self { inner, fixtures }
+    }
+
+    async fn recorded<T: Serialize>(
+        &self,
+        call: &str,
+        args: Value,
+        result: Result<T, ProviderError>,
+    ) -> Result<T, ProviderError> {
+        self.fixtures.record(call, args, &result).await;
+        result
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for RecordingProvider<P> {
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
+        let result = self.inner.get_dreps_page(query).await;
+        self.recorded("get_dreps_page", json!([query]), result)
+            .await
+    }
+
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        let result = self.inner.get_drep(id).await;
+        self.recorded("get_drep", json!([id]), result).await
+    }
+
+    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        let result = self.inner.get_drep_delegators(id).await;
+        self.recorded("get_drep_delegators", json!([id]), result)
+            .await
+    }
+
+    async fn get_drep_voting_history(
+        &self,
+        id: &str,
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        let result = self.inner.get_drep_voting_history(id).await;
+        self.recorded("get_drep_voting_history", json!([id]), result)
+            .await
+    }
+
+    async fn get_governance_actions_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<ActionsPage, ProviderError> {
+        let result = self.inner.get_governance_actions_page(page, count).await;
+        self.recorded("get_governance_actions_page", json!([page, count]), result)
+            .await
+    }
+
+    async fn get_governance_action(
+        &self,
+        id: &str,
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
+        let result = self.inner.get_governance_action(id).await;
+        self.recorded("get_governance_action", json!([id]), result)
+            .await
+    }
+
+    async fn get_action_voting_results(
+        &self,
+        id: &str,
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
+        let result = self.inner.get_action_voting_results(id).await;
+        self.recorded("get_action_voting_results", json!([id]), result)
+            .await
+    }
+
+    async fn get_drep_metadata(&self, id: &str) -> Result<Option<Value>, ProviderError> {
+        let result = self.inner.get_drep_metadata(id).await;
+        self.recorded("get_drep_metadata", json!([id]), result)
+            .await
+    }
+
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
+        let result = self.inner.get_total_active_dreps().await;
+        self.recorded("get_total_active_dreps", json!([]), result)
+            .await
+    }
+
+    async fn get_stake_delegation(
+        &self,
+        stake_address: &str,
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
+        let result = self.inner.get_stake_delegation(stake_address).await;
+        self.recorded("get_stake_delegation", json!([stake_address]), result)
+            .await
+    }
+
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<StakePoolPage, ProviderError> {
+        let result = self.inner.get_stake_pools_page(page, count).await;
+        self.recorded("get_stake_pools_page", json!([page, count]), result)
+            .await
+    }
+
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        let result = self.inner.get_committee_info().await;
+        self.recorded("get_committee_info", json!([]), result).await
+    }
+
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        let result = self.inner.get_epoch_start_time(epoch).await;
+        self.recorded("get_epoch_start_time", json!([epoch]), result)
+            .await
+    }
+
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        let result = self.inner.get_action_vote_records(action).await;
+        // Keyed by the action's id; its other fields change as it is voted on
+        self.recorded("get_action_vote_records", json!([action.action_id]), result)
+            .await
+    }
+
+    async fn health_check(&self) -> Result<bool, ProviderError> {
+        let result = self.inner.health_check().await;
+        self.recorded("health_check", json!([]), result).await
+    }
+
+    async fn get_tip(&self) -> Result<Option<ChainTip>, ProviderError> {
+        let result = self.inner.get_tip().await;
+        self.recorded("get_tip", json!([]), result).await
+    }
+
+    async fn get_votes_since(
+        &self,
+        block_height: u64,
+        limit: u32,
+    ) -> Result<Vec<RecentVote>, ProviderError> {
+        let result = self.inner.get_votes_since(block_height, limit).await;
+        self.recorded("get_votes_since", json!([block_height, limit]), result)
+            .await
+    }
+
+    async fn get_network_magic(&self) -> Result<Option<u64>, ProviderError> {
+        let result = self.inner.get_network_magic().await;
+        self.recorded("get_network_magic", json!([]), result).await
+    }
+
+    fn quota(&self) -> Option<QuotaSnapshot> {
+        self.inner.quota()
+    }
+}
+
+#[async_trait]
+impl<P: GovToolsApi> GovToolsApi for RecordingProvider<P> {
+    async fn list_dreps(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
+        let result = self.inner.list_dreps(query).await;
+        if let Ok(page) = &result {
+            self.fixtures
+                .record("list_dreps", json!([query]), &Ok(page))
+                .await;
+        }
+        result
+    }
+
+    async fn get_drep_profile(
+        &self,
+        hex_id: &str,
+    ) -> Result<Option<GovToolsEnrichment>, anyhow::Error> {
+        let result = self.inner.get_drep_profile(hex_id).await;
+        if let Ok(profile) = &result {
+            self.fixtures
+                .record("get_drep_profile", json!([hex_id]), &Ok(profile))
+                .await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::testing::StubProvider;
+    use crate::providers::{FixtureConfig, FixtureMode, ReplayProvider};
+
+    fn config(mode: FixtureMode, dir: &std::path::Path) -> FixtureConfig {
+        FixtureConfig {
+            mode,
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_what_was_recorded() {
+        let dir = std::env::temp_dir().join(format!("govtwool-fixtures-{}", fastrand::u64(..)));
+        let fixtures = ProviderFixtures::open(&config(FixtureMode::Record, &dir), "koios")
+            .await
+            .unwrap();
+        let live = StubProvider::new().answer("drep1a", json!({ "drep_id": "drep1a" }));
+        let recording = RecordingProvider::new(live, fixtures);
+        assert!(recording.get_drep("drep1a").await.unwrap().is_some());
+        assert!(recording.get_drep("drep1b").await.unwrap().is_none());
+
+        let fixtures = ProviderFixtures::open(&config(FixtureMode::Replay, &dir), "koios")
+            .await
+            .unwrap();
+        let replay = ReplayProvider::new(fixtures);
+        let drep = replay.get_drep("drep1a").await.unwrap().unwrap();
+        assert_eq!(drep.drep_id, "drep1a");
+        assert!(replay.get_drep("drep1b").await.unwrap().is_none());
+        assert!(matches!(
+            replay.get_drep("drep1c").await,
+            Err(ProviderError::NotRecorded(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn records_only_errors_that_would_repeat() {
+        let dir = std::env::temp_dir().join(format!("govtwool-fixtures-{}", fastrand::u64(..)));
+        let fixtures = ProviderFixtures::open(&config(FixtureMode::Record, &dir), "blockfrost")
+            .await
+            .unwrap();
+        let missing = StubProvider::new().failing_with(ProviderError::NotFound("drep1a".into()));
+        let missing = RecordingProvider::new(missing, fixtures.clone());
+        assert!(missing.get_drep("drep1a").await.is_err());
+        let down = RecordingProvider::new(StubProvider::new().failing(), fixtures);
+        assert!(down.get_drep("drep1b").await.is_err());
+
+        let fixtures = ProviderFixtures::open(&config(FixtureMode::Replay, &dir), "blockfrost")
+            .await
+            .unwrap();
+        let replay = ReplayProvider::new(fixtures);
+        assert!(matches!(
+            replay.get_drep("drep1a").await,
+            Err(ProviderError::NotFound(_))
+        ));
+        assert!(matches!(
+            replay.get_drep("drep1b").await,
+            Err(ProviderError::NotRecorded(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
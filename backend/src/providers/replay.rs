@@ -0,0 +1,143 @@
+use crate::models::*;
+use crate::providers::{
+    GovToolsApi, GovToolsEnrichment, Provider, ProviderError, ProviderFixtures,
+};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Answers every call from fixtures written by `RecordingProvider`, without
+/// going over the network. A call that was never recorded fails with
+/// `NotRecorded`. Stands in for Koios and Blockfrost as a `Provider`, and for
+/// GovTools as a `GovToolsApi`.
+pub struct ReplayProvider {
+    fixtures: Arc<ProviderFixtures>,
+}
+
+impl ReplayProvider {
+    pub fn new(fixtures: Arc<ProviderFixtures>) -> Self {
+        Self { fixtures }
+    }
+}
+
+#[async_trait]
+impl Provider for ReplayProvider {
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
+        self.fixtures.replay("get_dreps_page", json!([query]))
+    }
+
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        self.fixtures.replay("get_drep", json!([id]))
+    }
+
+    async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        self.fixtures.replay("get_drep_delegators", json!([id]))
+    }
+
+    async fn get_drep_voting_history(
+        &self,
+        id: &str,
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        self.fixtures.replay("get_drep_voting_history", json!([id]))
+    }
+
+    async fn get_governance_actions_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<ActionsPage, ProviderError> {
+        self.fixtures
+            .replay("get_governance_actions_page", json!([page, count]))
+    }
+
+    async fn get_governance_action(
+        &self,
+        id: &str,
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
+        self.fixtures.replay("get_governance_action", json!([id]))
+    }
+
+    async fn get_action_voting_results(
+        &self,
+        id: &str,
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
+        self.fixtures
+            .replay("get_action_voting_results", json!([id]))
+    }
+
+    async fn get_drep_metadata(&self, id: &str) -> Result<Option<Value>, ProviderError> {
+        self.fixtures.replay("get_drep_metadata", json!([id]))
+    }
+
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
+        self.fixtures.replay("get_total_active_dreps", json!([]))
+    }
+
+    async fn get_stake_delegation(
+        &self,
+        stake_address: &str,
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
+        self.fixtures
+            .replay("get_stake_delegation", json!([stake_address]))
+    }
+
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<StakePoolPage, ProviderError> {
+        self.fixtures
+            .replay("get_stake_pools_page", json!([page, count]))
+    }
+
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        self.fixtures.replay("get_committee_info", json!([]))
+    }
+
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        self.fixtures.replay("get_epoch_start_time", json!([epoch]))
+    }
+
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        self.fixtures
+            .replay("get_action_vote_records", json!([action.action_id]))
+    }
+
+    async fn health_check(&self) -> Result<bool, ProviderError> {
+        self.fixtures.replay("health_check", json!([]))
+    }
+
+    async fn get_tip(&self) -> Result<Option<ChainTip>, ProviderError> {
+        self.fixtures.replay("get_tip", json!([]))
+    }
+
+    async fn get_votes_since(
+        &self,
+        block_height: u64,
+        limit: u32,
+    ) -> Result<Vec<RecentVote>, ProviderError> {
+        self.fixtures
+            .replay("get_votes_since", json!([block_height, limit]))
+    }
+
+    async fn get_network_magic(&self) -> Result<Option<u64>, ProviderError> {
+        self.fixtures.replay("get_network_magic", json!([]))
+    }
+}
+
+#[async_trait]
+impl GovToolsApi for ReplayProvider {
+    async fn list_dreps(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
+        Ok(self.fixtures.replay("list_dreps", json!([query]))?)
+    }
+
+    async fn get_drep_profile(
+        &self,
+        hex_id: &str,
+    ) -> Result<Option<GovToolsEnrichment>, anyhow::Error> {
+        Ok(self.fixtures.replay("get_drep_profile", json!([hex_id]))?)
+    }
+}
@@ -0,0 +1,259 @@
+use crate::providers::error::ProviderError;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Retries that can be spent in a burst before the budget has to refill
+const BUDGET_RESERVE: f64 = 10.0;
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Retries per request on top of the first attempt
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubled for each further retry
+    pub base_delay: Duration,
+    /// Longest backoff, and the longest Retry-After that is waited out
+    pub max_delay: Duration,
+    /// Retries earned per request (0.2 = at most one retry per five requests)
+    pub budget_ratio: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            budget_ratio: 0.2,
+        }
+    }
+}
+
+/// Retry policy shared by the HTTP providers: jittered exponential backoff,
+/// Retry-After handling and a retry budget that keeps an outage from being
+/// multiplied by retries.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    config: RetryConfig,
+    budget: Arc<Mutex<f64>>,
+}
+
+impl RetryPolicy {
+    pub fn new(config: RetryConfig) -> Self {
+        Self {
+            config,
+            budget: Arc::new(Mutex::new(BUDGET_RESERVE)),
+        }
+    }
+
+    /// Runs `request`, retrying retryable failures until it succeeds, the
+    /// attempts run out or the budget is spent.
+    pub async fn run<T, F, Fut>(&self, label: &str, mut request: F) -> Result<T, ProviderError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.deposit();
+        let mut attempt = 0;
+
+        loop {
+            let error = match request().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            if attempt >= self.config.max_retries {
+                return Err(error);
+            }
+            let Some(delay) = self.delay_for(&error, attempt) else {
+                return Err(error);
+            };
+            if !self.withdraw() {
+                tracing::debug!("Retry budget exhausted, not retrying {}", label);
+                return Err(error);
+            }
+
+            attempt += 1;
+            tracing::warn!(
+                "{} failed ({}), retry {}/{} in {}ms",
+                label,
+                error,
+                attempt,
+                self.config.max_retries,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn delay_for(&self, error: &ProviderError, attempt: u32) -> Option<Duration> {
+        if !error.is_retryable() {
+            return None;
+        }
+
+        if let ProviderError::RateLimited {
+            retry_after: Some(retry_after),
+        } = error
+        {
+            // Waiting out a long Retry-After would stall the request; let the router fail over
+            if *retry_after > self.config.max_delay {
+                return None;
+            }
+            return Some(*retry_after);
+        }
+
+        // Full jitter: a random delay up to the exponential backoff
+        let backoff = self
+            .config
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_delay);
+        Some(backoff.mul_f64(fastrand::f64()))
+    }
+
+    fn deposit(&self) {
+        let mut budget = self.budget.lock().unwrap();
+        *budget = (*budget + self.config.budget_ratio).min(BUDGET_RESERVE);
+    }
+
+    fn withdraw(&self) -> bool {
+        let mut budget = self.budget.lock().unwrap();
+        if *budget >= 1.0 {
+            *budget -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::new(RetryConfig {
+            max_retries,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            budget_ratio: 0.2,
+        })
+    }
+
+    #[test]
+    fn backoff_is_full_jitter_under_a_capped_exponential() {
+        let policy = policy(10);
+        let error = ProviderError::Timeout;
+        for attempt in 0..10 {
+            let ceiling = Duration::from_millis((100 * 2u64.pow(attempt)).min(1000));
+            let delays: Vec<_> = (0..200)
+                .map(|_| policy.delay_for(&error, attempt).unwrap())
+                .collect();
+            assert!(
+                delays.iter().all(|delay| *delay <= ceiling),
+                "attempt {}",
+                attempt
+            );
+            // Jittered across the whole range, not pinned to the ceiling
+            assert!(
+                delays.iter().any(|delay| *delay < ceiling / 2),
+                "attempt {}",
+                attempt
+            );
+        }
+    }
+
+    #[test]
+    fn backoff_does_not_overflow_on_late_attempts() {
+        let delay = policy(100).delay_for(&ProviderError::Timeout, 64).unwrap();
+        assert!(delay <= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn waits_out_a_short_retry_after_exactly() {
+        let error = ProviderError::RateLimited {
+            retry_after: Some(Duration::from_millis(700)),
+        };
+        assert_eq!(
+            policy(3).delay_for(&error, 0),
+            Some(Duration::from_millis(700))
+        );
+    }
+
+    #[test]
+    fn gives_up_on_a_retry_after_longer_than_max_delay() {
+        let error = ProviderError::RateLimited {
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        assert_eq!(policy(3).delay_for(&error, 0), None);
+    }
+
+    #[test]
+    fn does_not_retry_permanent_errors() {
+        assert_eq!(
+            policy(3).delay_for(&ProviderError::NotFound("x".into()), 0),
+            None
+        );
+        let client_error = ProviderError::Upstream {
+            status: 400,
+            message: String::new(),
+        };
+        assert_eq!(policy(3).delay_for(&client_error, 0), None);
+    }
+
+    async fn attempts(policy: &RetryPolicy, error: ProviderError) -> u32 {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = policy
+            .run("test", || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let error = error.clone();
+                async move { Err(error) }
+            })
+            .await;
+        assert!(result.is_err());
+        calls.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_after_max_retries() {
+        assert_eq!(attempts(&policy(3), ProviderError::Timeout).await, 4);
+        assert_eq!(attempts(&policy(0), ProviderError::Timeout).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn succeeds_on_a_later_attempt() {
+        let calls = AtomicU32::new(0);
+        let result = policy(3)
+            .run("test", || {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if call < 2 {
+                        Err(ProviderError::Transport("reset".into()))
+                    } else {
+                        Ok(call)
+                    }
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn budget_exhaustion_stops_retries() {
+        let policy = policy(100);
+        // The reserve allows a burst of BUDGET_RESERVE retries
+        assert_eq!(
+            attempts(&policy, ProviderError::Timeout).await,
+            1 + BUDGET_RESERVE as u32
+        );
+        // Then each request earns budget_ratio of a retry
+        for _ in 0..4 {
+            assert_eq!(attempts(&policy, ProviderError::Timeout).await, 1);
+        }
+        assert_eq!(attempts(&policy, ProviderError::Timeout).await, 2);
+
+        // Clones share the budget
+        assert_eq!(attempts(&policy.clone(), ProviderError::Timeout).await, 1);
+    }
+}
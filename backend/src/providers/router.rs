@@ -1,270 +1,857 @@
 use crate::models::*;
-use crate::providers::{BlockfrostProvider, KoiosProvider, Provider};
+use crate::network::Network;
+use crate::providers::consistency::{
+    found, ConsistencyKind, ConsistencyMonitor, ConsistencyReport, KindCounts,
+};
+use crate::providers::health::{BreakerConfig, ProviderHealth, ProviderHealthSnapshot};
+use crate::providers::routing::{
+    Operation, ProviderKind, ProviderRoute, RouteResult, RoutingPolicy,
+};
+#[cfg(feature = "dbsync")]
+use crate::providers::DbSyncProvider;
+use crate::providers::{OgmiosProvider, Provider, ProviderError, QuotaSnapshot};
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+struct RoutedProvider {
+    kind: ProviderKind,
+    provider: Arc<dyn Provider>,
+    health: Arc<ProviderHealth>,
+}
 
 #[derive(Clone)]
 pub struct ProviderRouter {
-    blockfrost: Arc<BlockfrostProvider>,
-    koios: Arc<KoiosProvider>,
+    blockfrost: Arc<dyn Provider>,
+    koios: Arc<dyn Provider>,
+    #[cfg(feature = "dbsync")]
+    dbsync: Option<Arc<DbSyncProvider>>,
+    ogmios: Option<Arc<OgmiosProvider>>,
+    providers: Arc<Vec<RoutedProvider>>,
+    policy: Arc<RoutingPolicy>,
+    consistency: Arc<ConsistencyMonitor>,
 }
 
 impl ProviderRouter {
-    pub fn new(blockfrost: BlockfrostProvider, koios: KoiosProvider) -> Self {
+    /// Routes between Blockfrost and Koios, which are usually the HTTP
+    /// providers themselves but may be wrapped to record or replay fixtures.
+    pub fn new(
+        blockfrost: Arc<dyn Provider>,
+        koios: Arc<dyn Provider>,
+        breaker: BreakerConfig,
+        policy: RoutingPolicy,
+    ) -> Self {
+        let providers = vec![
+            RoutedProvider {
+                kind: ProviderKind::Koios,
+                provider: koios.clone(),
+                health: Arc::new(ProviderHealth::new(
+                    ProviderKind::Koios.as_str(),
+                    breaker.clone(),
+                )),
+            },
+            RoutedProvider {
+                kind: ProviderKind::Blockfrost,
+                provider: blockfrost.clone(),
+                health: Arc::new(ProviderHealth::new(
+                    ProviderKind::Blockfrost.as_str(),
+                    breaker,
+                )),
+            },
+        ];
+
         Self {
-            blockfrost: Arc::new(blockfrost),
-            koios: Arc::new(koios),
+            blockfrost,
+            koios,
+            #[cfg(feature = "dbsync")]
+            dbsync: None,
+            ogmios: None,
+            providers: Arc::new(providers),
+            policy: Arc::new(policy),
+            consistency: Arc::new(ConsistencyMonitor::new(0.0)),
         }
     }
 
-    // Smart routing strategy:
-    // - DRep list: Try Koios first (faster bulk queries), fallback to Blockfrost
-    // - DRep details: Use Blockfrost (more complete metadata)
-    // - DRep delegators: Use Koios (specialized endpoint), fallback to Blockfrost
-    // - DRep voting history: Use Koios (specialized endpoint), fallback to Blockfrost
-    // - Governance actions list: Try Koios first, fallback to Blockfrost
-    // - Governance action details: Try Koios first, fallback to Blockfrost
-    // - Voting results: Use Koios (specialized), fallback to Blockfrost
-    // - Active DReps count: Use Koios epoch summary
-
-    pub async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, anyhow::Error> {
-        // Try Koios first (faster bulk queries)
-        match self.koios.get_dreps_page(query).await {
-            Ok(result) if !result.dreps.is_empty() => {
-                tracing::debug!("Using Koios for DReps page");
-                return Ok(result);
-            }
-            _ => {
-                tracing::debug!("Koios failed, falling back to Blockfrost for DReps page");
-            }
+    /// Shadows `sample_rate` (0.0-1.0) of DRep, action and vote-breakdown
+    /// requests with a background consistency check between Koios and
+    /// Blockfrost. Each sampled request costs one extra call to each of them.
+    pub fn with_consistency_sampling(mut self, sample_rate: f64) -> Self {
+        self.consistency = Arc::new(ConsistencyMonitor::new(sample_rate));
+        self
+    }
+
+    /// Registers a db-sync database and puts it first on every route that
+    /// still has its default provider list; Koios and Blockfrost remain as
+    /// fallbacks.
+    #[cfg(feature = "dbsync")]
+    pub fn with_dbsync(mut self, dbsync: DbSyncProvider, breaker: BreakerConfig) -> Self {
+        let dbsync = Arc::new(dbsync);
+        let mut providers = self.providers.as_ref().clone();
+        providers.insert(
+            0,
+            RoutedProvider {
+                kind: ProviderKind::DbSync,
+                provider: dbsync.clone(),
+                health: Arc::new(ProviderHealth::new(ProviderKind::DbSync.as_str(), breaker)),
+            },
+        );
+
+        self.providers = Arc::new(providers);
+        self.policy = Arc::new(self.policy.as_ref().clone().prefer(ProviderKind::DbSync));
+        self.dbsync = Some(dbsync);
+        self
+    }
+
+    /// Registers an Ogmios endpoint. It answers committee members and DRep
+    /// voting power from the ledger state, and is the last fallback for
+    /// governance actions the indexers haven't picked up yet.
+    pub fn with_ogmios(mut self, ogmios: OgmiosProvider, breaker: BreakerConfig) -> Self {
+        let ogmios = Arc::new(ogmios);
+        let mut providers = self.providers.as_ref().clone();
+        providers.push(RoutedProvider {
+            kind: ProviderKind::Ogmios,
+            provider: ogmios.clone(),
+            health: Arc::new(ProviderHealth::new(ProviderKind::Ogmios.as_str(), breaker)),
+        });
+
+        self.providers = Arc::new(providers);
+        self.policy = Arc::new(
+            self.policy
+                .as_ref()
+                .clone()
+                .prefer_for(ProviderKind::Ogmios, &[Operation::CommitteeInfo])
+                .fall_back_to(ProviderKind::Ogmios, &[Operation::GovernanceAction]),
+        );
+        self.ogmios = Some(ogmios);
+        self
+    }
+
+    fn routed(&self, kind: ProviderKind) -> Option<&RoutedProvider> {
+        self.providers.iter().find(|routed| routed.kind == kind)
+    }
+
+    /// The Ogmios provider and its breaker, when one is registered.
+    fn ogmios(&self) -> Option<(&OgmiosProvider, &ProviderHealth)> {
+        let ogmios = self.ogmios.as_deref()?;
+        Some((ogmios, self.health(ProviderKind::Ogmios)))
+    }
+
+    fn health(&self, kind: ProviderKind) -> &ProviderHealth {
+        // Koios and Blockfrost are always registered, db-sync and Ogmios when configured
+        &self
+            .routed(kind)
+            .expect("provider registered with router")
+            .health
+    }
+
+    /// Runs a provider call through its circuit breaker, recording the outcome.
+    /// Returns `None` without calling the provider while its circuit is open.
+    async fn guarded<T, F>(health: &ProviderHealth, call: F) -> Option<Result<T, ProviderError>>
+    where
+        F: Future<Output = Result<T, ProviderError>>,
+    {
+        let Some(permit) = health.try_acquire() else {
+            tracing::debug!("Skipping {}: circuit open", health.name());
+            return None;
+        };
+
+        let started = Instant::now();
+        let result = call.await;
+        match &result {
+            Ok(_) => health.record_success(&permit, started.elapsed()),
+            Err(error) => match error {
+                // Honour the provider's Retry-After instead of hammering it until the breaker trips
+                ProviderError::RateLimited {
+                    retry_after: Some(retry_after),
+                } => health.record_rate_limited(started.elapsed(), *retry_after),
+                // A missing entity or fixture says nothing about the provider's health
+                ProviderError::NotFound(_)
+                | ProviderError::InvalidInput(_)
+                | ProviderError::NotRecorded(_) => {
+                    health.record_success(&permit, started.elapsed())
+                }
+                _ => health.record_failure(&permit, started.elapsed(), &error.to_string()),
+            },
         }
+        Some(result)
+    }
 
-        // Fallback to Blockfrost
-        self.blockfrost.get_dreps_page(query).await
+    /// Like `guarded`, for the last provider in a chain: an open circuit becomes an error.
+    async fn guarded_last<T, F>(health: &ProviderHealth, call: F) -> Result<T, ProviderError>
+    where
+        F: Future<Output = Result<T, ProviderError>>,
+    {
+        Self::guarded(health, call).await.unwrap_or_else(|| {
+            Err(ProviderError::Unavailable(format!(
+                "{} circuit open",
+                health.name()
+            )))
+        })
     }
 
-    pub async fn get_drep(&self, id: &str) -> Result<Option<DRep>, anyhow::Error> {
-        // Use Blockfrost (more complete metadata)
-        self.blockfrost.get_drep(id).await
+    async fn with_timeout<T, F>(timeout: Option<Duration>, call: F) -> Result<T, ProviderError>
+    where
+        F: Future<Output = Result<T, ProviderError>>,
+    {
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, call)
+                .await
+                .unwrap_or_else(|_| Err(ProviderError::Timeout)),
+            None => call.await,
+        }
+    }
+
+    /// Calls a single provider of a route, applying its timeout and circuit breaker.
+    /// Returns `None` when the provider is not registered or its circuit is open.
+    async fn attempt<'a, T, F>(
+        &'a self,
+        operation: Operation,
+        step: &ProviderRoute,
+        call: &F,
+    ) -> Option<Result<T, ProviderError>>
+    where
+        F: Fn(&'a dyn Provider) -> BoxFuture<'a, Result<T, ProviderError>>,
+    {
+        let Some(routed) = self.routed(step.provider) else {
+            tracing::debug!("{} not available for {}", step.provider, operation);
+            return None;
+        };
+
+        let call = Self::with_timeout(step.timeout(), call(routed.provider.as_ref()));
+        Self::guarded(&routed.health, call).await
     }
 
-    pub async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, anyhow::Error> {
-        // Try Koios first (specialized endpoint)
-        match self.koios.get_drep_delegators(id).await {
-            Ok(result) if !result.is_empty() => {
-                tracing::debug!("Using Koios for DRep delegators");
-                return Ok(result);
+    /// Walks the configured provider list for `operation`, moving on to the next
+    /// provider whenever the route's fallback condition matches the result.
+    ///
+    /// On routes with a hedge config each provider hedges the next one in line:
+    /// one that hasn't answered within the hedge delay gets the next provider
+    /// started alongside it, and so on down the list. The first acceptable
+    /// answer wins and dropping the calls still in flight cancels them. A
+    /// rejected answer starts the next provider straight away when nothing else
+    /// is in flight; otherwise the other calls keep running until they answer
+    /// or the next hedge delay passes.
+    async fn route<'a, T, F>(&'a self, operation: Operation, call: F) -> Result<T, ProviderError>
+    where
+        T: RouteResult,
+        F: Fn(&'a dyn Provider) -> BoxFuture<'a, Result<T, ProviderError>>,
+    {
+        let route = self.policy.route(operation);
+        let start = |step: &'a ProviderRoute| {
+            let call = &call;
+            async move { (step.provider, self.attempt(operation, step, call).await) }
+        };
+
+        let mut remaining = route.providers.iter();
+        let mut in_flight = FuturesUnordered::new();
+        let mut latest: Option<&ProviderRoute> = None;
+        let mut fallback: Option<Result<T, ProviderError>> = None;
+
+        loop {
+            if in_flight.is_empty() {
+                let Some(step) = remaining.next() else {
+                    break;
+                };
+                in_flight.push(start(step));
+                latest = Some(step);
             }
-            Ok(_) => {
-                // Empty result, try Blockfrost
+
+            // Delay before the next provider in line joins the ones in flight
+            let hedge = match (&route.hedge, latest, remaining.as_slice().first()) {
+                (Some(hedge), Some(latest), Some(next)) => {
+                    let observed = self
+                        .routed(latest.provider)
+                        .and_then(|routed| routed.health.latency_percentile(hedge.percentile));
+                    Some((hedge.delay(observed), latest, next))
+                }
+                _ => None,
+            };
+            let delay = hedge.map(|(delay, _, _)| delay).unwrap_or_default();
+
+            let finished = tokio::select! {
+                Some(finished) = in_flight.next() => Some(finished),
+                _ = tokio::time::sleep(delay), if hedge.is_some() => None,
+            };
+
+            let Some((provider, outcome)) = finished else {
+                if let Some((delay, slow, next)) = hedge {
+                    tracing::debug!(
+                        "{} slower than {}ms for {}, hedging with {}",
+                        slow.provider,
+                        delay.as_millis(),
+                        operation,
+                        next.provider
+                    );
+                    remaining.next();
+                    in_flight.push(start(next));
+                    latest = Some(next);
+                }
+                continue;
+            };
+
+            let Some(result) = outcome else {
+                fallback
+                    .get_or_insert_with(|| Err(ProviderError::Unavailable(provider.to_string())));
+                continue;
+            };
+
+            if !route.should_fall_back(&result) {
+                tracing::debug!("Using {} for {}", provider, operation);
+                return result;
             }
-            Err(e) => {
-                tracing::debug!(
-                    "Koios failed for DRep delegators: {}, falling back to Blockfrost",
-                    e
-                );
+
+            match &result {
+                Err(e) => tracing::debug!("{} failed for {}: {}", provider, operation, e),
+                Ok(_) => tracing::debug!("{} returned no data for {}", provider, operation),
             }
+            // The last provider's answer is returned even if it matches the condition
+            fallback = Some(result);
         }
 
-        // Fallback to Blockfrost
-        self.blockfrost.get_drep_delegators(id).await
+        fallback.unwrap_or_else(|| {
+            Err(ProviderError::Unavailable(format!(
+                "no provider for {}",
+                operation
+            )))
+        })
     }
 
-    pub async fn get_drep_voting_history(
-        &self,
-        id: &str,
-    ) -> Result<Vec<DRepVotingHistory>, anyhow::Error> {
-        // Try Koios first (specialized endpoint)
-        match self.koios.get_drep_voting_history(id).await {
-            Ok(result) if !result.is_empty() => {
-                tracing::debug!("Using Koios for DRep voting history");
-                return Ok(result);
+    pub fn provider_health(&self) -> Vec<ProviderHealthSnapshot> {
+        self.providers
+            .iter()
+            .map(|routed| routed.health.snapshot())
+            .collect()
+    }
+
+    pub fn provider_quotas(&self) -> Vec<QuotaSnapshot> {
+        [&self.koios, &self.blockfrost]
+            .iter()
+            .filter_map(|provider| provider.quota())
+            .collect()
+    }
+
+    /// Asks Koios and Blockfrost for the same entity and diffs their answers
+    /// field by field. The calls still go through the circuit breakers, so a
+    /// check never adds load to a provider that is already failing.
+    pub async fn check_consistency(&self, kind: ConsistencyKind, id: &str) -> ConsistencyReport {
+        let koios = self.health(ProviderKind::Koios);
+        let blockfrost = self.health(ProviderKind::Blockfrost);
+        let report = match kind {
+            ConsistencyKind::Drep => {
+                let (from_koios, from_blockfrost) = futures::join!(
+                    Self::guarded_last(koios, self.koios.get_drep(id)),
+                    Self::guarded_last(blockfrost, self.blockfrost.get_drep(id)),
+                );
+                ConsistencyReport::compare(kind, id, from_koios, from_blockfrost)
             }
-            Ok(_) => {
-                // Empty result, try Blockfrost
+            ConsistencyKind::Action => {
+                let (from_koios, from_blockfrost) = futures::join!(
+                    Self::guarded_last(koios, self.koios.get_governance_action(id)),
+                    Self::guarded_last(blockfrost, self.blockfrost.get_governance_action(id)),
+                );
+                ConsistencyReport::compare(kind, id, from_koios, from_blockfrost)
             }
-            Err(e) => {
-                tracing::debug!(
-                    "Koios failed for DRep voting history: {}, falling back to Blockfrost",
-                    e
+            ConsistencyKind::Votes => {
+                let (from_koios, from_blockfrost) = futures::join!(
+                    Self::guarded_last(koios, self.koios.get_action_voting_results(id)),
+                    Self::guarded_last(blockfrost, self.blockfrost.get_action_voting_results(id)),
                 );
+                ConsistencyReport::compare(kind, id, found(from_koios), found(from_blockfrost))
             }
+        };
+        self.consistency.record(&report);
+        report
+    }
+
+    pub fn consistency_stats(&self) -> Vec<(ConsistencyKind, KindCounts)> {
+        self.consistency.snapshot()
+    }
+
+    // Runs a check for a sampled request in the background, off its response path
+    fn sample_consistency(&self, kind: ConsistencyKind, id: &str) {
+        if !self.consistency.sample() {
+            return;
         }
+        let router = self.clone();
+        let id = id.to_string();
+        tokio::spawn(async move {
+            router.check_consistency(kind, &id).await;
+        });
+    }
+
+    // Per-operation provider order lives in the routing policy (see routing.rs)
 
-        // Fallback to Blockfrost
-        self.blockfrost.get_drep_voting_history(id).await
+    pub async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
+        self.route(Operation::DrepsPage, |p| p.get_dreps_page(query))
+            .await
     }
 
-    pub async fn get_governance_actions_page(
-        &self,
-        page: u32,
-        count: u32,
-    ) -> Result<ActionsPage, anyhow::Error> {
-        // Try Koios first
-        match self.koios.get_governance_actions_page(page, count).await {
-            Ok(result) if !result.actions.is_empty() => {
-                tracing::debug!("Using Koios for governance actions page");
-                return Ok(result);
-            }
-            _ => {
-                tracing::debug!(
-                    "Koios failed, falling back to Blockfrost for governance actions page"
-                );
+    pub async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        self.sample_consistency(ConsistencyKind::Drep, id);
+        let mut drep = self.route(Operation::Drep, |p| p.get_drep(id)).await?;
+
+        // The ledger's stake distribution is authoritative for voting power
+        if let (Some(drep), Some((ogmios, health))) = (drep.as_mut(), self.ogmios()) {
+            match Self::guarded(health, ogmios.get_drep_voting_power(id)).await {
+                Some(Ok(Some(power))) => {
+                    drep.voting_power = Some(power.clone());
+                    drep.amount = Some(power);
+                }
+                Some(Ok(None)) | None => {}
+                Some(Err(error)) => {
+                    tracing::debug!("Ogmios voting power failed for {}: {}", id, error)
+                }
             }
         }
+        Ok(drep)
+    }
 
-        // Fallback to Blockfrost
-        self.blockfrost
-            .get_governance_actions_page(page, count)
+    pub async fn get_drep_delegators(&self, id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        self.route(Operation::DrepDelegators, |p| p.get_drep_delegators(id))
             .await
     }
 
-    pub async fn get_governance_action(
+    pub async fn get_drep_voting_history(
         &self,
         id: &str,
-    ) -> Result<Option<GovernanceAction>, anyhow::Error> {
-        // Try Koios first
-        match self.koios.get_governance_action(id).await {
-            Ok(Some(action)) => {
-                tracing::debug!("Using Koios for governance action");
-                return Ok(Some(action));
-            }
-            Ok(None) => {
-                tracing::debug!(
-                    "Koios returned None, falling back to Blockfrost for governance action"
-                );
-            }
-            Err(e) => {
-                tracing::debug!(
-                    "Koios failed for governance action: {}, falling back to Blockfrost",
-                    e
-                );
-            }
-        }
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        self.route(Operation::DrepVotingHistory, |p| {
+            p.get_drep_voting_history(id)
+        })
+        .await
+    }
 
-        // Fallback to Blockfrost
-        self.blockfrost.get_governance_action(id).await
+    pub async fn get_governance_actions_page(
+        &self,
+        page: u32,
+        count: u32,
+    ) -> Result<ActionsPage, ProviderError> {
+        self.route(Operation::GovernanceActionsPage, |p| {
+            p.get_governance_actions_page(page, count)
+        })
+        .await
     }
 
-    pub async fn get_action_voting_results(
+    pub async fn get_governance_action(
         &self,
         id: &str,
-    ) -> Result<ActionVotingBreakdown, anyhow::Error> {
-        // Try Koios first (specialized)
-        match self.koios.get_action_voting_results(id).await {
-            Ok(result) if result.total_voting_power != "0" => {
-                tracing::debug!("Using Koios for action voting results");
-                return Ok(result);
-            }
-            _ => {
-                tracing::debug!(
-                    "Koios failed, falling back to Blockfrost for action voting results"
-                );
-            }
-        }
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
+        self.sample_consistency(ConsistencyKind::Action, id);
+        self.route(Operation::GovernanceAction, |p| p.get_governance_action(id))
+            .await
+    }
 
-        // Fallback to Blockfrost
-        self.blockfrost.get_action_voting_results(id).await
+    pub async fn get_action_voting_results(
+        &self,
+        id: &str,
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
+        self.sample_consistency(ConsistencyKind::Votes, id);
+        self.route(Operation::ActionVotingResults, |p| {
+            p.get_action_voting_results(id)
+        })
+        .await
     }
 
     pub async fn get_drep_metadata(
         &self,
         id: &str,
-    ) -> Result<Option<serde_json::Value>, anyhow::Error> {
-        // Use Blockfrost (has metadata endpoint)
-        self.blockfrost.get_drep_metadata(id).await
+    ) -> Result<Option<serde_json::Value>, ProviderError> {
+        self.route(Operation::DrepMetadata, |p| p.get_drep_metadata(id))
+            .await
     }
 
-    pub async fn get_total_active_dreps(&self) -> Result<Option<u32>, anyhow::Error> {
-        // Use Koios epoch summary
-        self.koios.get_total_active_dreps().await
+    pub async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
+        self.route(Operation::TotalActiveDreps, |p| p.get_total_active_dreps())
+            .await
     }
 
     pub async fn get_stake_delegation(
         &self,
         stake_address: &str,
-    ) -> Result<Option<StakeDelegation>, anyhow::Error> {
-        // Try Koios first (specialized endpoint)
-        match self.koios.get_stake_delegation(stake_address).await {
-            Ok(Some(delegation)) => {
-                tracing::debug!("Using Koios for stake delegation");
-                return Ok(Some(delegation));
-            }
-            Ok(None) => {
-                tracing::debug!(
-                    "Koios returned None, falling back to Blockfrost for stake delegation"
-                );
-            }
-            Err(e) => {
-                tracing::debug!(
-                    "Koios failed for stake delegation: {}, falling back to Blockfrost",
-                    e
-                );
-            }
-        }
-
-        // Fallback to Blockfrost
-        self.blockfrost.get_stake_delegation(stake_address).await
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
+        self.route(Operation::StakeDelegation, |p| {
+            p.get_stake_delegation(stake_address)
+        })
+        .await
     }
 
     pub async fn get_stake_pools_page(
         &self,
         page: u32,
         count: u32,
-    ) -> Result<StakePoolPage, anyhow::Error> {
-        self.blockfrost.get_stake_pools_page(page, count).await
+    ) -> Result<StakePoolPage, ProviderError> {
+        self.route(Operation::StakePoolsPage, |p| {
+            p.get_stake_pools_page(page, count)
+        })
+        .await
     }
 
-    pub async fn get_committee_members(&self) -> Result<Vec<CommitteeMemberInfo>, anyhow::Error> {
-        self.koios.get_committee_info().await
+    pub async fn get_committee_members(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        self.route(Operation::CommitteeInfo, |p| p.get_committee_info())
+            .await
     }
 
+    /// Vote records behind an action's participation figures.
     pub async fn get_action_vote_records(
         &self,
         action: &GovernanceAction,
-    ) -> Result<Vec<ActionVoteRecord>, anyhow::Error> {
-        if let Some(cert_index) = action.cert_index {
-            match self
-                .blockfrost
-                .get_action_vote_records(&action.tx_hash, cert_index)
-                .await
-            {
-                Ok(records) if !records.is_empty() => return Ok(records),
-                Ok(_) => {}
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        self.route(Operation::ActionVoteRecords, |p| {
+            p.get_action_vote_records(action)
+        })
+        .await
+    }
+
+    pub async fn get_chain_tip(&self) -> Result<Option<ChainTip>, ProviderError> {
+        Self::guarded_last(self.health(ProviderKind::Koios), self.koios.get_tip()).await
+    }
+
+    pub async fn get_votes_since(
+        &self,
+        block_height: u64,
+        limit: u32,
+    ) -> Result<Vec<RecentVote>, ProviderError> {
+        Self::guarded_last(
+            self.health(ProviderKind::Koios),
+            self.koios.get_votes_since(block_height, limit),
+        )
+        .await
+    }
+
+    /// Governance figures from the ledger state; `None` without Ogmios.
+    pub async fn get_ledger_state(&self) -> Result<Option<LedgerState>, ProviderError> {
+        let Some((ogmios, health)) = self.ogmios() else {
+            return Ok(None);
+        };
+        Self::guarded_last(health, ogmios.get_ledger_state())
+            .await
+            .map(Some)
+    }
+
+    pub async fn health_check(&self) -> Result<bool, ProviderError> {
+        let blockfrost_ok = self.blockfrost.health_check().await.unwrap_or(false);
+        let koios_ok = self.koios.health_check().await.unwrap_or(false);
+        if let Some(ogmios) = &self.ogmios {
+            if !ogmios.health_check().await.unwrap_or(false) {
+                return Ok(false);
+            }
+        }
+        #[cfg(feature = "dbsync")]
+        if let Some(dbsync) = &self.dbsync {
+            let dbsync_ok = dbsync.health_check().await.unwrap_or(false);
+            return Ok(blockfrost_ok && koios_ok && dbsync_ok);
+        }
+        Ok(blockfrost_ok && koios_ok)
+    }
+
+    /// Compares the protocol magic each provider reports with the configured
+    /// network. A provider that can't be reached is only logged, so a transient
+    /// outage doesn't block startup; one serving another network is an error.
+    pub async fn check_network(&self, network: &Network) -> Result<(), anyhow::Error> {
+        let Some(expected) = network.magic() else {
+            tracing::info!(
+                "Skipping provider network check for custom network {}",
+                network
+            );
+            return Ok(());
+        };
+
+        let (blockfrost, koios) = futures::join!(
+            self.blockfrost.get_network_magic(),
+            self.koios.get_network_magic()
+        );
+        for (name, result) in [("blockfrost", blockfrost), ("koios", koios)] {
+            match result {
+                Ok(Some(magic)) if magic != expected => {
+                    return Err(anyhow::anyhow!(
+                        "{} reports network magic {} but {} is {}",
+                        name,
+                        magic,
+                        network,
+                        expected
+                    ));
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => tracing::warn!("{} did not report a network magic", name),
                 Err(error) => {
-                    tracing::debug!(
-                        "Blockfrost vote records failed for {}: {}",
-                        action.action_id,
-                        error
-                    );
+                    tracing::warn!("Could not check {} network: {}", name, error)
                 }
             }
         }
 
-        let proposal_id = action
-            .proposal_id
-            .as_deref()
-            .unwrap_or_else(|| action.action_id.as_str());
-
-        match self.koios.get_action_vote_records(proposal_id).await {
-            Ok(records) => Ok(records),
-            Err(error) => {
-                tracing::debug!(
-                    "Koios vote records failed for {}: {}",
-                    action.action_id,
-                    error
-                );
-                Ok(Vec::new())
+        if let Some(ogmios) = &self.ogmios {
+            match ogmios.get_network_magic().await {
+                Ok(Some(magic)) if magic != expected => {
+                    return Err(anyhow::anyhow!(
+                        "ogmios reports network magic {} but {} is {}",
+                        magic,
+                        network,
+                        expected
+                    ));
+                }
+                Ok(_) => {}
+                Err(error) => tracing::warn!("Could not check ogmios network: {}", error),
+            }
+        }
+
+        // db-sync records the network name it was started with
+        #[cfg(feature = "dbsync")]
+        if let Some(dbsync) = &self.dbsync {
+            match dbsync.get_network_name().await {
+                Ok(Some(name)) if !name.eq_ignore_ascii_case(network.as_str()) => {
+                    return Err(anyhow::anyhow!(
+                        "db-sync database follows {} but is configured for {}",
+                        name,
+                        network
+                    ));
+                }
+                Ok(_) => {}
+                Err(error) => tracing::warn!("Could not check db-sync network: {}", error),
             }
         }
+        Ok(())
     }
 
-    pub async fn health_check(&self) -> Result<bool, anyhow::Error> {
-        let blockfrost_ok = self.blockfrost.health_check().await.unwrap_or(false);
-        let koios_ok = self.koios.health_check().await.unwrap_or(false);
-        Ok(blockfrost_ok && koios_ok)
+    pub async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        self.route(Operation::EpochStartTime, |p| p.get_epoch_start_time(epoch))
+            .await
+    }
+}
+
+#[cfg(test)]
+impl ProviderRouter {
+    /// A router that sends every route to `stubs` rather than Koios and
+    /// Blockfrost.
+    pub(crate) fn stubbed(
+        policy: RoutingPolicy,
+        stubs: &[(ProviderKind, Arc<crate::providers::testing::StubProvider>)],
+    ) -> Self {
+        use crate::providers::{BlockfrostProvider, KoiosProvider, RetryConfig, RetryPolicy};
+
+        // Only Koios-only calls such as the chain tip reach these, and there is
+        // nothing listening to retry
+        let retry = || {
+            RetryPolicy::new(RetryConfig {
+                max_retries: 0,
+                ..RetryConfig::default()
+            })
+        };
+        let mut router = ProviderRouter::new(
+            Arc::new(BlockfrostProvider::new(
+                "http://127.0.0.1:9".to_string(),
+                String::new(),
+                retry(),
+            )),
+            Arc::new(KoiosProvider::new(
+                "http://127.0.0.1:9".to_string(),
+                None,
+                retry(),
+            )),
+            BreakerConfig::default(),
+            policy,
+        );
+        router.providers = Arc::new(
+            stubs
+                .iter()
+                .map(|(kind, stub)| RoutedProvider {
+                    kind: *kind,
+                    provider: stub.clone(),
+                    health: Arc::new(ProviderHealth::new(kind.as_str(), BreakerConfig::default())),
+                })
+                .collect(),
+        );
+        router
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::testing::StubProvider;
+    use serde_json::json;
+    use tokio::time::Instant;
+
+    const ACTION: &str = "gov_action1test";
+    const HEDGE_MS: u64 = 100;
+
+    fn action(tx_hash: &str) -> serde_json::Value {
+        json!({ "tx_hash": tx_hash, "action_id": ACTION, "type": "info" })
+    }
+
+    fn answering(tx_hash: &str, delay_ms: u64) -> Arc<StubProvider> {
+        Arc::new(
+            StubProvider::new()
+                .slow(Duration::from_millis(delay_ms))
+                .answer(ACTION, action(tx_hash)),
+        )
+    }
+
+    fn failing(delay_ms: u64) -> Arc<StubProvider> {
+        Arc::new(
+            StubProvider::new()
+                .slow(Duration::from_millis(delay_ms))
+                .failing(),
+        )
+    }
+
+    // Routes governance actions through `stubs` in order, hedged after a
+    // fixed HEDGE_MS when `hedge` is set
+    fn router(hedge: bool, stubs: &[(ProviderKind, Arc<StubProvider>)]) -> ProviderRouter {
+        let mut route = json!({
+            "providers": stubs.iter().map(|(kind, _)| kind).collect::<Vec<_>>(),
+            "fallback_on": ["error", "none"],
+        });
+        if hedge {
+            route["hedge"] = json!({ "min_delay_ms": HEDGE_MS, "max_delay_ms": HEDGE_MS });
+        }
+        let policy = RoutingPolicy::from_json(
+            &json!({ "routes": { "get_governance_action": route } }).to_string(),
+        )
+        .unwrap();
+
+        ProviderRouter::stubbed(policy, stubs)
     }
 
-    pub async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, anyhow::Error> {
-        self.blockfrost.get_epoch_start_time(epoch).await
+    async fn lookup(router: &ProviderRouter) -> (Result<String, ProviderError>, Duration) {
+        let started = Instant::now();
+        let result = router
+            .route(Operation::GovernanceAction, |p| {
+                p.get_governance_action(ACTION)
+            })
+            .await
+            .map(|action| action.unwrap().tx_hash);
+        (result, started.elapsed())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedge_waits_for_the_delay() {
+        let koios = answering("koios", 60);
+        let blockfrost = answering("blockfrost", 10);
+        let router = router(
+            true,
+            &[
+                (ProviderKind::Koios, koios.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+            ],
+        );
+
+        let (result, elapsed) = lookup(&router).await;
+        assert_eq!(result.unwrap(), "koios");
+        assert_eq!(elapsed, Duration::from_millis(60));
+        assert_eq!(blockfrost.calls(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slower_request_is_dropped() {
+        let koios = answering("koios", 500);
+        let blockfrost = answering("blockfrost", 10);
+        let router = router(
+            true,
+            &[
+                (ProviderKind::Koios, koios.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+            ],
+        );
+
+        let (result, elapsed) = lookup(&router).await;
+        assert_eq!(result.unwrap(), "blockfrost");
+        assert_eq!(elapsed, Duration::from_millis(HEDGE_MS + 10));
+        // Koios was asked, then cancelled when Blockfrost won
+        assert_eq!(koios.calls(), 1);
+        assert_eq!(koios.finished(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn first_failure_waits_for_the_other_request() {
+        let koios = answering("koios", 300);
+        let blockfrost = failing(10);
+        let router = router(
+            true,
+            &[
+                (ProviderKind::Koios, koios.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+            ],
+        );
+
+        let (result, elapsed) = lookup(&router).await;
+        assert_eq!(result.unwrap(), "koios");
+        assert_eq!(elapsed, Duration::from_millis(300));
+        assert_eq!(blockfrost.finished(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn each_provider_hedges_the_next() {
+        let koios = answering("koios", 1000);
+        let blockfrost = answering("blockfrost", 1000);
+        let ogmios = answering("ogmios", 10);
+        let router = router(
+            true,
+            &[
+                (ProviderKind::Koios, koios.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+                (ProviderKind::Ogmios, ogmios.clone()),
+            ],
+        );
+
+        // Blockfrost joins after one delay and Ogmios after another, without
+        // waiting for either of the first two to fail
+        let (result, elapsed) = lookup(&router).await;
+        assert_eq!(result.unwrap(), "ogmios");
+        assert_eq!(elapsed, Duration::from_millis(2 * HEDGE_MS + 10));
+        assert_eq!(koios.finished() + blockfrost.finished(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failure_with_nothing_in_flight_moves_on_at_once() {
+        let koios = failing(10);
+        let blockfrost = answering("blockfrost", 10);
+        let router = router(
+            true,
+            &[
+                (ProviderKind::Koios, koios.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+            ],
+        );
+
+        let (result, elapsed) = lookup(&router).await;
+        assert_eq!(result.unwrap(), "blockfrost");
+        assert_eq!(elapsed, Duration::from_millis(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unhedged_routes_run_one_provider_at_a_time() {
+        // Koios has no such action, which falls back like an error
+        let missing = Arc::new(StubProvider::new().slow(Duration::from_millis(500)));
+        let blockfrost = answering("blockfrost", 10);
+        let router = router(
+            false,
+            &[
+                (ProviderKind::Koios, missing.clone()),
+                (ProviderKind::Blockfrost, blockfrost.clone()),
+            ],
+        );
+
+        let (result, elapsed) = lookup(&router).await;
+        assert_eq!(result.unwrap(), "blockfrost");
+        assert_eq!(elapsed, Duration::from_millis(510));
+        assert_eq!(missing.finished(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn every_failure_returns_the_last_error() {
+        let router = router(
+            true,
+            &[
+                (ProviderKind::Koios, failing(300)),
+                (ProviderKind::Blockfrost, failing(10)),
+            ],
+        );
+
+        let (result, elapsed) = lookup(&router).await;
+        assert!(matches!(result, Err(ProviderError::Unavailable(_))));
+        assert_eq!(elapsed, Duration::from_millis(300));
     }
 }
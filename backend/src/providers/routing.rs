@@ -0,0 +1,681 @@
+use crate::models::*;
+use crate::providers::ProviderError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Koios,
+    Blockfrost,
+    /// Self-hosted cardano-db-sync database (`dbsync` feature)
+    DbSync,
+    /// Cardano node ledger state through Ogmios
+    Ogmios,
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Koios => "koios",
+            ProviderKind::Blockfrost => "blockfrost",
+            ProviderKind::DbSync => "db_sync",
+            ProviderKind::Ogmios => "ogmios",
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Provider operations that are routed through the policy table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Operation {
+    #[serde(rename = "get_dreps_page")]
+    DrepsPage,
+    #[serde(rename = "get_drep")]
+    Drep,
+    #[serde(rename = "get_drep_delegators")]
+    DrepDelegators,
+    #[serde(rename = "get_drep_voting_history")]
+    DrepVotingHistory,
+    #[serde(rename = "get_governance_actions_page")]
+    GovernanceActionsPage,
+    #[serde(rename = "get_governance_action")]
+    GovernanceAction,
+    #[serde(rename = "get_action_voting_results")]
+    ActionVotingResults,
+    #[serde(rename = "get_drep_metadata")]
+    DrepMetadata,
+    #[serde(rename = "get_total_active_dreps")]
+    TotalActiveDreps,
+    #[serde(rename = "get_stake_delegation")]
+    StakeDelegation,
+    #[serde(rename = "get_stake_pools_page")]
+    StakePoolsPage,
+    #[serde(rename = "get_committee_info")]
+    CommitteeInfo,
+    #[serde(rename = "get_epoch_start_time")]
+    EpochStartTime,
+    #[serde(rename = "get_action_vote_records")]
+    ActionVoteRecords,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::DrepsPage => "get_dreps_page",
+            Operation::Drep => "get_drep",
+            Operation::DrepDelegators => "get_drep_delegators",
+            Operation::DrepVotingHistory => "get_drep_voting_history",
+            Operation::GovernanceActionsPage => "get_governance_actions_page",
+            Operation::GovernanceAction => "get_governance_action",
+            Operation::ActionVotingResults => "get_action_voting_results",
+            Operation::DrepMetadata => "get_drep_metadata",
+            Operation::TotalActiveDreps => "get_total_active_dreps",
+            Operation::StakeDelegation => "get_stake_delegation",
+            Operation::StakePoolsPage => "get_stake_pools_page",
+            Operation::CommitteeInfo => "get_committee_info",
+            Operation::EpochStartTime => "get_epoch_start_time",
+            Operation::ActionVoteRecords => "get_action_vote_records",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Provider result that moves a request on to the next provider in its route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackCondition {
+    /// The provider returned an error (including timeouts)
+    Error,
+    /// The provider returned an empty list/page or an all-zero tally
+    Empty,
+    /// The provider returned no result for a single-entity lookup
+    #[serde(rename = "none")]
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "ProviderRouteRepr")]
+pub struct ProviderRoute {
+    pub provider: ProviderKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl ProviderRoute {
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+}
+
+// Routes may list providers by name or as `{ "provider": ..., "timeout_ms": ... }`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProviderRouteRepr {
+    Name(ProviderKind),
+    Full {
+        provider: ProviderKind,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
+}
+
+impl From<ProviderRouteRepr> for ProviderRoute {
+    fn from(repr: ProviderRouteRepr) -> Self {
+        match repr {
+            ProviderRouteRepr::Name(provider) => ProviderRoute {
+                provider,
+                timeout_ms: None,
+            },
+            ProviderRouteRepr::Full {
+                provider,
+                timeout_ms,
+            } => ProviderRoute {
+                provider,
+                timeout_ms,
+            },
+        }
+    }
+}
+
+/// Races the next provider in the route once the current one is slower than usual.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgeConfig {
+    /// Latency percentile (0.0 - 1.0) of the primary after which the secondary is fired
+    #[serde(default = "default_hedge_percentile")]
+    pub percentile: f64,
+    /// Lower bound on the hedge delay, so a fast provider isn't hedged on every request
+    #[serde(default = "default_hedge_min_delay_ms")]
+    pub min_delay_ms: u64,
+    /// Upper bound on the hedge delay, also used while there are no latency samples yet
+    #[serde(default = "default_hedge_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_hedge_percentile() -> f64 {
+    0.95
+}
+
+fn default_hedge_min_delay_ms() -> u64 {
+    50
+}
+
+fn default_hedge_max_delay_ms() -> u64 {
+    2000
+}
+
+impl HedgeConfig {
+    /// Delay before hedging, given the primary's observed latency percentile.
+    pub fn delay(&self, observed: Option<Duration>) -> Duration {
+        let min = Duration::from_millis(self.min_delay_ms);
+        let max = Duration::from_millis(self.max_delay_ms);
+        observed.unwrap_or(max).clamp(min, max)
+    }
+
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if !(0.0..=1.0).contains(&self.percentile) {
+            return Err(anyhow::anyhow!(
+                "hedge percentile must be between 0.0 and 1.0"
+            ));
+        }
+        if self.min_delay_ms > self.max_delay_ms {
+            return Err(anyhow::anyhow!(
+                "hedge min_delay_ms must not exceed max_delay_ms"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteConfig {
+    pub providers: Vec<ProviderRoute>,
+    #[serde(default = "default_fallback_on")]
+    pub fallback_on: Vec<FallbackCondition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hedge: Option<HedgeConfig>,
+}
+
+fn default_fallback_on() -> Vec<FallbackCondition> {
+    vec![FallbackCondition::Error]
+}
+
+impl RouteConfig {
+    fn new(providers: &[ProviderKind], fallback_on: &[FallbackCondition]) -> Self {
+        Self {
+            providers: providers
+                .iter()
+                .map(|provider| ProviderRoute {
+                    provider: *provider,
+                    timeout_ms: None,
+                })
+                .collect(),
+            fallback_on: fallback_on.to_vec(),
+            hedge: None,
+        }
+    }
+
+    /// Whether `result` should move the request on to the next provider.
+    pub fn should_fall_back<T: RouteResult>(&self, result: &Result<T, ProviderError>) -> bool {
+        match result {
+            Err(_) => self.fallback_on.contains(&FallbackCondition::Error),
+            Ok(value) if value.is_none_result() => {
+                self.fallback_on.contains(&FallbackCondition::Missing)
+            }
+            Ok(value) if value.is_empty_result() => {
+                self.fallback_on.contains(&FallbackCondition::Empty)
+            }
+            Ok(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RoutingFile {
+    #[serde(default)]
+    routes: HashMap<Operation, RouteConfig>,
+}
+
+/// Ordered provider list, fallback condition and timeouts for each operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingPolicy {
+    routes: HashMap<Operation, RouteConfig>,
+    /// Operations whose route was set in the routing file
+    #[serde(skip)]
+    configured: HashSet<Operation>,
+}
+
+impl Default for RoutingPolicy {
+    fn default() -> Self {
+        use FallbackCondition::{Empty, Error, Missing};
+        use ProviderKind::{Blockfrost, Koios};
+
+        let routes = [
+            // Koios first (faster bulk queries)
+            (
+                Operation::DrepsPage,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Empty]),
+            ),
+            // Blockfrost has more complete DRep metadata
+            (Operation::Drep, RouteConfig::new(&[Blockfrost], &[Error])),
+            // Koios has specialized endpoints for delegators and votes
+            (
+                Operation::DrepDelegators,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Empty]),
+            ),
+            (
+                Operation::DrepVotingHistory,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Empty]),
+            ),
+            (
+                Operation::GovernanceActionsPage,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Empty]),
+            ),
+            (
+                Operation::GovernanceAction,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Missing]),
+            ),
+            (
+                Operation::ActionVotingResults,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Empty]),
+            ),
+            // Only Blockfrost has a DRep metadata endpoint
+            (
+                Operation::DrepMetadata,
+                RouteConfig::new(&[Blockfrost], &[Error]),
+            ),
+            // Koios epoch summary
+            (
+                Operation::TotalActiveDreps,
+                RouteConfig::new(&[Koios], &[Error]),
+            ),
+            (
+                Operation::StakeDelegation,
+                RouteConfig::new(&[Koios, Blockfrost], &[Error, Missing]),
+            ),
+            // Blockfrost's extended pool list carries names and descriptions
+            (
+                Operation::StakePoolsPage,
+                RouteConfig::new(&[Blockfrost, Koios], &[Error, Empty]),
+            ),
+            // Only Koios has a committee endpoint; falling back on empty lets
+            // providers put in front of it (Ogmios, db-sync) defer to it
+            (
+                Operation::CommitteeInfo,
+                RouteConfig::new(&[Koios], &[Error, Empty]),
+            ),
+            (
+                Operation::EpochStartTime,
+                RouteConfig::new(&[Blockfrost, Koios], &[Error, Missing]),
+            ),
+            // Blockfrost votes carry the voting transaction; Koios covers
+            // actions without a certificate index
+            (
+                Operation::ActionVoteRecords,
+                RouteConfig::new(&[Blockfrost, Koios], &[Error, Empty]),
+            ),
+        ];
+
+        Self {
+            routes: routes.into_iter().collect(),
+            configured: HashSet::new(),
+        }
+    }
+}
+
+impl RoutingPolicy {
+    /// Loads the routing table from a JSON file. Operations missing from the file
+    /// keep their default route.
+    pub fn load(path: Option<&str>) -> Result<Self, anyhow::Error> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read routing config {}: {}", path, e))?;
+        let policy = Self::from_json(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid routing config {}: {}", path, e))?;
+        tracing::info!("Loaded provider routing policy from {}", path);
+        Ok(policy)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self, anyhow::Error> {
+        let file: RoutingFile = serde_json::from_str(contents)?;
+        let mut policy = Self::default();
+
+        for (operation, route) in file.routes {
+            if route.providers.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "route for {} must list at least one provider",
+                    operation
+                ));
+            }
+            if let Some(hedge) = &route.hedge {
+                hedge
+                    .validate()
+                    .map_err(|e| anyhow::anyhow!("route for {}: {}", operation, e))?;
+            }
+            policy.routes.insert(operation, route);
+            policy.configured.insert(operation);
+        }
+
+        Ok(policy)
+    }
+
+    /// Puts `provider` first on every route that wasn't set in the routing
+    /// file. Configured routes are left as they are.
+    #[cfg_attr(not(feature = "dbsync"), allow(dead_code))]
+    pub fn prefer(mut self, provider: ProviderKind) -> Self {
+        for (operation, route) in self.routes.iter_mut() {
+            if self.configured.contains(operation) {
+                continue;
+            }
+            route.providers.insert(
+                0,
+                ProviderRoute {
+                    provider,
+                    timeout_ms: None,
+                },
+            );
+        }
+        self
+    }
+
+    /// Puts `provider` first on the routes for `operations`, skipping routes
+    /// set in the routing file.
+    pub fn prefer_for(mut self, provider: ProviderKind, operations: &[Operation]) -> Self {
+        for operation in operations {
+            if self.configured.contains(operation) {
+                continue;
+            }
+            if let Some(route) = self.routes.get_mut(operation) {
+                route.providers.insert(
+                    0,
+                    ProviderRoute {
+                        provider,
+                        timeout_ms: None,
+                    },
+                );
+            }
+        }
+        self
+    }
+
+    /// Adds `provider` as the last fallback for `operations`, skipping routes
+    /// set in the routing file.
+    pub fn fall_back_to(mut self, provider: ProviderKind, operations: &[Operation]) -> Self {
+        for operation in operations {
+            if self.configured.contains(operation) {
+                continue;
+            }
+            if let Some(route) = self.routes.get_mut(operation) {
+                route.providers.push(ProviderRoute {
+                    provider,
+                    timeout_ms: None,
+                });
+            }
+        }
+        self
+    }
+
+    pub fn route(&self, operation: Operation) -> &RouteConfig {
+        // Every operation has a default route, so this lookup cannot miss
+        &self.routes[&operation]
+    }
+}
+
+/// Classifies provider results for the `empty` and `none` fallback conditions.
+pub trait RouteResult {
+    fn is_empty_result(&self) -> bool {
+        false
+    }
+
+    fn is_none_result(&self) -> bool {
+        false
+    }
+}
+
+impl<T> RouteResult for Option<T> {
+    fn is_none_result(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T> RouteResult for Vec<T> {
+    fn is_empty_result(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl RouteResult for DRepsPage {
+    fn is_empty_result(&self) -> bool {
+        self.dreps.is_empty()
+    }
+}
+
+impl RouteResult for StakePoolPage {
+    fn is_empty_result(&self) -> bool {
+        self.pools.is_empty()
+    }
+}
+
+impl RouteResult for ActionsPage {
+    fn is_empty_result(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+impl RouteResult for ActionVotingBreakdown {
+    fn is_empty_result(&self) -> bool {
+        self.total_voting_power == "0"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use FallbackCondition::{Empty, Error, Missing};
+    use ProviderKind::{Blockfrost, DbSync, Koios, Ogmios};
+
+    fn providers(policy: &RoutingPolicy, operation: Operation) -> Vec<ProviderKind> {
+        policy
+            .route(operation)
+            .providers
+            .iter()
+            .map(|route| route.provider)
+            .collect()
+    }
+
+    #[test]
+    fn default_routes_match_the_previous_hard_coded_order() {
+        let policy = RoutingPolicy::default();
+        let expected = [
+            (
+                Operation::DrepsPage,
+                vec![Koios, Blockfrost],
+                vec![Error, Empty],
+            ),
+            (Operation::Drep, vec![Blockfrost], vec![Error]),
+            (
+                Operation::DrepDelegators,
+                vec![Koios, Blockfrost],
+                vec![Error, Empty],
+            ),
+            (
+                Operation::DrepVotingHistory,
+                vec![Koios, Blockfrost],
+                vec![Error, Empty],
+            ),
+            (
+                Operation::GovernanceActionsPage,
+                vec![Koios, Blockfrost],
+                vec![Error, Empty],
+            ),
+            (
+                Operation::GovernanceAction,
+                vec![Koios, Blockfrost],
+                vec![Error, Missing],
+            ),
+            (
+                Operation::ActionVotingResults,
+                vec![Koios, Blockfrost],
+                vec![Error, Empty],
+            ),
+            (Operation::DrepMetadata, vec![Blockfrost], vec![Error]),
+            (Operation::TotalActiveDreps, vec![Koios], vec![Error]),
+            (
+                Operation::StakeDelegation,
+                vec![Koios, Blockfrost],
+                vec![Error, Missing],
+            ),
+            // Previously Blockfrost or Koios only; the other provider was added as a fallback
+            (
+                Operation::StakePoolsPage,
+                vec![Blockfrost, Koios],
+                vec![Error, Empty],
+            ),
+            (Operation::CommitteeInfo, vec![Koios], vec![Error, Empty]),
+            (
+                Operation::EpochStartTime,
+                vec![Blockfrost, Koios],
+                vec![Error, Missing],
+            ),
+            (
+                Operation::ActionVoteRecords,
+                vec![Blockfrost, Koios],
+                vec![Error, Empty],
+            ),
+        ];
+
+        assert_eq!(policy.routes.len(), expected.len());
+        for (operation, order, fallback_on) in expected {
+            let route = policy.route(operation);
+            assert_eq!(providers(&policy, operation), order, "{}", operation);
+            assert_eq!(route.fallback_on, fallback_on, "{}", operation);
+            assert!(route.hedge.is_none(), "{}", operation);
+            assert!(
+                route.providers.iter().all(|p| p.timeout().is_none()),
+                "{}",
+                operation
+            );
+        }
+    }
+
+    #[test]
+    fn parses_the_example_routing_file() {
+        let policy = RoutingPolicy::from_json(include_str!("../../routing.example.json")).unwrap();
+
+        assert_eq!(
+            providers(&policy, Operation::DrepsPage),
+            [Blockfrost, Koios]
+        );
+        assert_eq!(providers(&policy, Operation::Drep), [Blockfrost, Koios]);
+        assert_eq!(policy.route(Operation::Drep).fallback_on, [Error, Missing]);
+        let hedge = policy.route(Operation::Drep).hedge.as_ref().unwrap();
+        assert_eq!(hedge.min_delay_ms, 100);
+        assert_eq!(hedge.max_delay_ms, 1500);
+
+        // Hedge fields left out take their defaults
+        let hedge = policy
+            .route(Operation::GovernanceAction)
+            .hedge
+            .as_ref()
+            .unwrap();
+        assert_eq!(hedge.percentile, 0.9);
+        assert_eq!(hedge.min_delay_ms, default_hedge_min_delay_ms());
+        assert_eq!(hedge.max_delay_ms, default_hedge_max_delay_ms());
+
+        assert_eq!(
+            providers(&policy, Operation::StakePoolsPage),
+            [Koios, Blockfrost]
+        );
+        // Operations missing from the file keep their default route
+        assert_eq!(providers(&policy, Operation::DrepMetadata), [Blockfrost]);
+    }
+
+    #[test]
+    fn reads_per_provider_timeouts() {
+        let policy = RoutingPolicy::from_json(
+            r#"{"routes": {"get_stake_delegation": {"providers": [
+                {"provider": "koios", "timeout_ms": 3000},
+                "blockfrost",
+                {"provider": "ogmios"}
+            ]}}}"#,
+        )
+        .unwrap();
+
+        let route = policy.route(Operation::StakeDelegation);
+        let timeouts: Vec<_> = route.providers.iter().map(|p| p.timeout()).collect();
+        assert_eq!(timeouts, [Some(Duration::from_millis(3000)), None, None]);
+        // fallback_on defaults to errors only
+        assert_eq!(route.fallback_on, [Error]);
+    }
+
+    #[test]
+    fn rejects_unknown_operations() {
+        let err =
+            RoutingPolicy::from_json(r#"{"routes": {"get_everything": {"providers": ["koios"]}}}"#)
+                .unwrap_err();
+        assert!(err.to_string().contains("get_everything"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unknown_providers() {
+        assert!(RoutingPolicy::from_json(
+            r#"{"routes": {"get_drep": {"providers": ["maestro"]}}}"#
+        )
+        .is_err());
+        assert!(RoutingPolicy::from_json(
+            r#"{"routes": {"get_drep": {"providers": [{"provider": "maestro", "timeout_ms": 10}]}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_empty_routes_and_bad_hedges() {
+        assert!(
+            RoutingPolicy::from_json(r#"{"routes": {"get_drep": {"providers": []}}}"#).is_err()
+        );
+        assert!(RoutingPolicy::from_json(
+            r#"{"routes": {"get_drep": {"providers": ["koios"], "hedge": {"percentile": 1.5}}}}"#
+        )
+        .is_err());
+        assert!(RoutingPolicy::from_json(
+            r#"{"routes": {"get_drep": {"providers": ["koios"],
+                "hedge": {"min_delay_ms": 500, "max_delay_ms": 100}}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn preferences_leave_configured_routes_alone() {
+        let policy =
+            RoutingPolicy::from_json(r#"{"routes": {"get_drep": {"providers": ["koios"]}}}"#)
+                .unwrap()
+                .prefer(DbSync)
+                .prefer_for(Ogmios, &[Operation::CommitteeInfo, Operation::Drep])
+                .fall_back_to(Ogmios, &[Operation::StakeDelegation]);
+
+        assert_eq!(providers(&policy, Operation::Drep), [Koios]);
+        assert_eq!(
+            providers(&policy, Operation::CommitteeInfo),
+            [Ogmios, DbSync, Koios]
+        );
+        assert_eq!(
+            providers(&policy, Operation::StakeDelegation),
+            [DbSync, Koios, Blockfrost, Ogmios]
+        );
+    }
+}
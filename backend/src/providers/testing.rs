@@ -0,0 +1,182 @@
+//! Scripted `Provider` for tests that route requests through `ProviderRouter`.
+
+use crate::models::*;
+use crate::providers::{Provider, ProviderError};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Answers lookups from JSON keyed by id, after `delay` of (tokio) time. List
+/// pages and other lookups are keyed as `dreps_page:<page>`, `votes:<id>` and
+/// so on; unscripted ones come back empty. DRep delegators, voting histories,
+/// metadata and stake delegation panic.
+#[derive(Default)]
+pub struct StubProvider {
+    delay: Duration,
+    error: Option<ProviderError>,
+    answers: HashMap<String, Value>,
+    calls: AtomicUsize,
+    finished: AtomicUsize,
+}
+
+impl StubProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn slow(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Every call fails with `Unavailable` once the delay has passed.
+    pub fn failing(self) -> Self {
+        self.failing_with(ProviderError::Unavailable("stub".to_string()))
+    }
+
+    /// Every call fails with `error` once the delay has passed.
+    pub fn failing_with(mut self, error: ProviderError) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn answer(mut self, id: &str, value: Value) -> Self {
+        self.answers.insert(id.to_string(), value);
+        self
+    }
+
+    /// Calls started.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Calls that ran to the end rather than being dropped.
+    pub fn finished(&self) -> usize {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    async fn lookup<T: DeserializeOwned>(&self, id: &str) -> Result<Option<T>, ProviderError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        self.finished.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        Ok(self
+            .answers
+            .get(id)
+            .map(|value| serde_json::from_value(value.clone()).unwrap()))
+    }
+
+    // A scripted list or aggregate, or `empty` when none was scripted
+    async fn listed<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        empty: Value,
+    ) -> Result<T, ProviderError> {
+        match self.lookup(key).await? {
+            Some(value) => Ok(value),
+            None => Ok(serde_json::from_value(empty).unwrap()),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for StubProvider {
+    async fn get_dreps_page(&self, query: &DRepsQuery) -> Result<DRepsPage, ProviderError> {
+        let key = format!("dreps_page:{}", query.page);
+        self.listed(&key, json!({ "dreps": [], "has_more": false }))
+            .await
+    }
+
+    async fn get_drep(&self, id: &str) -> Result<Option<DRep>, ProviderError> {
+        self.lookup(id).await
+    }
+
+    async fn get_drep_delegators(&self, _id: &str) -> Result<Vec<DRepDelegator>, ProviderError> {
+        unimplemented!("not scripted")
+    }
+
+    async fn get_drep_voting_history(
+        &self,
+        _id: &str,
+    ) -> Result<Vec<DRepVotingHistory>, ProviderError> {
+        unimplemented!("not scripted")
+    }
+
+    async fn get_governance_actions_page(
+        &self,
+        page: u32,
+        _count: u32,
+    ) -> Result<ActionsPage, ProviderError> {
+        let key = format!("actions_page:{}", page);
+        self.listed(&key, json!({ "actions": [], "has_more": false }))
+            .await
+    }
+
+    async fn get_governance_action(
+        &self,
+        id: &str,
+    ) -> Result<Option<GovernanceAction>, ProviderError> {
+        self.lookup(id).await
+    }
+
+    async fn get_action_voting_results(
+        &self,
+        id: &str,
+    ) -> Result<ActionVotingBreakdown, ProviderError> {
+        Ok(self
+            .lookup(&format!("votes:{}", id))
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn get_drep_metadata(&self, _id: &str) -> Result<Option<Value>, ProviderError> {
+        unimplemented!("not scripted")
+    }
+
+    async fn get_total_active_dreps(&self) -> Result<Option<u32>, ProviderError> {
+        self.lookup("total_active_dreps").await
+    }
+
+    async fn get_stake_delegation(
+        &self,
+        _stake_address: &str,
+    ) -> Result<Option<StakeDelegation>, ProviderError> {
+        unimplemented!("not scripted")
+    }
+
+    async fn get_stake_pools_page(
+        &self,
+        page: u32,
+        _count: u32,
+    ) -> Result<StakePoolPage, ProviderError> {
+        let key = format!("pools_page:{}", page);
+        self.listed(&key, json!({ "pools": [], "has_more": false }))
+            .await
+    }
+
+    async fn get_committee_info(&self) -> Result<Vec<CommitteeMemberInfo>, ProviderError> {
+        self.listed("committee", json!([])).await
+    }
+
+    async fn get_epoch_start_time(&self, epoch: u32) -> Result<Option<u64>, ProviderError> {
+        self.lookup(&format!("epoch_start:{}", epoch)).await
+    }
+
+    async fn get_action_vote_records(
+        &self,
+        action: &GovernanceAction,
+    ) -> Result<Vec<ActionVoteRecord>, ProviderError> {
+        let key = format!("vote_records:{}", action.action_id);
+        self.listed(&key, json!([])).await
+    }
+
+    async fn health_check(&self) -> Result<bool, ProviderError> {
+        Ok(self.error.is_none())
+    }
+}
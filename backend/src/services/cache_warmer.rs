@@ -0,0 +1,418 @@
+use crate::models::{DRepsQuery, GovernanceAction};
+use crate::providers::{CachedProviderRouter, ProviderError};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+// Page size the list endpoints default to, so warmed pages match real requests
+const PAGE_SIZE: u32 = 20;
+// Per-action fetches run side by side; participation alone walks dozens of pages
+const ACTION_CONCURRENCY: usize = 4;
+// Hard stop for the action list walk, should a provider never stop reporting
+// `has_more`
+const MAX_ACTION_PAGES: u32 = 50;
+
+#[derive(Debug, Clone)]
+pub struct WarmerConfig {
+    /// Leading pages of the DRep list to load. Action pages are loaded up to
+    /// the last one (at most 50), to find every open action.
+    pub pages: u32,
+    /// Epoch start times to load, counting back from the current epoch.
+    pub epochs: u32,
+    /// Time between runs; `None` warms once at startup.
+    pub interval: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmerState {
+    #[default]
+    Disabled,
+    Running,
+    Idle,
+}
+
+/// Progress of the current (or last) warm-up run, as reported by `/health`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WarmerProgress {
+    pub state: WarmerState,
+    pub runs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<&'static str>,
+    pub loaded: u32,
+    pub failed: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_started_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_finished_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct WarmerStatus {
+    progress: Mutex<WarmerProgress>,
+}
+
+impl WarmerStatus {
+    pub fn snapshot(&self) -> WarmerProgress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut WarmerProgress)) {
+        f(&mut self.progress.lock().unwrap());
+    }
+}
+
+/// Loads the pages and aggregates most visitors hit into the cache at startup
+/// and then on a schedule, so nobody waits on a cold participation walk after
+/// a deploy.
+pub struct CacheWarmer {
+    router: CachedProviderRouter,
+    config: WarmerConfig,
+    status: Arc<WarmerStatus>,
+}
+
+impl CacheWarmer {
+    pub fn new(
+        router: CachedProviderRouter,
+        config: WarmerConfig,
+        status: Arc<WarmerStatus>,
+    ) -> Self {
+        Self {
+            router,
+            config,
+            status,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        self.status.update(|p| p.state = WarmerState::Idle);
+        tokio::spawn(self.run())
+    }
+
+    async fn run(self) {
+        loop {
+            self.warm().await;
+            match self.config.interval {
+                Some(interval) => tokio::time::sleep(interval).await,
+                None => return,
+            }
+        }
+    }
+
+    async fn warm(&self) {
+        let started = Instant::now();
+        self.status.update(|p| {
+            p.state = WarmerState::Running;
+            p.runs += 1;
+            p.loaded = 0;
+            p.failed = 0;
+            p.last_started_at = Some(unix_now());
+        });
+
+        self.step("dreps");
+        for page in 1..=self.config.pages {
+            let query = DRepsQuery {
+                page,
+                count: PAGE_SIZE,
+                ..Default::default()
+            };
+            self.record(self.router.get_dreps_page(&query).await);
+        }
+
+        self.step("drep_stats");
+        self.record(self.router.get_drep_stats().await);
+
+        // Open actions can sit on any page, so the list is walked to its end
+        // (or an empty page) rather than stopping after `pages`
+        self.step("actions");
+        let mut active = Vec::new();
+        for page in 1..=MAX_ACTION_PAGES {
+            let result = self
+                .router
+                .get_governance_actions_page(page, PAGE_SIZE)
+                .await;
+            let has_more = match &result {
+                Ok(actions_page) => {
+                    active.extend(
+                        actions_page
+                            .actions
+                            .iter()
+                            .filter(|action| is_open(action))
+                            .map(|action| action.action_id.clone()),
+                    );
+                    actions_page.has_more && !actions_page.actions.is_empty()
+                }
+                Err(_) => false,
+            };
+            self.record(result);
+            if !has_more {
+                break;
+            }
+        }
+
+        self.step("action_votes");
+        let open_actions = active.len();
+        let mut fetches = stream::iter(active)
+            .map(|id| {
+                let router = self.router.clone();
+                async move {
+                    let votes = router.get_action_voting_results(&id).await.map(|_| ());
+                    let participation =
+                        router.get_action_voter_participation(&id).await.map(|_| ());
+                    [votes, participation]
+                }
+            })
+            .buffer_unordered(ACTION_CONCURRENCY);
+        while let Some(results) = fetches.next().await {
+            for result in results {
+                self.record(result);
+            }
+        }
+
+        self.step("epoch_start_times");
+        match self.router.get_chain_tip().await {
+            Ok(Some(tip)) => {
+                let first = tip
+                    .epoch_no
+                    .saturating_sub(self.config.epochs.saturating_sub(1));
+                for epoch in first..=tip.epoch_no {
+                    let start = self.router.get_epoch_start_time_cached(epoch).await;
+                    self.record(start.ok_or_else(|| {
+                        ProviderError::NotFound(format!("epoch {} start time", epoch))
+                    }));
+                }
+            }
+            Ok(None) => {}
+            Err(error) => self.record::<()>(Err(error)),
+        }
+
+        let elapsed = started.elapsed();
+        self.status.update(|p| {
+            p.state = WarmerState::Idle;
+            p.step = None;
+            p.last_finished_at = Some(unix_now());
+            p.last_duration_ms = Some(elapsed.as_millis() as u64);
+        });
+        let progress = self.status.snapshot();
+        info!(
+            "Cache warm-up finished in {:.1}s: {} loaded, {} failed ({} open actions)",
+            elapsed.as_secs_f64(),
+            progress.loaded,
+            progress.failed,
+            open_actions
+        );
+    }
+
+    fn step(&self, step: &'static str) {
+        debug!("Cache warm-up: {}", step);
+        self.status.update(|p| p.step = Some(step));
+    }
+
+    fn record<T>(&self, result: Result<T, ProviderError>) {
+        match result {
+            Ok(_) => self.status.update(|p| p.loaded += 1),
+            Err(error) => {
+                debug!("Cache warm-up fetch failed: {}", error);
+                self.status.update(|p| p.failed += 1);
+            }
+        }
+    }
+}
+
+// Actions still collecting votes
+fn is_open(action: &GovernanceAction) -> bool {
+    matches!(
+        action.status.as_deref(),
+        Some("voting" | "submitted" | "active")
+    )
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::health::health_check;
+    use crate::cache::keys::CacheKey;
+    use crate::cache::CacheManager;
+    use crate::network::Network;
+    use crate::providers::routing::{ProviderKind, RoutingPolicy};
+    use crate::providers::testing::StubProvider;
+    use crate::providers::ProviderRouter;
+    use crate::state::{AppState, NetworkState};
+    use axum::extract::State;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    fn action(id: &str, status: &str) -> Value {
+        json!({
+            "tx_hash": format!("{:064x}", id.len()),
+            "action_id": id,
+            "type": "info_action",
+            "status": status,
+        })
+    }
+
+    // Two action pages; the only open action is on the second
+    fn router() -> CachedProviderRouter {
+        let koios = StubProvider::new()
+            .answer(
+                "actions_page:1",
+                json!({ "actions": [action("gov_action_enacted", "enacted")], "has_more": true }),
+            )
+            .answer(
+                "actions_page:2",
+                json!({ "actions": [action("gov_action_open", "voting")], "has_more": false }),
+            )
+            .answer("gov_action_open", action("gov_action_open", "voting"));
+        stubbed(koios)
+    }
+
+    fn stubbed(koios: StubProvider) -> CachedProviderRouter {
+        let router = ProviderRouter::stubbed(
+            RoutingPolicy::default(),
+            &[(ProviderKind::Koios, Arc::new(koios))],
+        );
+        let cache = CacheManager::new(true, 1000, HashMap::new(), None);
+        CachedProviderRouter::new(router, cache, None, None, Network::Preview)
+    }
+
+    fn warmer(router: &CachedProviderRouter, status: &Arc<WarmerStatus>) -> CacheWarmer {
+        let config = WarmerConfig {
+            pages: 1,
+            epochs: 2,
+            interval: None,
+        };
+        CacheWarmer::new(router.clone(), config, status.clone())
+    }
+
+    fn actions_page(page: u32) -> CacheKey {
+        CacheKey::ActionsPage {
+            page,
+            count: PAGE_SIZE,
+        }
+    }
+
+    async fn cached(router: &CachedProviderRouter, key: CacheKey) -> bool {
+        router.cache().inspect(&key.to_string()).await.is_some()
+    }
+
+    #[tokio::test]
+    async fn warms_open_actions_past_the_configured_pages() {
+        let router = router();
+        warmer(&router, &Arc::default()).warm().await;
+
+        let open = || "gov_action_open".to_string();
+        assert!(cached(&router, CacheKey::ActionVotes { id: open() }).await);
+        assert!(cached(&router, CacheKey::ActionParticipation { id: open() }).await);
+        assert!(
+            !cached(
+                &router,
+                CacheKey::ActionVotes {
+                    id: "gov_action_enacted".to_string()
+                }
+            )
+            .await
+        );
+        assert!(
+            cached(
+                &router,
+                CacheKey::DRepsPage {
+                    page: 1,
+                    count: PAGE_SIZE,
+                    filters: None
+                }
+            )
+            .await
+        );
+        assert!(cached(&router, CacheKey::DRepStats).await);
+    }
+
+    #[tokio::test]
+    async fn reports_progress_in_health() {
+        let router = router();
+        let status = Arc::new(WarmerStatus::default());
+        assert_eq!(status.snapshot().state, WarmerState::Disabled);
+
+        warmer(&router, &status).warm().await;
+        warmer(&router, &status).warm().await;
+
+        let progress = status.snapshot();
+        assert_eq!(progress.state, WarmerState::Idle);
+        assert_eq!(progress.runs, 2);
+        assert_eq!(progress.step, None);
+        // One DRep page, DRep stats, two action pages, and the open action's
+        // votes and participation
+        assert_eq!(progress.loaded, 6);
+        // Nothing answers the Koios chain tip, so no epoch start times
+        assert_eq!(progress.failed, 1);
+        assert!(progress.last_started_at.is_some());
+        assert!(progress.last_finished_at >= progress.last_started_at);
+
+        let state = AppState {
+            default_network: Network::Preview,
+            networks: Arc::new(HashMap::from([(
+                Network::Preview,
+                NetworkState {
+                    router,
+                    warmer: status,
+                },
+            )])),
+        };
+        let health = health_check(State(state)).await.unwrap().0;
+        let warmer = &health["cache"]["warmer"];
+        assert_eq!(warmer["state"], "idle");
+        assert_eq!(warmer["runs"], 2);
+        assert_eq!(warmer["loaded"], 6);
+        assert_eq!(warmer["failed"], 1);
+        assert!(warmer.get("step").is_none());
+        assert!(warmer["last_duration_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn stops_at_an_empty_action_page() {
+        // The empty second page still claims there is more
+        let koios = StubProvider::new()
+            .answer(
+                "actions_page:1",
+                json!({ "actions": [action("gov_action_enacted", "enacted")], "has_more": true }),
+            )
+            .answer("actions_page:2", json!({ "actions": [], "has_more": true }))
+            .answer(
+                "actions_page:3",
+                json!({ "actions": [action("gov_action_open", "voting")], "has_more": false }),
+            );
+        let router = stubbed(koios);
+        warmer(&router, &Arc::default()).warm().await;
+
+        assert!(cached(&router, actions_page(1)).await);
+        assert!(!cached(&router, actions_page(3)).await);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_action_pages() {
+        let mut koios = StubProvider::new();
+        for page in 1..=MAX_ACTION_PAGES + 1 {
+            let id = format!("gov_action_{}", page);
+            koios = koios.answer(
+                &format!("actions_page:{}", page),
+                json!({ "actions": [action(&id, "enacted")], "has_more": true }),
+            );
+        }
+        let router = stubbed(koios);
+        warmer(&router, &Arc::default()).warm().await;
+
+        assert!(cached(&router, actions_page(MAX_ACTION_PAGES)).await);
+        assert!(!cached(&router, actions_page(MAX_ACTION_PAGES + 1)).await);
+    }
+}
@@ -0,0 +1,489 @@
+use crate::cache::keys::CacheKeyKind;
+use crate::cache::CacheManager;
+use crate::models::{ChainTip, RecentVote};
+use crate::providers::CachedProviderRouter;
+use crate::utils::drep_id::drep_credential_hash;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+// Rows per /vote_list request; a full page means votes may have been missed
+const VOTE_LIST_LIMIT: u32 = 1000;
+// Past this many blocks behind, drop every vote entry instead of listing votes
+const MAX_BLOCK_GAP: u64 = 180;
+// Koios indexes /vote_list behind /tip, so the most recent blocks are listed
+// again on the next poll in case their votes weren't there yet
+const VOTE_LIST_OVERLAP: u64 = 5;
+
+/// Entries that change whenever a vote lands on an action
+const VOTE_KINDS: [CacheKeyKind; 3] = [
+    CacheKeyKind::ActionVotes,
+    CacheKeyKind::ActionParticipation,
+    CacheKeyKind::DRepVotingHistory,
+];
+/// Entries derived from per-epoch snapshots (voting power, statuses, expiry)
+const EPOCH_KINDS: [CacheKeyKind; 3] = [
+    CacheKeyKind::DRepStats,
+    CacheKeyKind::DRepsPage,
+    CacheKeyKind::Action,
+];
+
+/// Polls the chain tip and invalidates cache entries that a new block or
+/// epoch made out of date, so vote tallies update within a block of a vote
+/// landing instead of waiting out their TTL.
+pub struct ChainTipWatcher {
+    router: CachedProviderRouter,
+    interval: Duration,
+    tip: Option<ChainTip>,
+    // Block after which votes are listed on the next poll
+    votes_applied_to: u64,
+}
+
+impl ChainTipWatcher {
+    pub fn new(router: CachedProviderRouter, interval: Duration) -> Self {
+        Self {
+            router,
+            interval,
+            tip: None,
+            votes_applied_to: 0,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(mut self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            self.poll().await;
+        }
+    }
+
+    async fn poll(&mut self) {
+        let tip = match self.router.get_chain_tip().await {
+            Ok(Some(tip)) => tip,
+            Ok(None) => return,
+            Err(error) => {
+                debug!("Chain tip poll failed: {}", error);
+                return;
+            }
+        };
+
+        let Some(previous) = self.tip.replace(tip.clone()) else {
+            info!(
+                "Watching chain tip from block {} (epoch {})",
+                tip.block_height, tip.epoch_no
+            );
+            self.advance_votes_cursor(&tip, None);
+            return;
+        };
+
+        if tip.epoch_no != previous.epoch_no {
+            info!(
+                "Epoch {} started, invalidating epoch-dependent cache entries",
+                tip.epoch_no
+            );
+            self.router
+                .cache()
+                .invalidate_where(&EPOCH_KINDS, |_| true)
+                .await;
+        }
+
+        if tip.block_height > self.votes_applied_to {
+            self.apply_votes(&tip).await;
+        }
+    }
+
+    async fn apply_votes(&mut self, tip: &ChainTip) {
+        let cache = self.router.cache();
+
+        if tip.block_height - self.votes_applied_to > MAX_BLOCK_GAP {
+            info!(
+                "Chain tip jumped {} blocks, invalidating all vote entries",
+                tip.block_height - self.votes_applied_to
+            );
+            cache.invalidate_where(&VOTE_KINDS, |_| true).await;
+            self.advance_votes_cursor(tip, None);
+            return;
+        }
+
+        let votes = match self
+            .router
+            .get_votes_since(self.votes_applied_to, VOTE_LIST_LIMIT)
+            .await
+        {
+            Ok(votes) => votes,
+            Err(error) => {
+                // Leave the cursor where it is and pick these blocks up next poll
+                debug!(
+                    "Failed to list votes since block {}: {}",
+                    self.votes_applied_to, error
+                );
+                return;
+            }
+        };
+
+        if votes.len() >= VOTE_LIST_LIMIT as usize {
+            cache.invalidate_where(&VOTE_KINDS, |_| true).await;
+        } else if !votes.is_empty() {
+            invalidate_votes(cache, &votes).await;
+        }
+        let listed_to = votes.iter().map(|vote| vote.block_height).max();
+        self.advance_votes_cursor(tip, listed_to);
+    }
+
+    /// Moves the cursor past the highest block `/vote_list` returned votes
+    /// for, and past every block old enough that Koios has indexed its votes,
+    /// but never onto the last `VOTE_LIST_OVERLAP` blocks of the tip.
+    fn advance_votes_cursor(&mut self, tip: &ChainTip, listed_to: Option<u64>) {
+        let settled = tip.block_height.saturating_sub(VOTE_LIST_OVERLAP);
+        self.votes_applied_to = self
+            .votes_applied_to
+            .max(settled)
+            .max(listed_to.unwrap_or(0));
+    }
+}
+
+/// Drops the vote-dependent cache entries of the actions and DReps `votes`
+/// touch.
+pub async fn invalidate_votes(cache: &CacheManager, votes: &[RecentVote]) {
+    let affected = AffectedEntries::from_votes(votes);
+    debug!(
+        "{} new votes on {} actions, invalidating their cache entries",
+        votes.len(),
+        affected.proposal_ids.len() + affected.tx_hashes.len()
+    );
+    cache
+        .invalidate_where(&VOTE_KINDS, move |key| affected.matches(key))
+        .await;
+}
+
+/// Actions and DReps touched by a batch of votes, matched against cache keys
+/// in whichever ID format the request used.
+struct AffectedEntries {
+    proposal_ids: HashSet<String>,
+    tx_hashes: HashSet<String>,
+    drep_hashes: HashSet<String>,
+}
+
+impl AffectedEntries {
+    fn from_votes(votes: &[RecentVote]) -> Self {
+        let mut affected = Self {
+            proposal_ids: HashSet::new(),
+            tx_hashes: HashSet::new(),
+            drep_hashes: HashSet::new(),
+        };
+        for vote in votes {
+            if let Some(id) = &vote.proposal_id {
+                affected.proposal_ids.insert(id.to_lowercase());
+            }
+            if let Some(hash) = &vote.proposal_tx_hash {
+                affected.tx_hashes.insert(hash.to_lowercase());
+            }
+            if vote.voter_role.eq_ignore_ascii_case("drep") {
+                if let Some(hash) = drep_credential_hash(&vote.voter_id) {
+                    affected.drep_hashes.insert(hash);
+                }
+            }
+        }
+        affected
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        let Some((_, id)) = key.split_once(':') else {
+            return false;
+        };
+
+        match CacheKeyKind::of(key) {
+            Some(CacheKeyKind::DRepVotingHistory) => {
+                drep_credential_hash(id).is_some_and(|hash| self.drep_hashes.contains(&hash))
+            }
+            Some(_) => {
+                let id = id.to_lowercase();
+                self.proposal_ids.contains(&id)
+                    || self.tx_hashes.iter().any(|hash| id.starts_with(hash))
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys::CacheKey;
+    use crate::network::Network;
+    use crate::providers::health::BreakerConfig;
+    use crate::providers::routing::RoutingPolicy;
+    use crate::providers::{
+        BlockfrostProvider, KoiosProvider, ProviderRouter, RetryConfig, RetryPolicy,
+    };
+    use crate::utils::bech32::encode_bech32;
+    use axum::extract::{Query, State};
+    use axum::{routing::get, Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const PROPOSAL_TX: &str = "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90";
+    const DREP_HASH: &str = "0123456789abcdef0123456789abcdef0123456789abcdef01234567";
+
+    fn vote(proposal_id: Option<&str>, tx_hash: Option<&str>, block_height: u64) -> RecentVote {
+        RecentVote {
+            proposal_id: proposal_id.map(|s| s.to_string()),
+            proposal_tx_hash: tx_hash.map(|s| s.to_string()),
+            voter_role: "DRep".to_string(),
+            voter_id: encode_bech32("drep", &hex::decode(DREP_HASH).unwrap()).unwrap(),
+            block_height,
+        }
+    }
+
+    #[test]
+    fn matches_by_proposal_id_tx_hash_and_drep_credential() {
+        let affected = AffectedEntries::from_votes(&[
+            vote(Some("gov_action1abc"), None, 10),
+            vote(None, Some(&PROPOSAL_TX.to_uppercase()), 10),
+        ]);
+
+        assert!(affected.matches("action_votes:gov_action1abc"));
+        assert!(affected.matches("action_participation:GOV_ACTION1ABC"));
+        assert!(!affected.matches("action_votes:gov_action1other"));
+
+        // Actions looked up as `<tx_hash>#<index>`
+        assert!(affected.matches(&format!("action_votes:{}#0", PROPOSAL_TX)));
+        assert!(affected.matches(&format!("action_participation:{}#1", PROPOSAL_TX)));
+        assert!(!affected.matches(&format!("action_votes:{:064x}#0", 1)));
+
+        // The voting DRep's history, by hex or bech32 ID, but not another DRep's
+        let bech32 = encode_bech32("drep", &hex::decode(DREP_HASH).unwrap()).unwrap();
+        assert!(affected.matches(&format!("drep_votes:{}", DREP_HASH)));
+        assert!(affected.matches(&format!("drep_votes:{}", bech32)));
+        assert!(!affected.matches(&format!("drep_votes:{:056x}", 1)));
+
+        assert!(!affected.matches("dreps_stats"));
+    }
+
+    #[test]
+    fn only_drep_votes_touch_voting_histories() {
+        let mut spo = vote(Some("gov_action1abc"), None, 10);
+        spo.voter_role = "SPO".to_string();
+        spo.voter_id = DREP_HASH.to_string();
+        let affected = AffectedEntries::from_votes(&[spo]);
+
+        assert!(affected.matches("action_votes:gov_action1abc"));
+        assert!(!affected.matches(&format!("drep_votes:{}", DREP_HASH)));
+    }
+
+    // What the Koios stand-in answers with, and how often /vote_list was asked
+    #[derive(Default)]
+    struct Chain {
+        epoch: u32,
+        height: u64,
+        // Votes /vote_list has indexed, as (block height, proposal ID)
+        votes: Vec<(u64, String)>,
+        vote_list_calls: usize,
+    }
+
+    type Shared = Arc<Mutex<Chain>>;
+
+    async fn tip(State(chain): State<Shared>) -> Json<Value> {
+        let chain = chain.lock().unwrap();
+        Json(json!([{
+            "hash": format!("{:064x}", chain.height),
+            "epoch_no": chain.epoch,
+            "abs_slot": chain.height * 20,
+            "block_height": chain.height,
+        }]))
+    }
+
+    async fn vote_list(
+        State(chain): State<Shared>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Json<Value> {
+        let mut chain = chain.lock().unwrap();
+        chain.vote_list_calls += 1;
+        let after: u64 = query["block_height"]
+            .strip_prefix("gt.")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let limit: usize = query["limit"].parse().unwrap();
+        let rows: Vec<Value> = chain
+            .votes
+            .iter()
+            .filter(|(height, _)| *height > after)
+            .take(limit)
+            .map(|(height, proposal_id)| {
+                json!({
+                    "proposal_id": proposal_id,
+                    "voter_role": "DRep",
+                    "voter_id": DREP_HASH,
+                    "block_height": height,
+                })
+            })
+            .collect();
+        Json(Value::Array(rows))
+    }
+
+    async fn watcher(chain: &Shared) -> ChainTipWatcher {
+        let app = Router::new()
+            .route("/tip", get(tip))
+            .route("/vote_list", get(vote_list))
+            .with_state(chain.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let retry = || {
+            RetryPolicy::new(RetryConfig {
+                max_retries: 0,
+                ..RetryConfig::default()
+            })
+        };
+        let router = ProviderRouter::new(
+            Arc::new(BlockfrostProvider::new(
+                "http://127.0.0.1:9".to_string(),
+                String::new(),
+                retry(),
+            )),
+            Arc::new(KoiosProvider::new(url, None, retry())),
+            BreakerConfig::default(),
+            RoutingPolicy::default(),
+        );
+        let cache = CacheManager::new(true, 100, HashMap::new(), None);
+        let router = CachedProviderRouter::new(router, cache, None, None, Network::Preview);
+        ChainTipWatcher::new(router, Duration::from_secs(20))
+    }
+
+    fn action_votes(id: &str) -> CacheKey {
+        CacheKey::ActionVotes { id: id.to_string() }
+    }
+
+    async fn cached(watcher: &ChainTipWatcher, key: &CacheKey) -> bool {
+        watcher
+            .router
+            .cache()
+            .inspect(&key.to_string())
+            .await
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn new_epoch_drops_epoch_dependent_entries() {
+        let chain = Shared::default();
+        chain.lock().unwrap().epoch = 500;
+        chain.lock().unwrap().height = 1000;
+        let mut watcher = watcher(&chain).await;
+        watcher.poll().await;
+
+        let cache = watcher.router.cache();
+        cache.set(&CacheKey::DRepStats, &"stats").await;
+        cache.set(&action_votes("gov_action1abc"), &"votes").await;
+
+        chain.lock().unwrap().height = 1001;
+        watcher.poll().await;
+        assert!(cached(&watcher, &CacheKey::DRepStats).await);
+
+        chain.lock().unwrap().epoch = 501;
+        chain.lock().unwrap().height = 1002;
+        watcher.poll().await;
+        assert!(!cached(&watcher, &CacheKey::DRepStats).await);
+        assert!(cached(&watcher, &action_votes("gov_action1abc")).await);
+    }
+
+    #[tokio::test]
+    async fn long_gap_drops_every_vote_entry_without_listing_votes() {
+        let chain = Shared::default();
+        chain.lock().unwrap().height = 1000;
+        let mut watcher = watcher(&chain).await;
+        watcher.poll().await;
+        let calls = chain.lock().unwrap().vote_list_calls;
+
+        let cache = watcher.router.cache();
+        cache.set(&action_votes("gov_action1abc"), &"votes").await;
+        cache.set(&CacheKey::DRepStats, &"stats").await;
+
+        chain.lock().unwrap().height = 1000 + MAX_BLOCK_GAP + 1;
+        watcher.poll().await;
+
+        assert!(!cached(&watcher, &action_votes("gov_action1abc")).await);
+        assert!(cached(&watcher, &CacheKey::DRepStats).await);
+        assert_eq!(chain.lock().unwrap().vote_list_calls, calls);
+    }
+
+    #[tokio::test]
+    async fn full_vote_page_drops_every_vote_entry() {
+        let chain = Shared::default();
+        chain.lock().unwrap().height = 1000;
+        let mut watcher = watcher(&chain).await;
+        watcher.poll().await;
+
+        let cache = watcher.router.cache();
+        cache.set(&action_votes("gov_action1abc"), &"votes").await;
+        cache.set(&action_votes("gov_action1other"), &"votes").await;
+
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.height = 1010;
+            chain.votes = (0..VOTE_LIST_LIMIT)
+                .map(|_| (1005, "gov_action1abc".to_string()))
+                .collect();
+        }
+        watcher.poll().await;
+
+        assert!(!cached(&watcher, &action_votes("gov_action1abc")).await);
+        assert!(!cached(&watcher, &action_votes("gov_action1other")).await);
+    }
+
+    #[tokio::test]
+    async fn votes_drop_only_the_entries_they_touch() {
+        let chain = Shared::default();
+        chain.lock().unwrap().height = 1000;
+        let mut watcher = watcher(&chain).await;
+        watcher.poll().await;
+
+        let cache = watcher.router.cache();
+        cache.set(&action_votes("gov_action1abc"), &"votes").await;
+        cache.set(&action_votes("gov_action1other"), &"votes").await;
+
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.height = 1002;
+            chain.votes = vec![(1001, "gov_action1abc".to_string())];
+        }
+        watcher.poll().await;
+
+        assert!(!cached(&watcher, &action_votes("gov_action1abc")).await);
+        assert!(cached(&watcher, &action_votes("gov_action1other")).await);
+    }
+
+    #[tokio::test]
+    async fn vote_indexed_after_its_block_reached_the_tip_is_still_applied() {
+        let chain = Shared::default();
+        chain.lock().unwrap().height = 1000;
+        let mut watcher = watcher(&chain).await;
+        watcher.poll().await;
+
+        // Block 1001 is the tip, but /vote_list hasn't indexed its vote yet
+        chain.lock().unwrap().height = 1001;
+        watcher.poll().await;
+
+        watcher
+            .router
+            .cache()
+            .set(&action_votes("gov_action1abc"), &"votes")
+            .await;
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.height = 1002;
+            chain.votes = vec![(1001, "gov_action1abc".to_string())];
+        }
+        watcher.poll().await;
+
+        assert!(!cached(&watcher, &action_votes("gov_action1abc")).await);
+    }
+}
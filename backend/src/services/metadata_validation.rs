@@ -192,7 +192,7 @@ impl MetadataValidator {
             Some(url) if url.starts_with("ipfs://") => {
                 CheckOutcome::pass("Metadata hosted on IPFS")
             }
-            Some(url) => CheckOutcome::fail(&format!(
+            Some(url) => CheckOutcome::fail(format!(
                 "Metadata URI uses '{}' scheme; expected ipfs://",
                 url.split(':').next().unwrap_or("unknown")
             )),
@@ -235,7 +235,7 @@ impl MetadataValidator {
                 }
             }
             Err(error) => (
-                CheckOutcome::fail(&format!("Failed to validate metadata hash: {}", error)),
+                CheckOutcome::fail(format!("Failed to validate metadata hash: {}", error)),
                 None,
             ),
         }
@@ -366,7 +366,7 @@ impl VerifierError {
             VerifierError::InvalidResponse => CheckOutcome::warning(
                 "Unexpected response from Cardano Foundation author witness verifier",
             ),
-            VerifierError::ApiFailure(status) => CheckOutcome::warning(&format!(
+            VerifierError::ApiFailure(status) => CheckOutcome::warning(format!(
                 "Cardano Foundation author witness verifier returned status {}",
                 status.as_u16()
             )),
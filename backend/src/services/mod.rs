@@ -1 +1,3 @@
+pub mod cache_warmer;
+pub mod chain_tip;
 pub mod metadata_validation;
@@ -0,0 +1,65 @@
+use crate::api::error::ApiError;
+use crate::network::Network;
+use crate::providers::CachedProviderRouter;
+use crate::services::cache_warmer::WarmerStatus;
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Providers, cache and warm-up status for one served network.
+#[derive(Clone)]
+pub struct NetworkState {
+    pub router: CachedProviderRouter,
+    pub warmer: Arc<WarmerStatus>,
+}
+
+/// Shared state for every handler. Handlers extract the `NetworkState` (or
+/// just its router) for the network the request was scoped to.
+#[derive(Clone)]
+pub struct AppState {
+    pub default_network: Network,
+    pub networks: Arc<HashMap<Network, NetworkState>>,
+}
+
+impl AppState {
+    /// Served networks, the default one first.
+    pub fn networks(&self) -> Vec<(&Network, &NetworkState)> {
+        let mut networks: Vec<_> = self.networks.iter().collect();
+        networks.sort_by_key(|(network, _)| {
+            (
+                *network != &self.default_network,
+                network.as_str().to_string(),
+            )
+        });
+        networks
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for NetworkState {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        // Set by `network::scope_network`
+        let network = parts
+            .extensions
+            .get::<Network>()
+            .unwrap_or(&state.default_network);
+        state
+            .networks
+            .get(network)
+            .cloned()
+            .ok_or_else(|| ApiError::not_found(format!("Network {}", network)))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for CachedProviderRouter {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        NetworkState::from_request_parts(parts, state)
+            .await
+            .map(|network| network.router)
+    }
+}
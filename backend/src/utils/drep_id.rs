@@ -25,6 +25,16 @@ pub fn decode_drep_id_to_hex(bech_id: &str) -> Result<String, anyhow::Error> {
     Ok(hex::encode(bytes))
 }
 
+/// The 28-byte credential hash of a DRep ID in CIP-105, CIP-129 or hex form.
+pub fn drep_credential_hash(id: &str) -> Option<String> {
+    let hex = if id.chars().all(|c| c.is_ascii_hexdigit()) {
+        id.to_lowercase()
+    } else {
+        decode_drep_id_to_hex(id).ok()?.to_lowercase()
+    };
+    hex.get(hex.len().checked_sub(56)?..).map(|s| s.to_string())
+}
+
 pub fn is_hex_id_cip105(hex_id: &str) -> bool {
     hex_id.len() == 56
 }
@@ -1,3 +1,5 @@
+use crate::utils::bech32::{decode_bech32, encode_bech32};
+
 #[allow(dead_code)]
 pub fn is_cip129_proposal_id(proposal_id: &str) -> bool {
     proposal_id.starts_with("gov_action1")
@@ -85,3 +87,22 @@ pub fn extract_tx_hash_and_index(proposal_id: &str) -> Option<(String, u32)> {
 pub fn format_proposal_id(tx_hash: &str, cert_index: u32) -> String {
     format!("{}#{}", tx_hash, cert_index)
 }
+
+/// CIP-129 `gov_action1...` id: the proposal's tx hash followed by its index.
+pub fn to_cip129_proposal_id(tx_hash: &str, cert_index: u32) -> Result<String, anyhow::Error> {
+    let mut bytes = hex::decode(tx_hash)?;
+    let index = u8::try_from(cert_index)
+        .map_err(|_| anyhow::anyhow!("proposal index {} does not fit CIP-129", cert_index))?;
+    bytes.push(index);
+    encode_bech32("gov_action", &bytes)
+}
+
+/// The tx hash and index of a proposal id in CIP-129 or `tx_hash#index` form.
+pub fn resolve_proposal_id(proposal_id: &str) -> Option<(String, u32)> {
+    if !is_cip129_proposal_id(proposal_id) {
+        return extract_tx_hash_and_index(proposal_id);
+    }
+    let (_, bytes) = decode_bech32(proposal_id).ok()?;
+    let (index, hash) = bytes.split_last()?;
+    (hash.len() == 32).then(|| (hex::encode(hash), u32::from(*index)))
+}
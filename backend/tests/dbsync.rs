@@ -0,0 +1,233 @@
+//! Runs `DbSyncProvider` against `fixtures/dbsync.sql` seeded into a
+//! PostgreSQL database. Ignored by default; run them with `DBSYNC_TEST_URL`
+//! pointing at one, e.g.
+//!
+//!   DBSYNC_TEST_URL=postgres://postgres@localhost/postgres \
+//!     cargo test --features dbsync --test dbsync -- --ignored
+//!
+//! Each test seeds its own schema, which is dropped and recreated first;
+//! nothing outside the `dbsync_test_*` schemas is touched.
+#![cfg(feature = "dbsync")]
+
+use govtwool_backend::models::*;
+use govtwool_backend::providers::{DbSyncProvider, Provider};
+use tokio_postgres::NoTls;
+
+// The seeded treasury withdrawal, sha256("tx-proposal")#0
+const ACTION_ID: &str = "gov_action1q7vpcfc48flhkxd46ruq72erun8tdqegts5d9tcqra6s3zd4jv2sq3u7efk";
+const PROPOSAL_TX: &str = "07981c27153a7f7b19b5d0f80f2b23e4ceb683285c28d2af001f750889b59315";
+const DREP_A: &str = "drep1yu3gkqnl48r0tj6mat80vltstx0t3deqmhfq3855pfm56dcf8xv";
+const DREP_B: &str = "drep1862y6kn7wjyhvh5rdee2ylw0p54wvy69mr77jvc8saxl7euewpw";
+const STAKE: &str = "stake_test1uz85fz55056p8ehushu60uxj0d6sy4u7k8ml04zwf6cdu7szkju4w";
+const POOL: &str = "pool1976dhavh880etvjjercmhazxftx4ajm8lm9w5ra4wa97cxjnqxm";
+const CC_COLD: &str = "e1c554243a0c006d50dcb196a8e6e3f25927cbc9e255b7d6055dae1b";
+const CC_HOT: &str = "81de86639f5905a6c0c3063f69d933f2fa3c06de2fa935bb42e78c34";
+
+/// Seeds the fixture into a fresh `dbsync_test_<name>` schema and returns a
+/// provider whose connections resolve tables there. Panics when
+/// `DBSYNC_TEST_URL` is unset.
+async fn provider(name: &str) -> DbSyncProvider {
+    let url = std::env::var("DBSYNC_TEST_URL").expect("DBSYNC_TEST_URL is not set");
+    let schema = format!("dbsync_test_{}", name);
+
+    let (client, connection) = tokio_postgres::connect(&url, NoTls).await.unwrap();
+    tokio::spawn(connection);
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA IF EXISTS {schema} CASCADE;
+             CREATE SCHEMA {schema};
+             SET search_path TO {schema};
+             {}",
+            include_str!("../fixtures/dbsync.sql")
+        ))
+        .await
+        .unwrap();
+
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let url = format!("{url}{separator}options=-c%20search_path%3D{schema}");
+    DbSyncProvider::connect(&url, 2).unwrap()
+}
+
+fn vote(record: &ActionVoteRecord) -> Option<&str> {
+    match record.vote {
+        Some(VoteChoice::Yes) => Some("yes"),
+        Some(VoteChoice::No) => Some("no"),
+        Some(VoteChoice::Abstain) => Some("abstain"),
+        None => None,
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs DBSYNC_TEST_URL"]
+async fn dreps() {
+    let provider = provider("dreps").await;
+
+    let page = provider
+        .get_dreps_page(&DRepsQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total, Some(2));
+    assert!(!page.has_more);
+    let active = DRepsQuery {
+        statuses: vec!["active".to_string()],
+        ..Default::default()
+    };
+    let page = provider.get_dreps_page(&active).await.unwrap();
+    assert_eq!(page.dreps.len(), 1);
+    assert_eq!(page.dreps[0].view.as_deref(), Some(DREP_A));
+
+    let drep = provider.get_drep(DREP_A).await.unwrap().unwrap();
+    assert_eq!(drep.status.as_deref(), Some("active"));
+    assert_eq!(drep.voting_power.as_deref(), Some("5000000000"));
+    assert_eq!(drep.given_name.as_deref(), Some("Fixture DRep"));
+    let retired = provider.get_drep(DREP_B).await.unwrap().unwrap();
+    assert_eq!(retired.status.as_deref(), Some("retired"));
+    assert!(provider
+        .get_drep("drep_always_abstain")
+        .await
+        .unwrap()
+        .is_none());
+
+    let metadata = provider.get_drep_metadata(DREP_A).await.unwrap().unwrap();
+    assert_eq!(metadata["body"]["givenName"], "Fixture DRep");
+
+    let delegators = provider.get_drep_delegators(DREP_A).await.unwrap();
+    assert_eq!(delegators.len(), 1);
+    assert_eq!(delegators[0].address, STAKE);
+    assert_eq!(delegators[0].amount, "5000000000");
+
+    // Both votes were cast, newest first
+    let history = provider.get_drep_voting_history(DREP_A).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].vote, "yes");
+    assert_eq!(history[0].proposal_id.as_deref(), Some(ACTION_ID));
+    assert_eq!(history[0].epoch, Some(701));
+
+    // The retired DRep and the predefined options don't count
+    assert_eq!(provider.get_total_active_dreps().await.unwrap(), Some(1));
+}
+
+#[tokio::test]
+#[ignore = "needs DBSYNC_TEST_URL"]
+async fn actions() {
+    let provider = provider("actions").await;
+
+    let page = provider.get_governance_actions_page(1, 10).await.unwrap();
+    assert_eq!(page.total, Some(1));
+    assert_eq!(page.actions[0].action_id, ACTION_ID);
+
+    let action = provider
+        .get_governance_action(ACTION_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(action.tx_hash, PROPOSAL_TX);
+    assert_eq!(action.cert_index, Some(0));
+    assert_eq!(action.r#type, "treasury_withdrawals");
+    assert_eq!(action.status.as_deref(), Some("voting"));
+    assert_eq!(action.proposed_epoch, Some(700));
+    assert_eq!(action.expiration, Some(706));
+    assert_eq!(
+        action.withdrawal.as_ref().map(|w| w.amount.as_str()),
+        Some("250000000000")
+    );
+    // Looked up by `tx#index` too
+    let by_hash = provider
+        .get_governance_action(&format!("{}#0", PROPOSAL_TX))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(by_hash.action_id, ACTION_ID);
+    assert!(provider
+        .get_governance_action(&format!("{}#1", PROPOSAL_TX))
+        .await
+        .unwrap()
+        .is_none());
+
+    // Only the DRep's latest vote counts
+    let mut records = provider.get_action_vote_records(&action).await.unwrap();
+    records.sort_by(|a, b| a.voter_type.cmp(&b.voter_type));
+    let votes: Vec<_> = records
+        .iter()
+        .map(|r| (r.voter_type.as_str(), vote(r), r.voting_power.as_deref()))
+        .collect();
+    assert_eq!(
+        votes,
+        [
+            ("cc", Some("yes"), None),
+            ("drep", Some("yes"), Some("5000000000")),
+            ("spo", Some("abstain"), Some("6200000000")),
+        ]
+    );
+    assert_eq!(records[0].voter_identifier, CC_HOT);
+
+    let results = provider.get_action_voting_results(ACTION_ID).await.unwrap();
+    assert_eq!(results.drep_votes.yes, "5000000000");
+    assert_eq!(results.drep_votes.no, "0");
+    assert_eq!(results.spo_votes.abstain, "6200000000");
+    assert_eq!(results.cc_votes.yes_votes_cast, Some(1));
+    let summary = results.summary.unwrap();
+    assert_eq!(
+        summary.drep_always_abstain_vote_power.as_deref(),
+        Some("800000000")
+    );
+    assert_eq!(
+        summary.drep_always_no_confidence_vote_power.as_deref(),
+        Some("100000000")
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs DBSYNC_TEST_URL"]
+async fn stake() {
+    let provider = provider("stake").await;
+
+    let delegation = provider.get_stake_delegation(STAKE).await.unwrap().unwrap();
+    assert_eq!(delegation.delegated_pool.as_deref(), Some(POOL));
+    assert!(delegation.delegated_drep.is_some());
+    // The output spent by tx 4 is excluded, the withdrawal taken off rewards
+    assert_eq!(delegation.utxo_balance.as_deref(), Some("5000000000"));
+    assert_eq!(delegation.rewards_available.as_deref(), Some("20000000"));
+    assert_eq!(delegation.total_balance.as_deref(), Some("5020000000"));
+    assert!(provider
+        .get_stake_delegation("stake_test1unknown")
+        .await
+        .unwrap()
+        .is_none());
+
+    let pools = provider.get_stake_pools_page(1, 10).await.unwrap();
+    assert_eq!(pools.total, Some(1));
+    assert_eq!(pools.pools[0].pool_id, POOL);
+    assert_eq!(pools.pools[0].ticker.as_deref(), Some("FIXT"));
+    assert_eq!(pools.pools[0].name.as_deref(), Some("Fixture Pool"));
+}
+
+#[tokio::test]
+#[ignore = "needs DBSYNC_TEST_URL"]
+async fn chain() {
+    let provider = provider("chain").await;
+
+    let committee = provider.get_committee_info().await.unwrap();
+    assert_eq!(committee.len(), 1);
+    assert_eq!(committee[0].identifier, CC_COLD);
+    assert_eq!(committee[0].role.as_deref(), Some("authorized"));
+    assert_eq!(committee[0].hot_key.as_deref(), Some(CC_HOT));
+    assert_eq!(committee[0].expiry_epoch, Some(800));
+
+    // 2024-09-20 and 2024-09-21, midnight UTC
+    assert_eq!(
+        provider.get_epoch_start_time(700).await.unwrap(),
+        Some(1726790400)
+    );
+    assert_eq!(
+        provider.get_epoch_start_time(701).await.unwrap(),
+        Some(1726876800)
+    );
+    assert_eq!(provider.get_epoch_start_time(702).await.unwrap(), None);
+
+    assert!(provider.health_check().await.unwrap());
+    assert_eq!(
+        provider.get_network_name().await.unwrap().as_deref(),
+        Some("preview")
+    );
+}
@@ -0,0 +1,217 @@
+//! Runs the API against the recorded preview fixtures in
+//! `fixtures/providers/preview`, with nothing going over the network.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use govtwool_backend::config::Config;
+use govtwool_backend::Backend;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::OnceLock;
+use tower::ServiceExt;
+
+const ACTION_ID: &str = "gov_action1q7vpcfc48flhkxd46ruq72erun8tdqegts5d9tcqra6s3zd4jv2sq3u7efk";
+const PROPOSAL_TX: &str = "07981c27153a7f7b19b5d0f80f2b23e4ceb683285c28d2af001f750889b59315";
+const DREP_ID: &str = "drep1ygnj9zcz075udawtt04vaanawpveaw9hyrwayzy7js98wngc40t62";
+
+// Read once, so tests running in parallel don't race on the environment
+fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/providers");
+        for (name, value) in [
+            ("PROVIDER_FIXTURES", "replay"),
+            ("PROVIDER_FIXTURES_DIR", fixtures.to_str().unwrap()),
+            ("BLOCKFROST_NETWORK", "preview"),
+            ("NETWORKS", ""),
+            ("GOVTOOLS_ENABLED", "true"),
+            ("CACHE_L2_BACKEND", "none"),
+            ("CACHE_SNAPSHOT_PATH", ""),
+            ("CACHE_WARM_ENABLED", "false"),
+            ("CHAIN_TIP_POLL_SECS", "0"),
+            ("CARDANO_VERIFIER_ENABLED", "false"),
+            ("CIRCUIT_FAILURE_THRESHOLD", "2"),
+            ("PROVIDER_MAX_RETRIES", "0"),
+            ("DBSYNC_URL", ""),
+            ("OGMIOS_URL", ""),
+            ("INDEXER_ENABLED", "false"),
+            ("ADMIN_API_TOKEN", ""),
+        ] {
+            std::env::set_var(name, value);
+        }
+        Config::from_env().unwrap()
+    })
+}
+
+async fn app() -> Router {
+    Backend::build(config()).await.unwrap().app()
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn lists_dreps() {
+    let app = app().await;
+    let (status, body) = get(&app, "/api/dreps").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["has_more"], false);
+
+    let dreps = body["dreps"].as_array().unwrap();
+    assert_eq!(dreps.len(), 2);
+    assert_eq!(dreps[0]["drep_id"], DREP_ID);
+    assert_eq!(dreps[0]["status"], "active");
+    assert_eq!(dreps[0]["voting_power"], "12500000000000");
+    assert_eq!(dreps[0]["given_name"], "DRep A");
+    assert_eq!(dreps[1]["status"], "inactive");
+    assert_eq!(dreps[1]["has_script"], true);
+}
+
+#[tokio::test]
+async fn gets_action() {
+    let app = app().await;
+    let (status, body) = get(&app, &format!("/api/actions/{}", ACTION_ID)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["action_id"], ACTION_ID);
+    assert_eq!(body["tx_hash"], PROPOSAL_TX);
+    assert_eq!(body["type"], "treasury_withdrawals");
+    assert_eq!(body["withdrawal"]["amount"], "250000000000");
+    // Start times follow from the preview genesis, without another request
+    assert_eq!(body["proposed_epoch_start_time"], 1727222400);
+    assert_eq!(body["expiration_epoch_start_time"], 1729814400);
+}
+
+#[tokio::test]
+async fn action_is_served_by_koios() {
+    let app = app().await;
+    let requests = |health: &Value, provider: &str| {
+        health["circuits"][provider]["total_requests"]
+            .as_u64()
+            .unwrap()
+    };
+    let (_, before) = get(&app, "/health").await;
+    let (status, body) = get(&app, &format!("/api/actions/{}", ACTION_ID)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tx_hash"], PROPOSAL_TX);
+    assert_eq!(body["status"], "voting");
+
+    // Koios answers first on this route, so Blockfrost is never asked
+    let (_, after) = get(&app, "/health").await;
+    assert_eq!(requests(&after, "koios"), requests(&before, "koios") + 1);
+    assert_eq!(
+        requests(&after, "blockfrost"),
+        requests(&before, "blockfrost")
+    );
+}
+
+#[tokio::test]
+async fn reports_health() {
+    let app = app().await;
+    let (status, body) = get(&app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["network"], "preview");
+    assert_eq!(body["circuits"]["koios"]["circuit"], "closed");
+    assert_eq!(body["circuits"]["blockfrost"]["circuit"], "closed");
+    assert!(body["networks"]["preview"].is_object());
+}
+
+#[tokio::test]
+async fn unrecorded_requests_leave_circuits_closed() {
+    let app = app().await;
+    // The recorded proposal's index 1, which was never requested
+    let missing = "gov_action1q7vpcfc48flhkxd46ruq72erun8tdqegts5d9tcqra6s3zd4jv2szzel66m";
+    // More misses than the failure threshold
+    for _ in 0..3 {
+        let (status, body) = get(&app, &format!("/api/actions/{}", missing)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["code"], "not_recorded");
+    }
+
+    let (_, body) = get(&app, "/health").await;
+    assert_eq!(body["circuits"]["blockfrost"]["circuit"], "closed");
+    assert_eq!(body["circuits"]["blockfrost"]["total_failures"], 0);
+    let (status, _) = get(&app, &format!("/api/actions/{}", ACTION_ID)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn participation_without_vote_records_is_not_cached() {
+    let app = app().await;
+    // The DRep, pool and committee lookups are recorded, the vote records are not
+    let uri = format!("/api/actions/{}/participation", ACTION_ID);
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(Request::get(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-cache-status"], "miss");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["dreps"]["participants"][0]["drep_id"], DREP_ID);
+        assert_eq!(body["dreps"]["participants"][0]["has_voted"], false);
+        assert_eq!(
+            body["stake_pools"]["participants"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            body["committee"]["participants"].as_array().unwrap().len(),
+            1
+        );
+    }
+}
+
+#[tokio::test]
+async fn responses_name_their_network() {
+    let app = app().await;
+    for uri in ["/api/dreps", "/api/preview/dreps", "/health"] {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_eq!(
+            response.headers()["x-cardano-network"],
+            "preview",
+            "{}",
+            uri
+        );
+    }
+
+    let (status, _) = get(&app, "/api/preprod/dreps").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rejects_a_mainnet_stake_address() {
+    let app = app().await;
+    // A mainnet `stake1` address on preview, which only has `stake_test1`
+    let uri = "/api/stake/stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw/delegation";
+    let (status, body) = get(&app, uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["error"].as_str().unwrap().contains("stake_test"),
+        "{}",
+        body
+    );
+}